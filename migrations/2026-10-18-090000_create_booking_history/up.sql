@@ -0,0 +1,13 @@
+-- Your SQL goes here
+CREATE TABLE booking_history (
+    id SERIAL PRIMARY KEY,
+    booking_id INTEGER NOT NULL REFERENCES bookings(id) ON DELETE CASCADE,
+    from_status INTEGER,
+    to_status INTEGER NOT NULL,
+    changed_by_uid VARCHAR(255) NOT NULL,
+    changed_by_role VARCHAR(32) NOT NULL,
+    note TEXT,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_booking_history_booking_id ON booking_history(booking_id);
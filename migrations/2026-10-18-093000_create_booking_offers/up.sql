@@ -0,0 +1,14 @@
+-- Your SQL goes here
+CREATE TABLE booking_offers (
+    id SERIAL PRIMARY KEY,
+    booking_id INTEGER NOT NULL REFERENCES bookings(id) ON DELETE CASCADE,
+    version INTEGER NOT NULL,
+    offered_by_uid VARCHAR(255) NOT NULL,
+    offered_by_role VARCHAR(32) NOT NULL,
+    offering_price FLOAT8 NOT NULL,
+    date_time TIMESTAMPTZ,
+    end_time TIMESTAMPTZ,
+    message TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT unique_booking_offer_version UNIQUE (booking_id, version)
+);
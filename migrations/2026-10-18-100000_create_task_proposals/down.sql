@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS task_status_idx;
+DROP TABLE IF EXISTS task_proposals;
+ALTER TABLE task DROP COLUMN status;
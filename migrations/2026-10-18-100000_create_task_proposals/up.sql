@@ -0,0 +1,16 @@
+-- Your SQL goes here
+ALTER TABLE task ADD COLUMN status INT4 NOT NULL DEFAULT 0;
+
+CREATE TABLE task_proposals (
+    id SERIAL PRIMARY KEY,
+    task_id INT4 NOT NULL REFERENCES task(id) ON DELETE CASCADE,
+    professional_profile_id INT4 NOT NULL REFERENCES professional_profiles(id) ON DELETE CASCADE,
+    price DOUBLE PRECISION NOT NULL,
+    message TEXT,
+    status INT4 NOT NULL DEFAULT 0,
+    booking_id INT4 REFERENCES bookings(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (task_id, professional_profile_id)
+);
+
+CREATE INDEX task_status_idx ON task(status);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE professional_profiles DROP COLUMN review_count;
+
+DROP INDEX IF EXISTS review_booking_id_key;
+ALTER TABLE review DROP COLUMN booking_id;
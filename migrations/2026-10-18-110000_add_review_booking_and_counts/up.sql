@@ -0,0 +1,17 @@
+-- Your SQL goes here
+ALTER TABLE review ADD COLUMN booking_id INT4 REFERENCES bookings(id) ON DELETE SET NULL;
+CREATE UNIQUE INDEX review_booking_id_key ON review(booking_id);
+
+ALTER TABLE professional_profiles ADD COLUMN review_count INT4 NOT NULL DEFAULT 0;
+
+UPDATE professional_profiles
+SET review_count = stats.review_count,
+    average_rating = stats.average_rating
+FROM (
+    SELECT professional_profile_id,
+           COUNT(*) AS review_count,
+           AVG(rate)::double precision AS average_rating
+    FROM review
+    GROUP BY professional_profile_id
+) AS stats
+WHERE professional_profiles.id = stats.professional_profile_id;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS review_reports;
+DROP TABLE IF EXISTS review_replies;
+ALTER TABLE review DROP COLUMN is_hidden;
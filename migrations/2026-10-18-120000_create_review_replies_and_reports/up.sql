@@ -0,0 +1,25 @@
+-- Your SQL goes here
+ALTER TABLE review ADD COLUMN is_hidden BOOLEAN NOT NULL DEFAULT false;
+
+CREATE TABLE review_replies (
+    id SERIAL PRIMARY KEY,
+    review_id INT4 NOT NULL UNIQUE REFERENCES review(id) ON DELETE CASCADE,
+    professional_profile_id INT4 NOT NULL REFERENCES professional_profiles(id) ON DELETE CASCADE,
+    message TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ
+);
+
+CREATE TABLE review_reports (
+    id SERIAL PRIMARY KEY,
+    review_id INT4 NOT NULL REFERENCES review(id) ON DELETE CASCADE,
+    reporter_uid VARCHAR(255) NOT NULL,
+    reason TEXT NOT NULL,
+    status INT4 NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    resolved_at TIMESTAMPTZ,
+    resolved_by_uid VARCHAR(255),
+    UNIQUE (review_id, reporter_uid)
+);
+
+CREATE INDEX review_reports_status_idx ON review_reports(status);
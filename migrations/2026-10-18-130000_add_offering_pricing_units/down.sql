@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE service_offerings
+    DROP COLUMN estimated_duration_minutes,
+    DROP COLUMN pricing_unit;
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+-- 0 = fixed, 1 = hourly, 2 = per square meter
+ALTER TABLE service_offerings
+    ADD COLUMN pricing_unit INT4 NOT NULL DEFAULT 0 CHECK (pricing_unit IN (0, 1, 2)),
+    ADD COLUMN estimated_duration_minutes INT4 CHECK (estimated_duration_minutes > 0);
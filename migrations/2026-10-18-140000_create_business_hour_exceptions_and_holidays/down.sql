@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE public_holidays;
+DROP TABLE business_hour_exceptions;
+DROP INDEX business_hours_profile_idx;
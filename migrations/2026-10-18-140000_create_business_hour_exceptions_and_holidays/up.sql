@@ -0,0 +1,32 @@
+-- Your SQL goes here
+CREATE INDEX business_hours_profile_idx ON business_hours(professional_profile_id);
+
+-- Rows without times close the profile for the whole range.
+CREATE TABLE business_hour_exceptions (
+    id SERIAL PRIMARY KEY,
+    professional_profile_id INT4 NOT NULL REFERENCES professional_profiles(id) ON DELETE CASCADE,
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    opening_time TIME,
+    closing_time TIME,
+    reason VARCHAR(255),
+    CHECK (start_date <= end_date),
+    CHECK ((opening_time IS NULL) = (closing_time IS NULL)),
+    CHECK (opening_time < closing_time)
+);
+
+CREATE INDEX business_hour_exceptions_profile_idx
+    ON business_hour_exceptions(professional_profile_id, end_date);
+
+-- Maintained through the admin holiday endpoints. A NULL state applies
+-- nationwide, otherwise it matches `addresses.state`.
+CREATE TABLE public_holidays (
+    id SERIAL PRIMARY KEY,
+    holiday_date DATE NOT NULL,
+    state VARCHAR(255),
+    name VARCHAR(255) NOT NULL
+);
+
+CREATE UNIQUE INDEX public_holidays_date_state_idx
+    ON public_holidays(holiday_date, COALESCE(state, ''));
+
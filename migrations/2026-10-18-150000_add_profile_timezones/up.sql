@@ -0,0 +1,4 @@
+-- Your SQL goes here
+-- IANA zone the profile's opening hours are given in.
+ALTER TABLE professional_profiles
+    ADD COLUMN timezone VARCHAR(64) NOT NULL DEFAULT 'Europe/Berlin';
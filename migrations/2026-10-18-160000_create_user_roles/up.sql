@@ -0,0 +1,18 @@
+-- Your SQL goes here
+-- 0 = customer, 1 = professional, 2 = admin
+CREATE TABLE user_roles (
+    id SERIAL PRIMARY KEY,
+    user_uid VARCHAR(255) NOT NULL,
+    role INT4 NOT NULL CHECK (role IN (0, 1, 2)),
+    granted_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (user_uid, role)
+);
+
+-- Everyone registered so far keeps the role their registration implied.
+INSERT INTO user_roles (user_uid, role)
+SELECT user_uid, 0 FROM users
+ON CONFLICT DO NOTHING;
+
+INSERT INTO user_roles (user_uid, role)
+SELECT user_uid, 1 FROM professionals
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE professional_profiles DROP COLUMN is_active;
+DROP TABLE IF EXISTS deleted_accounts;
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+-- Accounts whose data was deleted. Their ID and session tokens stay valid
+-- until they expire, so authentication rejects the uids listed here.
+CREATE TABLE deleted_accounts (
+    user_uid VARCHAR(255) PRIMARY KEY,
+    deleted_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Profiles of deleted accounts can no longer be found or booked.
+ALTER TABLE professional_profiles
+    ADD COLUMN is_active BOOLEAN NOT NULL DEFAULT TRUE;
@@ -0,0 +1,235 @@
+use super::{booking_db, task_db};
+use crate::errors::account_errors::AccountError;
+use crate::models::dtos::account_dto::{AccountDeletionDTO, AccountProfileDTO, ProfileUpdateDTO};
+use crate::models::professional_aggregate::professional::Professional;
+use crate::models::user_aggregate::user::User;
+use crate::schema::schema::{
+    deleted_accounts, professional_profiles, professionals, review, user_roles, users,
+};
+use actix_web::web;
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool};
+
+/// Shown instead of the name of a user who deleted their account.
+pub const DELETED_USER_NAME: &str = "Deleted user";
+
+const MAX_FIELD_LENGTH: usize = 255;
+
+/// Updates the name and picture of `user_uid`, both as a customer and as a
+/// professional. A new name is also copied onto the user's profiles.
+pub async fn update_profile(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    user_uid: String,
+    update: ProfileUpdateDTO,
+) -> Result<AccountProfileDTO, AccountError> {
+    let (new_name, new_image_url) = validate_profile_update(update)?;
+
+    web::block(move || -> Result<AccountProfileDTO, AccountError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| AccountError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, AccountError, _>(|conn| {
+            let user = diesel::update(users::table.filter(users::user_uid.eq(&user_uid)))
+                .set((
+                    new_name.clone().map(|name| users::name.eq(name)),
+                    new_image_url.clone().map(|url| users::image_url.eq(url)),
+                ))
+                .get_result::<User>(conn)
+                .optional()?;
+            let professional =
+                diesel::update(professionals::table.filter(professionals::user_uid.eq(&user_uid)))
+                    .set((
+                        new_name.clone().map(|name| professionals::name.eq(name)),
+                        new_image_url
+                            .clone()
+                            .map(|url| professionals::image_url.eq(url)),
+                    ))
+                    .get_result::<Professional>(conn)
+                    .optional()?;
+
+            if let (Some(name), Some(professional)) = (&new_name, &professional) {
+                diesel::update(
+                    professional_profiles::table
+                        .filter(professional_profiles::professional_id.eq(professional.id)),
+                )
+                .set(professional_profiles::professional_name.eq(name))
+                .execute(conn)?;
+            }
+
+            match (user, professional) {
+                (Some(user), _) => Ok(AccountProfileDTO {
+                    user_uid: user.user_uid,
+                    name: user.name,
+                    image_url: user.image_url,
+                }),
+                (None, Some(professional)) => Ok(AccountProfileDTO {
+                    user_uid: professional.user_uid,
+                    name: professional.name,
+                    image_url: professional.image_url,
+                }),
+                (None, None) => Err(AccountError::UserNotFound),
+            }
+        })
+    })
+    .await
+    .map_err(|e| AccountError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Removes what the backend keeps about `user_uid` ahead of deleting its
+/// identity: reviews are anonymized, open tasks closed, pending proposals
+/// withdrawn and upcoming bookings cancelled. The user rows stay because
+/// bookings, tasks and reviews still reference them, but their name, email
+/// and picture are scrubbed and all roles dropped. The user's profiles are
+/// deactivated, and the account is marked deleted so its remaining tokens
+/// are refused.
+///
+/// Everything happens in one transaction and running it again is harmless,
+/// so a deletion that failed at the identity provider can simply be retried.
+pub async fn delete_account_data(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    user_uid: String,
+) -> Result<AccountDeletionDTO, AccountError> {
+    web::block(move || -> Result<AccountDeletionDTO, AccountError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| AccountError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, AccountError, _>(|conn| {
+            let user_ids = users::table
+                .filter(users::user_uid.eq(&user_uid))
+                .select(users::id);
+            let anonymized_reviews =
+                diesel::update(review::table.filter(review::user_id.eq_any(user_ids)))
+                    .set(review::user_name.eq(DELETED_USER_NAME))
+                    .execute(conn)?;
+
+            let closed_tasks = task_db::close_open_tasks(conn, &user_uid)?;
+            let withdrawn_proposals = task_db::withdraw_pending_proposals(conn, &user_uid)?;
+            let cancelled_bookings =
+                booking_db::cancel_upcoming_bookings(conn, &user_uid, "Account deleted")?;
+
+            let scrubbed_email = format!("deleted-{}@invalid", user_uid);
+            diesel::update(users::table.filter(users::user_uid.eq(&user_uid)))
+                .set((
+                    users::name.eq(DELETED_USER_NAME),
+                    users::email.eq(&scrubbed_email),
+                    users::image_url.eq(None::<String>),
+                ))
+                .execute(conn)?;
+            let professional_ids: Vec<i32> =
+                diesel::update(professionals::table.filter(professionals::user_uid.eq(&user_uid)))
+                    .set((
+                        professionals::name.eq(DELETED_USER_NAME),
+                        professionals::email.eq(&scrubbed_email),
+                        professionals::image_url.eq(None::<String>),
+                    ))
+                    .returning(professionals::id)
+                    .get_results(conn)?;
+            diesel::update(
+                professional_profiles::table
+                    .filter(professional_profiles::professional_id.eq_any(professional_ids)),
+            )
+            .set((
+                professional_profiles::professional_name.eq(DELETED_USER_NAME),
+                professional_profiles::is_active.eq(false),
+            ))
+            .execute(conn)?;
+
+            diesel::delete(user_roles::table.filter(user_roles::user_uid.eq(&user_uid)))
+                .execute(conn)?;
+            diesel::insert_into(deleted_accounts::table)
+                .values(deleted_accounts::user_uid.eq(&user_uid))
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+
+            Ok(AccountDeletionDTO {
+                user_uid: user_uid.clone(),
+                anonymized_reviews,
+                closed_tasks,
+                withdrawn_proposals,
+                cancelled_bookings,
+            })
+        })
+    })
+    .await
+    .map_err(|e| AccountError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Whether `user_uid` deleted their account.
+pub fn is_deleted(conn: &mut PgConnection, user_uid: &str) -> QueryResult<bool> {
+    diesel::select(diesel::dsl::exists(deleted_accounts::table.find(user_uid))).get_result(conn)
+}
+
+/// Trims the fields of `update` and checks their lengths. The picture comes
+/// back as `Some(None)` when it should be removed.
+fn validate_profile_update(
+    update: ProfileUpdateDTO,
+) -> Result<(Option<String>, Option<Option<String>>), AccountError> {
+    let name = update.name.map(|name| name.trim().to_string());
+    if let Some(name) = &name {
+        if name.is_empty() || name.chars().count() > MAX_FIELD_LENGTH {
+            return Err(AccountError::InvalidProfile(format!(
+                "name must be between 1 and {} characters",
+                MAX_FIELD_LENGTH
+            )));
+        }
+    }
+
+    let image_url = update.image_url.map(|url| {
+        let url = url.trim().to_string();
+        (!url.is_empty()).then_some(url)
+    });
+    if let Some(Some(url)) = &image_url {
+        if url.chars().count() > MAX_FIELD_LENGTH {
+            return Err(AccountError::InvalidProfile(format!(
+                "image_url must be at most {} characters",
+                MAX_FIELD_LENGTH
+            )));
+        }
+    }
+
+    if name.is_none() && image_url.is_none() {
+        return Err(AccountError::InvalidProfile(
+            "nothing to update".to_string(),
+        ));
+    }
+    Ok((name, image_url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(name: Option<&str>, image_url: Option<&str>) -> ProfileUpdateDTO {
+        ProfileUpdateDTO {
+            name: name.map(String::from),
+            image_url: image_url.map(String::from),
+        }
+    }
+
+    #[test]
+    fn profile_updates_are_trimmed_and_checked() {
+        let (name, image_url) = validate_profile_update(update(Some("  Ada "), Some(" "))).unwrap();
+        assert_eq!(name.as_deref(), Some("Ada"));
+        assert_eq!(image_url, Some(None));
+
+        let (name, image_url) =
+            validate_profile_update(update(None, Some("https://img/a.png"))).unwrap();
+        assert_eq!(name, None);
+        assert_eq!(image_url, Some(Some("https://img/a.png".to_string())));
+
+        let too_long = "x".repeat(MAX_FIELD_LENGTH + 1);
+        for invalid in [
+            update(None, None),
+            update(Some("   "), None),
+            update(Some(&too_long), None),
+            update(None, Some(&too_long)),
+        ] {
+            assert!(matches!(
+                validate_profile_update(invalid),
+                Err(AccountError::InvalidProfile(_))
+            ));
+        }
+    }
+}
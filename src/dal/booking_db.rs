@@ -1,13 +1,47 @@
+use super::business_hours_db;
 use super::chat_db::get_or_create_chat;
 use crate::errors::booking_errors::BookingError;
 use crate::models::booking_aggregate::booking::{Booking, NewBooking};
 use crate::models::booking_aggregate::booking_assignment::NewBookingAssignment;
-use crate::models::dtos::booking_dto::BookingDTO;
-use crate::schema::schema::{booking_assignments, bookings};
+use crate::models::booking_aggregate::booking_history::{BookingHistory, NewBookingHistory};
+use crate::models::booking_aggregate::booking_offer::{BookingOffer, NewBookingOffer};
+use crate::models::booking_aggregate::booking_status::{
+    BookingAction, BookingParty, BookingStatus,
+};
+use crate::models::dtos::booking_dto::{
+    BookingDTO, BookingDetailDTO, BookingSummaryDTO, CounterOfferDTO,
+};
+use crate::models::dtos::professional_profile_detail_dto::ServiceOfferingDTO;
+use crate::models::professional_aggregate::availability::{self, BusySlot, TimeSlot};
+use crate::models::professional_aggregate::service_offering::ServiceOffering;
+use crate::models::task_aggregate::task::Task;
+use crate::models::task_aggregate::task_proposal::TaskProposal;
+use crate::models::user_aggregate::role::Role;
+use crate::policies::ownership;
+use crate::schema::schema::{
+    booking_assignments, booking_history, booking_offers, bookings, chat, professional_profiles,
+    professionals, service_offerings, subcategories,
+};
 use actix_web::web;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
+use diesel::pg::Pg;
 use diesel::prelude::*;
 use diesel::r2d2::{ConnectionManager, Pool};
+use std::collections::HashMap;
+
+/// Assumed length of a booking that was requested without an end time, when
+/// its offering has no estimated duration either.
+const DEFAULT_BOOKING_DURATION_MINUTES: i64 = 60;
+
+/// Optional filters shared by the customer and professional booking lists.
+pub struct BookingFilter {
+    pub statuses: Option<Vec<i32>>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub category_id: Option<i32>,
+    pub limit: i64,
+    pub offset: i64,
+}
 
 pub async fn place_booking(
     db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
@@ -22,34 +56,83 @@ pub async fn place_booking(
                 .get()
                 .map_err(|e| BookingError::DatabasePoolError(e.to_string()))?;
 
-            let date_time: Option<DateTime<Utc>> = booking_dto
-                .date_time
-                .as_ref()
-                .and_then(|dt_str| DateTime::parse_from_rfc3339(dt_str).ok())
-                .map(|dt| dt.with_timezone(&Utc));
+            // The offering has to belong to the profile named in the path
+            // and in the request alike. Its terms are the booking's terms.
+            let (is_active, offering, category_id) = service_offerings::table
+                .inner_join(professional_profiles::table)
+                .inner_join(subcategories::table)
+                .filter(service_offerings::id.eq(booking_dto.offering_id))
+                .filter(service_offerings::professional_profile_id.eq(profile_id))
+                .filter(
+                    professional_profiles::professional_profile_uid
+                        .eq(&booking_dto.professional_profile_uid),
+                )
+                .select((
+                    professional_profiles::is_active,
+                    ServiceOffering::as_select(),
+                    subcategories::category_id,
+                ))
+                .first::<(bool, ServiceOffering, i32)>(&mut conn)
+                .optional()?
+                .ok_or(BookingError::UnknownOffering)?;
+            if !is_active {
+                return Err(BookingError::NotFound);
+            }
 
-            let chat_id =
-                get_or_create_chat(&mut conn, &user_uid, &booking_dto.professional_profile_uid)?;
+            let date_time = parse_timestamp(&booking_dto.date_time)?;
+            let end_time = match (date_time, parse_timestamp(&booking_dto.end_time)?) {
+                (Some(start), None) => {
+                    let duration_minutes = offering
+                        .estimated_duration_minutes
+                        .map_or(DEFAULT_BOOKING_DURATION_MINUTES, i64::from);
+                    Some(start + Duration::minutes(duration_minutes))
+                }
+                (_, end_time) => end_time,
+            };
 
             conn.transaction::<_, BookingError, _>(|conn| {
+                if let (Some(start), Some(end)) = (date_time, end_time) {
+                    validate_slot(
+                        conn,
+                        &booking_dto.professional_profile_uid,
+                        start,
+                        end,
+                        None,
+                    )?;
+                }
+                let chat_id =
+                    get_or_create_chat(conn, &user_uid, &booking_dto.professional_profile_uid)?;
+
                 let new_booking = NewBooking {
                     customer_uid: user_uid.clone(),
                     professional_profile_uid: booking_dto.professional_profile_uid.clone(),
                     date_time,
-                    status: booking_dto.status,
+                    end_time,
+                    status: BookingStatus::Proposed.into(),
                     description: booking_dto.description,
-                    category_id: booking_dto.category_id,
-                    service_offering_id: Some(booking_dto.offering_id),
-                    service_offering_name: Some(booking_dto.service_offering_name),
-                    offering_price: booking_dto.offering_price,
+                    category_id,
+                    service_offering_id: Some(offering.id),
+                    service_offering_name: Some(offering.subcategory_name),
+                    offering_price: offering.price,
                     chat_id,
                     creation_time: Utc::now(),
                 };
 
-                diesel::insert_into(bookings::table)
+                let booking: Booking = diesel::insert_into(bookings::table)
                     .values(&new_booking)
-                    .get_result(conn)
-                    .map_err(BookingError::DieselError)
+                    .get_result(conn)?;
+
+                record_transition(
+                    conn,
+                    booking.id,
+                    None,
+                    BookingStatus::Proposed,
+                    &user_uid,
+                    BookingParty::Customer,
+                    None,
+                )?;
+
+                Ok(booking)
             })
         }
     })
@@ -82,6 +165,70 @@ pub async fn place_booking(
     Ok(booking)
 }
 
+/// Creates the booking for a task whose owner accepted `proposal`. The booking
+/// starts out accepted at the proposed price and takes over the task's
+/// description, schedule and images. Runs on the caller's connection so it can
+/// share the caller's transaction.
+pub fn create_booking_for_task(
+    conn: &mut PgConnection,
+    task: &Task,
+    proposal: &TaskProposal,
+    professional_profile_uid: &str,
+    image_urls: &[String],
+) -> Result<Booking, BookingError> {
+    let chat_id = get_or_create_chat(conn, &task.user_uid, professional_profile_uid)?;
+
+    let date_time = match (task.scheduled_date, task.scheduled_time) {
+        (Some(date), Some(time)) => Some(date.and_time(time).and_utc()),
+        _ => None,
+    };
+    let new_booking = NewBooking {
+        customer_uid: task.user_uid.clone(),
+        professional_profile_uid: professional_profile_uid.to_string(),
+        date_time,
+        end_time: date_time
+            .map(|start| start + Duration::minutes(DEFAULT_BOOKING_DURATION_MINUTES)),
+        status: BookingStatus::Accepted.into(),
+        description: task.description.clone(),
+        category_id: task.category_id,
+        service_offering_id: None,
+        service_offering_name: Some(task.title.clone()),
+        offering_price: proposal.price,
+        chat_id,
+        creation_time: Utc::now(),
+    };
+
+    let booking: Booking = diesel::insert_into(bookings::table)
+        .values(&new_booking)
+        .get_result(conn)?;
+
+    let assignments: Vec<NewBookingAssignment> = image_urls
+        .iter()
+        .map(|image_url| NewBookingAssignment {
+            appointment_id: booking.id,
+            image_url: image_url.clone(),
+        })
+        .collect();
+    diesel::insert_into(booking_assignments::table)
+        .values(&assignments)
+        .execute(conn)?;
+
+    record_transition(
+        conn,
+        booking.id,
+        None,
+        BookingStatus::Accepted,
+        &task.user_uid,
+        BookingParty::Customer,
+        Some(format!(
+            "Accepted proposal {} for task {}",
+            proposal.id, task.id
+        )),
+    )?;
+
+    Ok(booking)
+}
+
 fn insert_booking_assignment(
     db_pool: &Pool<ConnectionManager<PgConnection>>,
     booking_id: i32,
@@ -103,35 +250,972 @@ fn insert_booking_assignment(
 
     Ok(())
 }
-// pub fn get_booking_by_user(
-//     conn: &mut PgConnection,
-//     user_uid: &str,
-// ) -> Result<Vec<BookingDto>, BookingError> {
-//     let bookings: Vec<(Booking, Option<BookingAssignments>)> = bookings::table
-//         .filter(bookings::customer_uid.eq(user_uid))
-//         .left_join(
-//             booking_assignments::table.on(bookings::id.eq(booking_assignments::appointment_id)),
-//         )
-//         .load(conn)?;
-
-//     let booking_dtos: Vec<BookingDto> = bookings
-//         .into_iter()
-//         .map(|(booking, booking_assignment)| {
-//             let image_strings = booking_assignment.map(|assignment| vec![assignment.image_url]);
-
-//             BookingDto {
-//                 title: booking.title.clone(),
-//                 description: booking.description.clone(),
-//                 image_strings,
-//                 category_id: booking.category_id,
-//                 is_flexible_timing: booking.is_flexible_timing,
-//                 scheduled_date: booking.scheduled_date.map(|d| d.to_string()),
-//                 scheduled_time: booking.scheduled_time.map(|t| t.to_string()),
-//                 min_price: booking.min_price,
-//                 max_price: booking.max_price,
-//             }
-//         })
-//         .collect();
-
-//     Ok(booking_dtos)
-// }
+
+pub async fn transition_booking(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    booking_id: i32,
+    user_uid: String,
+    roles: Vec<Role>,
+    action: BookingAction,
+    note: Option<String>,
+) -> Result<Booking, BookingError> {
+    web::block(move || -> Result<Booking, BookingError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| BookingError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, BookingError, _>(|conn| {
+            let booking = bookings::table
+                .find(booking_id)
+                .for_update()
+                .first::<Booking>(conn)
+                .optional()?
+                .ok_or(BookingError::NotFound)?;
+
+            let party = resolve_acting_party(conn, &booking, &user_uid, &roles)?;
+            let current = BookingStatus::try_from(booking.status)?;
+            let next = current.apply(action, party)?;
+
+            let updated: Booking =
+                if current == BookingStatus::CounterOffer && action == BookingAction::Accept {
+                    accept_latest_offer(conn, &booking, party)?
+                } else {
+                    // Proposals are not busy yet, so an overlapping one may
+                    // have been accepted since this one was placed.
+                    if next == BookingStatus::Accepted {
+                        validate_booking_slot(conn, &booking, booking.date_time, booking.end_time)?;
+                    }
+                    diesel::update(bookings::table.find(booking.id))
+                        .set(bookings::status.eq(i32::from(next)))
+                        .get_result(conn)?
+                };
+
+            record_transition(
+                conn,
+                booking.id,
+                Some(current),
+                next,
+                &user_uid,
+                party,
+                note,
+            )?;
+
+            Ok(updated)
+        })
+    })
+    .await
+    .map_err(|e| BookingError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+pub async fn get_booking_history(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    booking_id: i32,
+    user_uid: String,
+) -> Result<Vec<BookingHistory>, BookingError> {
+    web::block(move || -> Result<Vec<BookingHistory>, BookingError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| BookingError::DatabasePoolError(e.to_string()))?;
+
+        let booking = bookings::table
+            .find(booking_id)
+            .first::<Booking>(&mut conn)
+            .optional()?
+            .ok_or(BookingError::NotFound)?;
+        resolve_party(&mut conn, &booking, &user_uid)?;
+
+        booking_history::table
+            .filter(booking_history::booking_id.eq(booking.id))
+            .order(booking_history::changed_at.asc())
+            .load::<BookingHistory>(&mut conn)
+            .map_err(BookingError::DieselError)
+    })
+    .await
+    .map_err(|e| BookingError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+pub async fn submit_counter_offer(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    booking_id: i32,
+    user_uid: String,
+    roles: Vec<Role>,
+    offer_dto: CounterOfferDTO,
+) -> Result<BookingOffer, BookingError> {
+    web::block(move || -> Result<BookingOffer, BookingError> {
+        let (date_time, end_time) = validate_counter_offer(&offer_dto)?;
+
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| BookingError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, BookingError, _>(|conn| {
+            let booking = bookings::table
+                .find(booking_id)
+                .for_update()
+                .first::<Booking>(conn)
+                .optional()?
+                .ok_or(BookingError::NotFound)?;
+
+            let party = resolve_acting_party(conn, &booking, &user_uid, &roles)?;
+            let current = BookingStatus::try_from(booking.status)?;
+            let next = current.apply(BookingAction::CounterOffer, party)?;
+            if date_time.is_some() || end_time.is_some() {
+                validate_booking_slot(
+                    conn,
+                    &booking,
+                    date_time.or(booking.date_time),
+                    end_time.or(booking.end_time),
+                )?;
+            }
+
+            let latest_version: Option<i32> = booking_offers::table
+                .filter(booking_offers::booking_id.eq(booking.id))
+                .select(diesel::dsl::max(booking_offers::version))
+                .first(conn)?;
+
+            let new_offer = NewBookingOffer {
+                booking_id: booking.id,
+                version: latest_version.unwrap_or(0) + 1,
+                offered_by_uid: user_uid.clone(),
+                offered_by_role: party.as_str().to_string(),
+                offering_price: offer_dto.offering_price,
+                date_time,
+                end_time,
+                message: offer_dto.message,
+                created_at: Utc::now(),
+            };
+            let offer: BookingOffer = diesel::insert_into(booking_offers::table)
+                .values(&new_offer)
+                .get_result(conn)?;
+
+            diesel::update(bookings::table.find(booking.id))
+                .set(bookings::status.eq(i32::from(next)))
+                .execute(conn)?;
+
+            record_transition(
+                conn,
+                booking.id,
+                Some(current),
+                next,
+                &user_uid,
+                party,
+                offer.message.clone(),
+            )?;
+
+            // The offer shows up in the booking's chat, so bump it like a new message.
+            diesel::update(chat::table.find(booking.chat_id))
+                .set(chat::last_message_time.eq(offer.created_at.naive_utc()))
+                .execute(conn)?;
+
+            Ok(offer)
+        })
+    })
+    .await
+    .map_err(|e| BookingError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+pub async fn get_booking_offers(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    booking_id: i32,
+    user_uid: String,
+) -> Result<Vec<BookingOffer>, BookingError> {
+    web::block(move || -> Result<Vec<BookingOffer>, BookingError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| BookingError::DatabasePoolError(e.to_string()))?;
+
+        let booking = bookings::table
+            .find(booking_id)
+            .first::<Booking>(&mut conn)
+            .optional()?
+            .ok_or(BookingError::NotFound)?;
+        resolve_party(&mut conn, &booking, &user_uid)?;
+
+        booking_offers::table
+            .filter(booking_offers::booking_id.eq(booking.id))
+            .order(booking_offers::version.asc())
+            .load::<BookingOffer>(&mut conn)
+            .map_err(BookingError::DieselError)
+    })
+    .await
+    .map_err(|e| BookingError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Accepts the standing counter-offer and copies its terms onto the booking,
+/// if its slot is still free. Only the side that did not make the offer may
+/// accept it.
+fn accept_latest_offer(
+    conn: &mut PgConnection,
+    booking: &Booking,
+    party: BookingParty,
+) -> Result<Booking, BookingError> {
+    let latest_offer = booking_offers::table
+        .filter(booking_offers::booking_id.eq(booking.id))
+        .order(booking_offers::version.desc())
+        .first::<BookingOffer>(conn)
+        .optional()?;
+
+    let accepted = i32::from(BookingStatus::Accepted);
+    let booking = match latest_offer {
+        Some(offer) => {
+            if offer.offered_by_role == party.as_str() {
+                return Err(BookingError::ActionNotPermitted {
+                    action: BookingAction::Accept,
+                    party,
+                });
+            }
+            let date_time = offer.date_time.or(booking.date_time);
+            let end_time = offer.end_time.or(booking.end_time);
+            validate_booking_slot(conn, booking, date_time, end_time)?;
+            diesel::update(bookings::table.find(booking.id))
+                .set((
+                    bookings::status.eq(accepted),
+                    bookings::offering_price.eq(offer.offering_price),
+                    bookings::date_time.eq(date_time),
+                    bookings::end_time.eq(end_time),
+                ))
+                .get_result(conn)?
+        }
+        None => {
+            validate_booking_slot(conn, booking, booking.date_time, booking.end_time)?;
+            diesel::update(bookings::table.find(booking.id))
+                .set(bookings::status.eq(accepted))
+                .get_result(conn)?
+        }
+    };
+
+    Ok(booking)
+}
+
+/// Start and end of a booking or offer; either may be left open.
+type TimeRange = (Option<DateTime<Utc>>, Option<DateTime<Utc>>);
+
+/// Checks the price of a counter-offer and returns its parsed start and end.
+fn validate_counter_offer(offer_dto: &CounterOfferDTO) -> Result<TimeRange, BookingError> {
+    if !offer_dto.offering_price.is_finite() || offer_dto.offering_price <= 0.0 {
+        return Err(BookingError::InvalidOffer(
+            "Price must be greater than zero".to_string(),
+        ));
+    }
+    let date_time = parse_timestamp(&offer_dto.date_time)?;
+    let end_time = parse_timestamp(&offer_dto.end_time)?;
+    if let (Some(start), Some(end)) = (date_time, end_time) {
+        if end <= start {
+            return Err(BookingError::InvalidOffer(
+                "End time must be after the start time".to_string(),
+            ));
+        }
+    }
+    Ok((date_time, end_time))
+}
+
+pub fn parse_timestamp(value: &Option<String>) -> Result<Option<DateTime<Utc>>, BookingError> {
+    value
+        .as_deref()
+        .map(|dt_str| DateTime::parse_from_rfc3339(dt_str).map(|dt| dt.with_timezone(&Utc)))
+        .transpose()
+        .map_err(BookingError::from)
+}
+
+/// Free slots of `duration` on profile `profile_id` between `from` and `to`.
+pub async fn get_open_slots(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    profile_id: i32,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    duration: Duration,
+) -> Result<Vec<TimeSlot>, BookingError> {
+    web::block(move || -> Result<Vec<TimeSlot>, BookingError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| BookingError::DatabasePoolError(e.to_string()))?;
+
+        let profile_uid: String = professional_profiles::table
+            .find(profile_id)
+            .filter(professional_profiles::is_active.eq(true))
+            .select(professional_profiles::professional_profile_uid)
+            .first(&mut conn)
+            .optional()?
+            .ok_or(BookingError::NotFound)?;
+
+        let calendar = business_hours_db::load_calendar(&mut conn, profile_id, from, to)?;
+        let busy = load_busy_slots(&mut conn, &profile_uid, from, to)?;
+
+        Ok(availability::open_slots(
+            &calendar, &busy, from, to, duration,
+        ))
+    })
+    .await
+    .map_err(|e| BookingError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+pub async fn get_bookings_for_customer(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    user_uid: String,
+    filter: BookingFilter,
+) -> Result<Vec<BookingSummaryDTO>, BookingError> {
+    web::block(move || -> Result<Vec<BookingSummaryDTO>, BookingError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| BookingError::DatabasePoolError(e.to_string()))?;
+
+        let bookings_from_db = filtered_bookings(&filter)
+            .filter(bookings::customer_uid.eq(&user_uid))
+            .load::<Booking>(&mut conn)?;
+
+        with_images(&mut conn, bookings_from_db)
+    })
+    .await
+    .map_err(|e| BookingError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+pub async fn get_bookings_for_profile(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    user_uid: String,
+    profile_id: i32,
+    filter: BookingFilter,
+) -> Result<Vec<BookingSummaryDTO>, BookingError> {
+    web::block(move || -> Result<Vec<BookingSummaryDTO>, BookingError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| BookingError::DatabasePoolError(e.to_string()))?;
+
+        let (profile_uid, owner_uid): (String, String) = professional_profiles::table
+            .inner_join(professionals::table)
+            .filter(professional_profiles::id.eq(profile_id))
+            .select((
+                professional_profiles::professional_profile_uid,
+                professionals::user_uid,
+            ))
+            .first(&mut conn)
+            .optional()?
+            .ok_or(BookingError::NotFound)?;
+        if owner_uid != user_uid {
+            return Err(BookingError::NotParticipant);
+        }
+
+        let bookings_from_db = filtered_bookings(&filter)
+            .filter(bookings::professional_profile_uid.eq(&profile_uid))
+            .load::<Booking>(&mut conn)?;
+
+        with_images(&mut conn, bookings_from_db)
+    })
+    .await
+    .map_err(|e| BookingError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+pub async fn get_booking_detail(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    booking_id: i32,
+    user_uid: String,
+) -> Result<BookingDetailDTO, BookingError> {
+    web::block(move || -> Result<BookingDetailDTO, BookingError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| BookingError::DatabasePoolError(e.to_string()))?;
+
+        let booking = bookings::table
+            .find(booking_id)
+            .first::<Booking>(&mut conn)
+            .optional()?
+            .ok_or(BookingError::NotFound)?;
+        resolve_party(&mut conn, &booking, &user_uid)?;
+
+        let service_offering = match booking.service_offering_id {
+            Some(offering_id) => service_offerings::table
+                .inner_join(subcategories::table)
+                .filter(service_offerings::id.eq(offering_id))
+                .select((ServiceOffering::as_select(), subcategories::category_id))
+                .first::<(ServiceOffering, i32)>(&mut conn)
+                .optional()?
+                .map(|(offering, category_id)| {
+                    ServiceOfferingDTO::service_offering_to_dto(&offering, category_id)
+                }),
+            None => None,
+        };
+
+        let booking = with_images(&mut conn, vec![booking])?
+            .pop()
+            .ok_or(BookingError::NotFound)?;
+
+        Ok(BookingDetailDTO {
+            booking,
+            service_offering,
+        })
+    })
+    .await
+    .map_err(|e| BookingError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+fn filtered_bookings(filter: &BookingFilter) -> bookings::BoxedQuery<'_, Pg> {
+    let mut query = bookings::table.into_boxed();
+
+    if let Some(statuses) = &filter.statuses {
+        query = query.filter(bookings::status.eq_any(statuses));
+    }
+    if let Some(from) = filter.from {
+        query = query.filter(bookings::date_time.ge(from));
+    }
+    if let Some(to) = filter.to {
+        query = query.filter(bookings::date_time.le(to));
+    }
+    if let Some(category_id) = filter.category_id {
+        query = query.filter(bookings::category_id.eq(category_id));
+    }
+
+    query
+        .order((bookings::date_time.desc().nulls_last(), bookings::id.desc()))
+        .limit(filter.limit)
+        .offset(filter.offset)
+}
+
+/// Attaches the `booking_assignments` image urls with a single query.
+fn with_images(
+    conn: &mut PgConnection,
+    bookings_from_db: Vec<Booking>,
+) -> Result<Vec<BookingSummaryDTO>, BookingError> {
+    let booking_ids: Vec<i32> = bookings_from_db.iter().map(|booking| booking.id).collect();
+
+    let assignments: Vec<(i32, String)> = booking_assignments::table
+        .filter(booking_assignments::appointment_id.eq_any(&booking_ids))
+        .order(booking_assignments::id.asc())
+        .select((
+            booking_assignments::appointment_id,
+            booking_assignments::image_url,
+        ))
+        .load(conn)?;
+
+    let mut images: HashMap<i32, Vec<String>> = HashMap::new();
+    for (booking_id, image_url) in assignments {
+        images.entry(booking_id).or_default().push(image_url);
+    }
+
+    Ok(bookings_from_db
+        .into_iter()
+        .map(|booking| {
+            let image_urls = images.remove(&booking.id).unwrap_or_default();
+            BookingSummaryDTO::booking_to_dto(booking, image_urls)
+        })
+        .collect())
+}
+
+/// Rejects slots outside the profile's business hours or overlapping another
+/// accepted or in-progress booking than `exclude_booking_id`. Locks the
+/// profile row so concurrent requests for the same professional are checked
+/// one after another.
+fn validate_slot(
+    conn: &mut PgConnection,
+    professional_profile_uid: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    exclude_booking_id: Option<i32>,
+) -> Result<(), BookingError> {
+    if end <= start {
+        return Err(BookingError::InvalidTimeRange);
+    }
+
+    let profile_id: i32 = professional_profiles::table
+        .filter(professional_profiles::professional_profile_uid.eq(professional_profile_uid))
+        .select(professional_profiles::id)
+        .for_update()
+        .first(conn)
+        .optional()?
+        .ok_or(BookingError::NotFound)?;
+
+    let calendar = business_hours_db::load_calendar(conn, profile_id, start, end)?;
+
+    let mut busy = load_busy_slots(conn, professional_profile_uid, start, end)?;
+    busy.retain(|slot| Some(slot.booking_id) != exclude_booking_id);
+
+    availability::check_slot(&calendar, start, end, &busy).map_err(BookingError::SlotUnavailable)
+}
+
+/// Checks that `booking` can take the slot from `start` to `end` before it
+/// becomes binding. Unscheduled bookings have no slot to check, and without
+/// an end time the booking counts as the default length.
+fn validate_booking_slot(
+    conn: &mut PgConnection,
+    booking: &Booking,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+) -> Result<(), BookingError> {
+    let Some(start) = start else {
+        return Ok(());
+    };
+    let end = end.unwrap_or(start + Duration::minutes(DEFAULT_BOOKING_DURATION_MINUTES));
+    validate_slot(
+        conn,
+        &booking.professional_profile_uid,
+        start,
+        end,
+        Some(booking.id),
+    )
+}
+
+/// Accepted and running bookings of a profile that intersect
+/// `[from, to)`. Bookings without an end time count as the default length.
+fn load_busy_slots(
+    conn: &mut PgConnection,
+    professional_profile_uid: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> QueryResult<Vec<BusySlot>> {
+    let busy = bookings::table
+        .filter(bookings::professional_profile_uid.eq(professional_profile_uid))
+        .filter(bookings::status.eq_any([
+            i32::from(BookingStatus::Accepted),
+            i32::from(BookingStatus::InProgress),
+        ]))
+        .filter(bookings::date_time.lt(to))
+        .filter(
+            bookings::end_time
+                .gt(from)
+                .or(bookings::end_time.is_null().and(
+                    bookings::date_time
+                        .gt(from - Duration::minutes(DEFAULT_BOOKING_DURATION_MINUTES)),
+                )),
+        )
+        .select((bookings::id, bookings::date_time, bookings::end_time))
+        .load::<(i32, Option<DateTime<Utc>>, Option<DateTime<Utc>>)>(conn)?
+        .into_iter()
+        .filter_map(|(booking_id, date_time, end_time)| {
+            date_time.map(|booking_start| BusySlot {
+                booking_id,
+                start: booking_start,
+                end: end_time
+                    .unwrap_or(booking_start + Duration::minutes(DEFAULT_BOOKING_DURATION_MINUTES)),
+            })
+        })
+        .collect();
+
+    Ok(busy)
+}
+
+/// Works out whether `user_uid` is the customer or the professional behind
+/// the booked profile.
+fn resolve_party(
+    conn: &mut PgConnection,
+    booking: &Booking,
+    user_uid: &str,
+) -> Result<BookingParty, BookingError> {
+    ownership::booking_party(conn, booking, user_uid)?.ok_or(BookingError::NotParticipant)
+}
+
+/// Like `resolve_party`, for callers about to change the booking: they must
+/// also still hold the role of the side they act for.
+fn resolve_acting_party(
+    conn: &mut PgConnection,
+    booking: &Booking,
+    user_uid: &str,
+    roles: &[Role],
+) -> Result<BookingParty, BookingError> {
+    let party = resolve_party(conn, booking, user_uid)?;
+    if roles.contains(&party.role()) {
+        Ok(party)
+    } else {
+        Err(BookingError::RoleRequired(party.role()))
+    }
+}
+
+/// Cancels the bookings of `user_uid` that have not started yet, as the
+/// customer or as owner of the booked profile: open requests, counter offers
+/// and accepted bookings that are unscheduled or scheduled in the future.
+/// Returns how many bookings were cancelled.
+pub fn cancel_upcoming_bookings(
+    conn: &mut PgConnection,
+    user_uid: &str,
+    note: &str,
+) -> Result<usize, BookingError> {
+    let profile_uids: Vec<String> = professional_profiles::table
+        .inner_join(professionals::table)
+        .filter(professionals::user_uid.eq(user_uid))
+        .select(professional_profiles::professional_profile_uid)
+        .load(conn)?;
+    let open_statuses = [
+        BookingStatus::Proposed,
+        BookingStatus::CounterOffer,
+        BookingStatus::Accepted,
+    ]
+    .map(i32::from);
+
+    let upcoming = bookings::table
+        .filter(
+            bookings::customer_uid
+                .eq(user_uid)
+                .or(bookings::professional_profile_uid.eq_any(&profile_uids)),
+        )
+        .filter(bookings::status.eq_any(open_statuses))
+        .filter(
+            bookings::date_time
+                .is_null()
+                .or(bookings::date_time.gt(Utc::now())),
+        )
+        .for_update()
+        .load::<Booking>(conn)?;
+
+    for booking in &upcoming {
+        let party = if booking.customer_uid == user_uid {
+            BookingParty::Customer
+        } else {
+            BookingParty::Professional
+        };
+        let current = BookingStatus::try_from(booking.status)?;
+        let next = current.apply(BookingAction::Cancel, party)?;
+
+        diesel::update(bookings::table.find(booking.id))
+            .set(bookings::status.eq(i32::from(next)))
+            .execute(conn)?;
+        record_transition(
+            conn,
+            booking.id,
+            Some(current),
+            next,
+            user_uid,
+            party,
+            Some(note.to_string()),
+        )?;
+    }
+
+    Ok(upcoming.len())
+}
+
+fn record_transition(
+    conn: &mut PgConnection,
+    booking_id: i32,
+    from_status: Option<BookingStatus>,
+    to_status: BookingStatus,
+    changed_by_uid: &str,
+    party: BookingParty,
+    note: Option<String>,
+) -> Result<(), BookingError> {
+    let entry = NewBookingHistory {
+        booking_id,
+        from_status: from_status.map(i32::from),
+        to_status: to_status.into(),
+        changed_by_uid: changed_by_uid.to_string(),
+        changed_by_role: party.as_str().to_string(),
+        note,
+        changed_at: Utc::now(),
+    };
+
+    diesel::insert_into(booking_history::table)
+        .values(&entry)
+        .execute(conn)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::schema::business_hours;
+    use crate::test_support::{
+        booking_request, insert_fixture, request_as, status_of, test_app, test_pool, PROFESSIONAL,
+        STRANGER,
+    };
+    use actix_web::http::{Method, StatusCode};
+    use chrono::NaiveTime;
+
+    fn offer(price: f64, date_time: Option<&str>, end_time: Option<&str>) -> CounterOfferDTO {
+        CounterOfferDTO {
+            offering_price: price,
+            date_time: date_time.map(String::from),
+            end_time: end_time.map(String::from),
+            message: None,
+        }
+    }
+
+    #[test]
+    fn counter_offers_need_a_price_and_an_ordered_time_range() {
+        let (start, end) = validate_counter_offer(&offer(
+            80.0,
+            Some("2026-10-20T09:00:00+02:00"),
+            Some("2026-10-20T10:00:00+02:00"),
+        ))
+        .unwrap();
+        assert_eq!(start.unwrap().to_rfc3339(), "2026-10-20T07:00:00+00:00");
+        assert_eq!(end.unwrap() - start.unwrap(), Duration::hours(1));
+        assert!(validate_counter_offer(&offer(80.0, None, None)).is_ok());
+
+        for invalid in [
+            offer(0.0, None, None),
+            offer(f64::NAN, None, None),
+            offer(
+                80.0,
+                Some("2026-10-20T10:00:00Z"),
+                Some("2026-10-20T10:00:00Z"),
+            ),
+        ] {
+            assert!(matches!(
+                validate_counter_offer(&invalid),
+                Err(BookingError::InvalidOffer(_))
+            ));
+        }
+        assert!(matches!(
+            validate_counter_offer(&offer(80.0, Some("tomorrow"), None)),
+            Err(BookingError::InvalidDate(_))
+        ));
+    }
+
+    /// Opens profile `profile_id` from 8:00 to 18:00 on every day of the week.
+    fn open_every_day(pool: &Pool<ConnectionManager<PgConnection>>, profile_id: i32) {
+        let conn = &mut pool.get().unwrap();
+        for day_of_week in 0..7 {
+            diesel::insert_into(business_hours::table)
+                .values((
+                    business_hours::professional_profile_id.eq(profile_id),
+                    business_hours::day_of_week.eq(day_of_week),
+                    business_hours::opening_time.eq(NaiveTime::from_hms_opt(8, 0, 0)),
+                    business_hours::closing_time.eq(NaiveTime::from_hms_opt(18, 0, 0)),
+                    business_hours::is_available.eq(true),
+                ))
+                .execute(conn)
+                .unwrap();
+        }
+    }
+
+    #[actix_web::test]
+    async fn rejected_bookings_leave_no_chat_behind() {
+        let Some(pool) = test_pool() else {
+            return;
+        };
+        let fixture = insert_fixture(&pool);
+        let app = test_app!(pool);
+
+        // The fixture's profile has no business hours, so every slot is closed.
+        let book = format!("/profiles/{}/book-service", fixture.profile_id);
+        let request = booking_request(
+            &fixture,
+            "2030-01-07T10:00:00Z",
+            Some("2030-01-07T11:00:00Z"),
+        );
+        assert_eq!(
+            status_of!(
+                app,
+                request_as(Method::POST, &book, STRANGER).set_json(request)
+            ),
+            StatusCode::CONFLICT
+        );
+
+        let chats: i64 = chat::table
+            .filter(chat::user_uid.eq(STRANGER))
+            .count()
+            .get_result(&mut pool.get().unwrap())
+            .unwrap();
+        assert_eq!(chats, 0);
+    }
+
+    #[actix_web::test]
+    async fn bookings_need_an_offering_of_the_booked_profile() {
+        let Some(pool) = test_pool() else {
+            return;
+        };
+        let fixture = insert_fixture(&pool);
+        let app = test_app!(pool);
+
+        let request = booking_request(&fixture, "2030-01-07T10:00:00Z", None);
+        let mut unknown_offering = request.clone();
+        unknown_offering["offering_id"] = serde_json::json!(fixture.offering_id + 1000);
+        for (profile_id, request) in [
+            (fixture.profile_id, unknown_offering),
+            (fixture.profile_id + 1000, request),
+        ] {
+            let book = format!("/profiles/{}/book-service", profile_id);
+            assert_eq!(
+                status_of!(
+                    app,
+                    request_as(Method::POST, &book, STRANGER).set_json(request)
+                ),
+                StatusCode::BAD_REQUEST
+            );
+        }
+    }
+
+    #[actix_web::test]
+    async fn bookings_take_their_terms_from_the_offering() {
+        let Some(pool) = test_pool() else {
+            return;
+        };
+        let fixture = insert_fixture(&pool);
+        open_every_day(&pool, fixture.profile_id);
+        let app = test_app!(pool);
+
+        let mut request = booking_request(&fixture, "2030-01-07T10:00:00Z", None);
+        request["category_id"] = serde_json::json!(fixture.category_id + 1000);
+        request["offering_price"] = serde_json::json!(1.0);
+        request["service_offering_name"] = serde_json::json!("Free consultation");
+        let book = format!("/profiles/{}/book-service", fixture.profile_id);
+        let booking: serde_json::Value = actix_web::test::call_and_read_body_json(
+            &app,
+            request_as(Method::POST, &book, STRANGER)
+                .set_json(request)
+                .to_request(),
+        )
+        .await;
+
+        assert_eq!(
+            booking["category_id"],
+            serde_json::json!(fixture.category_id)
+        );
+        assert_eq!(booking["offering_price"], serde_json::json!(50.0));
+        assert_eq!(
+            booking["service_offering_name"],
+            serde_json::json!("Policy consultations")
+        );
+    }
+
+    #[actix_web::test]
+    async fn bookings_spanning_several_days_keep_their_slots_busy() {
+        let Some(pool) = test_pool() else {
+            return;
+        };
+        let fixture = insert_fixture(&pool);
+        open_every_day(&pool, fixture.profile_id);
+        {
+            let conn = &mut pool.get().unwrap();
+            // Started two days before the requested slot and still running.
+            diesel::update(bookings::table.find(fixture.booking_id))
+                .set((
+                    bookings::status.eq(i32::from(BookingStatus::Accepted)),
+                    bookings::date_time.eq("2030-01-05T09:00:00Z".parse::<DateTime<Utc>>().ok()),
+                    bookings::end_time.eq("2030-01-07T10:30:00Z".parse::<DateTime<Utc>>().ok()),
+                ))
+                .execute(conn)
+                .unwrap();
+        }
+        let app = test_app!(pool);
+
+        let book = format!("/profiles/{}/book-service", fixture.profile_id);
+        for (start, expected) in [
+            ("2030-01-07T10:00:00Z", StatusCode::CONFLICT),
+            ("2030-01-07T11:00:00Z", StatusCode::OK),
+        ] {
+            let request = booking_request(&fixture, start, None);
+            assert_eq!(
+                status_of!(
+                    app,
+                    request_as(Method::POST, &book, STRANGER).set_json(request)
+                ),
+                expected,
+                "{}",
+                start
+            );
+        }
+    }
+
+    #[actix_web::test]
+    async fn overlapping_proposals_cannot_both_be_accepted() {
+        let Some(pool) = test_pool() else {
+            return;
+        };
+        let fixture = insert_fixture(&pool);
+        open_every_day(&pool, fixture.profile_id);
+        let app = test_app!(pool);
+
+        let book = format!("/profiles/{}/book-service", fixture.profile_id);
+        let mut booking_ids = Vec::new();
+        for start in ["2030-01-07T10:00:00Z", "2030-01-07T10:30:00Z"] {
+            let booking: serde_json::Value = actix_web::test::call_and_read_body_json(
+                &app,
+                request_as(Method::POST, &book, STRANGER)
+                    .set_json(booking_request(&fixture, start, None))
+                    .to_request(),
+            )
+            .await;
+            booking_ids.push(booking["id"].as_i64().unwrap());
+        }
+
+        for (booking_id, expected) in [
+            (booking_ids[0], StatusCode::OK),
+            (booking_ids[1], StatusCode::CONFLICT),
+        ] {
+            let accept = format!("/bookings/{}/accept", booking_id);
+            assert_eq!(
+                status_of!(app, request_as(Method::POST, &accept, PROFESSIONAL)),
+                expected,
+                "{}",
+                accept
+            );
+        }
+    }
+
+    #[actix_web::test]
+    async fn counter_offers_need_a_free_slot() {
+        let Some(pool) = test_pool() else {
+            return;
+        };
+        let fixture = insert_fixture(&pool);
+        open_every_day(&pool, fixture.profile_id);
+        let app = test_app!(pool);
+
+        let book = format!("/profiles/{}/book-service", fixture.profile_id);
+        let mut booking_ids = Vec::new();
+        for start in [
+            "2030-01-07T10:00:00Z",
+            "2030-01-07T14:00:00Z",
+            "2030-01-07T15:30:00Z",
+        ] {
+            let booking: serde_json::Value = actix_web::test::call_and_read_body_json(
+                &app,
+                request_as(Method::POST, &book, STRANGER)
+                    .set_json(booking_request(&fixture, start, None))
+                    .to_request(),
+            )
+            .await;
+            booking_ids.push(booking["id"].as_i64().unwrap());
+        }
+        let (accepted, countered, taken) = (booking_ids[0], booking_ids[1], booking_ids[2]);
+        let accept = |booking_id: i64| format!("/bookings/{}/accept", booking_id);
+        assert_eq!(
+            status_of!(
+                app,
+                request_as(Method::POST, &accept(accepted), PROFESSIONAL)
+            ),
+            StatusCode::OK
+        );
+
+        let counter_offer = format!("/bookings/{}/counter-offer", countered);
+        for (start, end, expected) in [
+            (
+                "2030-01-07T10:30:00Z",
+                "2030-01-07T11:30:00Z",
+                StatusCode::CONFLICT,
+            ),
+            (
+                "2030-01-07T19:00:00Z",
+                "2030-01-07T20:00:00Z",
+                StatusCode::CONFLICT,
+            ),
+            (
+                "2030-01-07T15:00:00Z",
+                "2030-01-07T16:00:00Z",
+                StatusCode::OK,
+            ),
+        ] {
+            let offer = serde_json::json!({
+                "offering_price": 60.0,
+                "date_time": start,
+                "end_time": end,
+            });
+            assert_eq!(
+                status_of!(
+                    app,
+                    request_as(Method::POST, &counter_offer, PROFESSIONAL).set_json(&offer)
+                ),
+                expected,
+                "offer from {}",
+                start
+            );
+        }
+
+        // The offered slot was taken before the customer accepted the offer.
+        assert_eq!(
+            status_of!(app, request_as(Method::POST, &accept(taken), PROFESSIONAL)),
+            StatusCode::OK
+        );
+        assert_eq!(
+            status_of!(app, request_as(Method::POST, &accept(countered), STRANGER)),
+            StatusCode::CONFLICT
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use crate::dal::holiday_db;
+use crate::dal::professional_profile_db::find_own_profile;
+use crate::errors::professional_profile_errors::ProfessionalProfileError;
+use crate::models::dtos::business_hours_dto::{
+    BusinessHourExceptionInputDTO, BusinessHoursDTO, WeeklyHoursDTO,
+};
+use crate::models::professional_aggregate::availability::{self, ProfileCalendar};
+use crate::models::professional_aggregate::business_hour::{BusinessHours, NewBusinessHours};
+use crate::models::professional_aggregate::business_hour_exception::{
+    BusinessHourException, NewBusinessHourException,
+};
+use crate::schema::schema::{
+    address_assignments, addresses, business_hour_exceptions, business_hours, professional_profiles,
+};
+use actix_web::web;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool};
+
+/// Bound of `business_hour_exceptions.reason`.
+const MAX_REASON_LENGTH: usize = 255;
+
+/// Weekly schedule of a profile and the exceptions that have not ended yet.
+pub async fn get_business_hours(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    profile_id: i32,
+) -> Result<BusinessHoursDTO, ProfessionalProfileError> {
+    web::block(
+        move || -> Result<BusinessHoursDTO, ProfessionalProfileError> {
+            let mut conn = db_pool
+                .get()
+                .map_err(|e| ProfessionalProfileError::DatabasePoolError(e.to_string()))?;
+
+            let profile_exists = diesel::select(diesel::dsl::exists(
+                professional_profiles::table.find(profile_id),
+            ))
+            .get_result::<bool>(&mut conn)?;
+            if !profile_exists {
+                return Err(ProfessionalProfileError::NotFound);
+            }
+
+            load_business_hours(&mut conn, profile_id)
+        },
+    )
+    .await
+    .map_err(|e| ProfessionalProfileError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Replaces the whole weekly schedule of an own profile. Days without
+/// intervals are closed.
+pub async fn set_weekly_hours(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    profile_id: i32,
+    user_uid: String,
+    weekly_hours: Vec<WeeklyHoursDTO>,
+) -> Result<BusinessHoursDTO, ProfessionalProfileError> {
+    availability::validate_weekly_hours(&weekly_hours)
+        .map_err(ProfessionalProfileError::InvalidBusinessHours)?;
+
+    web::block(
+        move || -> Result<BusinessHoursDTO, ProfessionalProfileError> {
+            let mut conn = db_pool
+                .get()
+                .map_err(|e| ProfessionalProfileError::DatabasePoolError(e.to_string()))?;
+
+            conn.transaction::<_, ProfessionalProfileError, _>(|conn| {
+                find_own_profile(conn, profile_id, &user_uid)?;
+
+                diesel::delete(
+                    business_hours::table
+                        .filter(business_hours::professional_profile_id.eq(profile_id)),
+                )
+                .execute(conn)?;
+                let new_hours: Vec<NewBusinessHours> = weekly_hours
+                    .iter()
+                    .map(|entry| NewBusinessHours {
+                        professional_profile_id: profile_id,
+                        day_of_week: entry.day_of_week,
+                        opening_time: Some(entry.opening_time),
+                        closing_time: Some(entry.closing_time),
+                        is_available: true,
+                    })
+                    .collect();
+                diesel::insert_into(business_hours::table)
+                    .values(&new_hours)
+                    .execute(conn)?;
+
+                load_business_hours(conn, profile_id)
+            })
+        },
+    )
+    .await
+    .map_err(|e| ProfessionalProfileError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+pub async fn create_exception(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    profile_id: i32,
+    user_uid: String,
+    exception_dto: BusinessHourExceptionInputDTO,
+) -> Result<BusinessHourException, ProfessionalProfileError> {
+    availability::validate_exception(&exception_dto)
+        .map_err(ProfessionalProfileError::InvalidBusinessHours)?;
+    let reason = exception_dto
+        .reason
+        .map(|reason| reason.trim().to_string())
+        .filter(|reason| !reason.is_empty());
+    if reason
+        .as_ref()
+        .is_some_and(|reason| reason.chars().count() > MAX_REASON_LENGTH)
+    {
+        return Err(ProfessionalProfileError::InvalidBusinessHours(format!(
+            "reason must be at most {} characters",
+            MAX_REASON_LENGTH
+        )));
+    }
+
+    web::block(
+        move || -> Result<BusinessHourException, ProfessionalProfileError> {
+            let mut conn = db_pool
+                .get()
+                .map_err(|e| ProfessionalProfileError::DatabasePoolError(e.to_string()))?;
+
+            conn.transaction::<_, ProfessionalProfileError, _>(|conn| {
+                find_own_profile(conn, profile_id, &user_uid)?;
+                Ok(diesel::insert_into(business_hour_exceptions::table)
+                    .values(&NewBusinessHourException {
+                        professional_profile_id: profile_id,
+                        start_date: exception_dto.start_date,
+                        end_date: exception_dto.end_date,
+                        opening_time: exception_dto.opening_time,
+                        closing_time: exception_dto.closing_time,
+                        reason,
+                    })
+                    .returning(BusinessHourException::as_returning())
+                    .get_result(conn)?)
+            })
+        },
+    )
+    .await
+    .map_err(|e| ProfessionalProfileError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+pub async fn delete_exception(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    profile_id: i32,
+    exception_id: i32,
+    user_uid: String,
+) -> Result<(), ProfessionalProfileError> {
+    web::block(move || -> Result<(), ProfessionalProfileError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| ProfessionalProfileError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, ProfessionalProfileError, _>(|conn| {
+            find_own_profile(conn, profile_id, &user_uid)?;
+            let deleted = diesel::delete(
+                business_hour_exceptions::table
+                    .filter(business_hour_exceptions::id.eq(exception_id))
+                    .filter(business_hour_exceptions::professional_profile_id.eq(profile_id)),
+            )
+            .execute(conn)?;
+            if deleted == 0 {
+                return Err(ProfessionalProfileError::ExceptionNotFound);
+            }
+            Ok(())
+        })
+    })
+    .await
+    .map_err(|e| ProfessionalProfileError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Weekly hours, exceptions and the holidays at the profile's address that
+/// matter between `from` and `to`, in the profile's timezone.
+pub fn load_calendar(
+    conn: &mut PgConnection,
+    profile_id: i32,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> QueryResult<ProfileCalendar> {
+    let timezone_name = professional_profiles::table
+        .find(profile_id)
+        .select(professional_profiles::timezone)
+        .first::<String>(conn)?;
+    // Only ever written after validation, so this falls back for rows edited
+    // by hand.
+    let timezone =
+        availability::parse_timezone(&timezone_name).unwrap_or(availability::DEFAULT_TIMEZONE);
+    let from = from.with_timezone(&timezone).date_naive();
+    let to = to.with_timezone(&timezone).date_naive();
+
+    let hours = business_hours::table
+        .filter(business_hours::professional_profile_id.eq(profile_id))
+        .load::<BusinessHours>(conn)?;
+    let exceptions = business_hour_exceptions::table
+        .filter(business_hour_exceptions::professional_profile_id.eq(profile_id))
+        .filter(business_hour_exceptions::start_date.le(to))
+        .filter(business_hour_exceptions::end_date.ge(from))
+        .select(BusinessHourException::as_select())
+        .load::<BusinessHourException>(conn)?;
+    // Search and the profile page use the first assigned address as well.
+    let state = address_assignments::table
+        .inner_join(addresses::table)
+        .filter(address_assignments::professional_profile_id.eq(profile_id))
+        .order(address_assignments::id)
+        .select(addresses::state)
+        .first::<String>(conn)
+        .optional()?;
+    let holidays = holiday_db::get_holiday_dates(conn, state.as_deref(), from, to)?;
+
+    Ok(ProfileCalendar {
+        timezone,
+        hours,
+        exceptions,
+        holidays,
+    })
+}
+
+fn load_business_hours(
+    conn: &mut PgConnection,
+    profile_id: i32,
+) -> Result<BusinessHoursDTO, ProfessionalProfileError> {
+    let weekly = business_hours::table
+        .filter(business_hours::professional_profile_id.eq(profile_id))
+        .filter(business_hours::is_available.eq(true))
+        .order((business_hours::day_of_week, business_hours::opening_time))
+        .load::<BusinessHours>(conn)?
+        .into_iter()
+        .filter_map(|bh| match (bh.opening_time, bh.closing_time) {
+            (Some(opening_time), Some(closing_time)) => Some(WeeklyHoursDTO {
+                day_of_week: bh.day_of_week,
+                opening_time,
+                closing_time,
+            }),
+            _ => None,
+        })
+        .collect();
+    let exceptions = business_hour_exceptions::table
+        .filter(business_hour_exceptions::professional_profile_id.eq(profile_id))
+        .filter(business_hour_exceptions::end_date.ge(Utc::now().date_naive()))
+        .order((
+            business_hour_exceptions::start_date,
+            business_hour_exceptions::opening_time,
+            business_hour_exceptions::id,
+        ))
+        .select(BusinessHourException::as_select())
+        .load::<BusinessHourException>(conn)?;
+
+    Ok(BusinessHoursDTO { weekly, exceptions })
+}
@@ -1,13 +1,409 @@
-use crate::models::category_aggregate::sub_category::Subcategory;
-use crate::schema::schema::subcategories::dsl::*;
+use crate::errors::category_errors::CategoryError;
+use crate::models::category_aggregate::category::{Category, NewCategory};
+use crate::models::category_aggregate::sub_category::{NewSubcategory, Subcategory};
+use crate::models::dtos::category_dto::{
+    CategoryDTO, CategoryInputDTO, SubcategoryCountDTO, SubcategoryInputDTO,
+};
+use crate::schema::schema::{
+    bookings, categories, professional_profiles, service_offerings, subcategories, task,
+};
+use actix_web::web;
+use diesel::dsl::count_star;
 use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool};
 use diesel::result::Error;
+use diesel::sql_types::Text;
+use std::collections::HashMap;
+
+/// Longest category or subcategory name, bounded by
+/// `service_offerings.subcategory_name`.
+const MAX_NAME_LENGTH: usize = 255;
+
+diesel::define_sql_function!(fn lower(x: Text) -> Text);
 
 pub async fn get_subcategory_by_category_id(
     conn: &mut PgConnection,
     id_category: i32,
 ) -> Result<Vec<Subcategory>, Error> {
-    subcategories
-        .filter(category_id.eq(id_category))
+    subcategories::table
+        .filter(subcategories::category_id.eq(id_category))
         .load::<Subcategory>(conn)
 }
+
+/// All categories with their subcategories, ordered by name, and the number of
+/// service offerings in each.
+pub fn get_category_catalog(conn: &mut PgConnection) -> Result<Vec<CategoryDTO>, Error> {
+    let all_categories = categories::table
+        .order(categories::name)
+        .select(Category::as_select())
+        .load::<Category>(conn)?;
+    let all_subcategories = subcategories::table
+        .order(subcategories::name)
+        .load::<Subcategory>(conn)?;
+    let offering_counts: HashMap<i32, i64> = service_offerings::table
+        .group_by(service_offerings::subcategory_id)
+        .select((service_offerings::subcategory_id, count_star()))
+        .load::<(i32, i64)>(conn)?
+        .into_iter()
+        .collect();
+
+    let mut subcategories_by_category: HashMap<i32, Vec<SubcategoryCountDTO>> = HashMap::new();
+    for subcategory in all_subcategories {
+        subcategories_by_category
+            .entry(subcategory.category_id)
+            .or_default()
+            .push(SubcategoryCountDTO {
+                offering_count: offering_counts.get(&subcategory.id).copied().unwrap_or(0),
+                id: subcategory.id,
+                name: subcategory.name,
+                category_id: subcategory.category_id,
+            });
+    }
+
+    Ok(all_categories
+        .into_iter()
+        .map(|category| {
+            let category_subcategories = subcategories_by_category
+                .remove(&category.id)
+                .unwrap_or_default();
+            CategoryDTO {
+                offering_count: category_subcategories
+                    .iter()
+                    .map(|subcategory| subcategory.offering_count)
+                    .sum(),
+                id: category.id,
+                name: category.name,
+                description: category.description,
+                subcategories: category_subcategories,
+            }
+        })
+        .collect())
+}
+
+pub async fn create_category(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    category_dto: CategoryInputDTO,
+) -> Result<Category, CategoryError> {
+    let new_category = NewCategory {
+        name: validate_name(&category_dto.name)?,
+        description: category_dto.description,
+    };
+
+    web::block(move || -> Result<Category, CategoryError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| CategoryError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, CategoryError, _>(|conn| {
+            ensure_category_name_free(conn, &new_category.name, None)?;
+            Ok(diesel::insert_into(categories::table)
+                .values(&new_category)
+                .returning(Category::as_returning())
+                .get_result(conn)?)
+        })
+    })
+    .await
+    .map_err(|e| CategoryError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+pub async fn update_category(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    category_id: i32,
+    category_dto: CategoryInputDTO,
+) -> Result<Category, CategoryError> {
+    let changes = NewCategory {
+        name: validate_name(&category_dto.name)?,
+        description: category_dto.description,
+    };
+
+    web::block(move || -> Result<Category, CategoryError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| CategoryError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, CategoryError, _>(|conn| {
+            ensure_category_name_free(conn, &changes.name, Some(category_id))?;
+            diesel::update(categories::table.find(category_id))
+                .set(&changes)
+                .returning(Category::as_returning())
+                .get_result(conn)
+                .optional()?
+                .ok_or(CategoryError::NotFound)
+        })
+    })
+    .await
+    .map_err(|e| CategoryError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Deletes a category together with its subcategories, unless offerings,
+/// profiles, bookings or tasks still refer to any of them.
+pub async fn delete_category(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    category_id: i32,
+) -> Result<(), CategoryError> {
+    web::block(move || -> Result<(), CategoryError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| CategoryError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, CategoryError, _>(|conn| {
+            categories::table
+                .find(category_id)
+                .select(categories::id)
+                .for_update()
+                .first::<i32>(conn)
+                .optional()?
+                .ok_or(CategoryError::NotFound)?;
+
+            let offering_count = service_offerings::table
+                .inner_join(subcategories::table)
+                .filter(subcategories::category_id.eq(category_id))
+                .count()
+                .get_result::<i64>(conn)?;
+            let profile_count = professional_profiles::table
+                .filter(professional_profiles::category_id.eq(category_id))
+                .count()
+                .get_result::<i64>(conn)?;
+            let booking_count = bookings::table
+                .filter(bookings::category_id.eq(category_id))
+                .count()
+                .get_result::<i64>(conn)?;
+            let task_count = task::table
+                .filter(task::category_id.eq(category_id))
+                .count()
+                .get_result::<i64>(conn)?;
+            ensure_unreferenced(&[
+                (offering_count, "service offering", "service offerings"),
+                (
+                    profile_count,
+                    "professional profile",
+                    "professional profiles",
+                ),
+                (booking_count, "booking", "bookings"),
+                (task_count, "task", "tasks"),
+            ])?;
+
+            diesel::delete(subcategories::table.filter(subcategories::category_id.eq(category_id)))
+                .execute(conn)?;
+            diesel::delete(categories::table.find(category_id)).execute(conn)?;
+            Ok(())
+        })
+    })
+    .await
+    .map_err(|e| CategoryError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+pub async fn create_subcategory(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    subcategory_dto: SubcategoryInputDTO,
+) -> Result<Subcategory, CategoryError> {
+    let new_subcategory = NewSubcategory {
+        name: validate_name(&subcategory_dto.name)?,
+        category_id: subcategory_dto.category_id,
+    };
+
+    web::block(move || -> Result<Subcategory, CategoryError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| CategoryError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, CategoryError, _>(|conn| {
+            ensure_subcategory_name_free(conn, &new_subcategory, None)?;
+            Ok(diesel::insert_into(subcategories::table)
+                .values(&new_subcategory)
+                .get_result::<Subcategory>(conn)?)
+        })
+    })
+    .await
+    .map_err(|e| CategoryError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Renames or moves a subcategory. A new name is copied to the offerings
+/// that store it alongside their subcategory id.
+pub async fn update_subcategory(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    subcategory_id: i32,
+    subcategory_dto: SubcategoryInputDTO,
+) -> Result<Subcategory, CategoryError> {
+    let changes = NewSubcategory {
+        name: validate_name(&subcategory_dto.name)?,
+        category_id: subcategory_dto.category_id,
+    };
+
+    web::block(move || -> Result<Subcategory, CategoryError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| CategoryError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, CategoryError, _>(|conn| {
+            ensure_subcategory_name_free(conn, &changes, Some(subcategory_id))?;
+            let subcategory = diesel::update(subcategories::table.find(subcategory_id))
+                .set(&changes)
+                .get_result::<Subcategory>(conn)
+                .optional()?
+                .ok_or(CategoryError::SubcategoryNotFound)?;
+
+            diesel::update(
+                service_offerings::table
+                    .filter(service_offerings::subcategory_id.eq(subcategory_id))
+                    .filter(service_offerings::subcategory_name.ne(&subcategory.name)),
+            )
+            .set(service_offerings::subcategory_name.eq(&subcategory.name))
+            .execute(conn)?;
+
+            Ok(subcategory)
+        })
+    })
+    .await
+    .map_err(|e| CategoryError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+pub async fn delete_subcategory(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    subcategory_id: i32,
+) -> Result<(), CategoryError> {
+    web::block(move || -> Result<(), CategoryError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| CategoryError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, CategoryError, _>(|conn| {
+            subcategories::table
+                .find(subcategory_id)
+                .select(subcategories::id)
+                .for_update()
+                .first::<i32>(conn)
+                .optional()?
+                .ok_or(CategoryError::SubcategoryNotFound)?;
+
+            let offering_count = service_offerings::table
+                .filter(service_offerings::subcategory_id.eq(subcategory_id))
+                .count()
+                .get_result::<i64>(conn)?;
+            ensure_unreferenced(&[(offering_count, "service offering", "service offerings")])?;
+
+            diesel::delete(subcategories::table.find(subcategory_id)).execute(conn)?;
+            Ok(())
+        })
+    })
+    .await
+    .map_err(|e| CategoryError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+fn validate_name(name: &str) -> Result<String, CategoryError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(CategoryError::InvalidName(
+            "name must not be empty".to_string(),
+        ));
+    }
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(CategoryError::InvalidName(format!(
+            "name must be at most {} characters",
+            MAX_NAME_LENGTH
+        )));
+    }
+    Ok(name.to_string())
+}
+
+/// Category names are unique ignoring case.
+fn ensure_category_name_free(
+    conn: &mut PgConnection,
+    name: &str,
+    except_id: Option<i32>,
+) -> Result<(), CategoryError> {
+    let mut query = categories::table
+        .filter(lower(categories::name).eq(name.to_lowercase()))
+        .into_boxed();
+    if let Some(except_id) = except_id {
+        query = query.filter(categories::id.ne(except_id));
+    }
+    if diesel::select(diesel::dsl::exists(query)).get_result::<bool>(conn)? {
+        return Err(CategoryError::DuplicateName);
+    }
+    Ok(())
+}
+
+/// Subcategory names are unique within their category, ignoring case. Also
+/// checks that the category exists.
+fn ensure_subcategory_name_free(
+    conn: &mut PgConnection,
+    subcategory: &NewSubcategory,
+    except_id: Option<i32>,
+) -> Result<(), CategoryError> {
+    let category_exists = diesel::select(diesel::dsl::exists(
+        categories::table.find(subcategory.category_id),
+    ))
+    .get_result::<bool>(conn)?;
+    if !category_exists {
+        return Err(CategoryError::NotFound);
+    }
+
+    let mut query = subcategories::table
+        .filter(subcategories::category_id.eq(subcategory.category_id))
+        .filter(lower(subcategories::name).eq(subcategory.name.to_lowercase()))
+        .into_boxed();
+    if let Some(except_id) = except_id {
+        query = query.filter(subcategories::id.ne(except_id));
+    }
+    if diesel::select(diesel::dsl::exists(query)).get_result::<bool>(conn)? {
+        return Err(CategoryError::DuplicateName);
+    }
+    Ok(())
+}
+
+/// Fails with the non-zero counts, e.g. "2 bookings and 1 task".
+fn ensure_unreferenced(references: &[(i64, &str, &str)]) -> Result<(), CategoryError> {
+    let in_use: Vec<String> = references
+        .iter()
+        .filter(|(count, _, _)| *count > 0)
+        .map(|&(count, singular, plural)| {
+            format!("{} {}", count, if count == 1 { singular } else { plural })
+        })
+        .collect();
+
+    match in_use.split_last() {
+        None => Ok(()),
+        Some((last, [])) => Err(CategoryError::InUse(last.clone())),
+        Some((last, rest)) => Err(CategoryError::InUse(format!(
+            "{} and {}",
+            rest.join(", "),
+            last
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn in_use(references: &[(i64, &str, &str)]) -> Option<String> {
+        match ensure_unreferenced(references) {
+            Ok(()) => None,
+            Err(CategoryError::InUse(message)) => Some(message),
+            Err(e) => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn references_are_listed_with_their_counts() {
+        assert_eq!(in_use(&[]), None);
+        assert_eq!(
+            in_use(&[(0, "booking", "bookings"), (0, "task", "tasks")]),
+            None
+        );
+        assert_eq!(
+            in_use(&[(1, "booking", "bookings"), (0, "task", "tasks")]).as_deref(),
+            Some("1 booking")
+        );
+        assert_eq!(
+            in_use(&[
+                (2, "booking", "bookings"),
+                (1, "task", "tasks"),
+                (3, "service offering", "service offerings"),
+            ])
+            .as_deref(),
+            Some("2 bookings, 1 task and 3 service offerings")
+        );
+    }
+}
@@ -2,7 +2,8 @@ use std::collections::HashMap;
 
 use crate::db::Pool;
 use crate::models::booking_aggregate::{
-    booking::Booking, booking_assignment::BookingAssignment, booking_status::BookingStatus,
+    booking::Booking, booking_assignment::BookingAssignment, booking_offer::BookingOffer,
+    booking_status::BookingStatus,
 };
 use crate::models::chat_aggregate::message_assignment::NewMessageAssignment;
 use crate::models::chat_aggregate::{
@@ -10,7 +11,9 @@ use crate::models::chat_aggregate::{
 };
 use crate::models::dtos::chat_dto::ChatDTO;
 use crate::models::dtos::message_dto::{MessageAssignmentDTO, MessageDTO};
-use crate::schema::schema::{bookings, chat, message, message_assignments, professional_profiles};
+use crate::schema::schema::{
+    booking_offers, bookings, chat, message, message_assignments, professional_profiles,
+};
 use actix_web::{web, Error as ActixError};
 use chrono::{NaiveDateTime, Utc};
 use diesel::prelude::*;
@@ -78,7 +81,20 @@ pub fn get_messages_for_chat(
         .map(ChatItem::Booking)
         .collect();
 
+    let offers: Vec<ChatItem> = booking_offers::table
+        .inner_join(bookings::table)
+        .filter(bookings::chat_id.eq(chat_id_val))
+        .select(booking_offers::all_columns)
+        .order(booking_offers::created_at.desc())
+        .limit(limit_val)
+        .offset(offset_val)
+        .load::<BookingOffer>(conn)?
+        .into_iter()
+        .map(ChatItem::Offer)
+        .collect();
+
     chat_items.extend(bookings);
+    chat_items.extend(offers);
     chat_items.sort_by(|a, b| a.get_time().cmp(&b.get_time()).reverse());
 
     Ok(chat_items)
@@ -0,0 +1,143 @@
+use crate::errors::holiday_errors::HolidayError;
+use crate::models::public_holiday::{NewPublicHoliday, PublicHoliday};
+use crate::schema::schema::public_holidays;
+use actix_web::web;
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::result::{DatabaseErrorKind, Error};
+
+/// Bound of `public_holidays.name` and `public_holidays.state`.
+const MAX_TEXT_LENGTH: usize = 255;
+
+/// Holidays ordered by date, optionally only those that apply in `state`
+/// (including nationwide ones) or fall in `year`.
+pub async fn get_holidays(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    state: Option<String>,
+    year: Option<i32>,
+) -> Result<Vec<PublicHoliday>, HolidayError> {
+    web::block(move || -> Result<Vec<PublicHoliday>, HolidayError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| HolidayError::DatabasePoolError(e.to_string()))?;
+
+        let mut query = public_holidays::table
+            .select(PublicHoliday::as_select())
+            .into_boxed();
+        if let Some(state) = state {
+            query = query.filter(
+                public_holidays::state
+                    .is_null()
+                    .or(public_holidays::state.eq(state)),
+            );
+        }
+        if let Some(year) = year {
+            let first = NaiveDate::from_ymd_opt(year, 1, 1)
+                .ok_or_else(|| HolidayError::InvalidHoliday(format!("invalid year {}", year)))?;
+            let last = NaiveDate::from_ymd_opt(year, 12, 31)
+                .ok_or_else(|| HolidayError::InvalidHoliday(format!("invalid year {}", year)))?;
+            query = query.filter(public_holidays::holiday_date.between(first, last));
+        }
+
+        Ok(query
+            .order((public_holidays::holiday_date, public_holidays::id))
+            .load::<PublicHoliday>(&mut conn)?)
+    })
+    .await
+    .map_err(|e| HolidayError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+pub async fn create_holiday(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    holiday: NewPublicHoliday,
+) -> Result<PublicHoliday, HolidayError> {
+    let name = holiday.name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_TEXT_LENGTH {
+        return Err(HolidayError::InvalidHoliday(format!(
+            "name must be between 1 and {} characters",
+            MAX_TEXT_LENGTH
+        )));
+    }
+    // A blank state means the holiday applies nationwide.
+    let state = holiday
+        .state
+        .map(|state| state.trim().to_string())
+        .filter(|state| !state.is_empty());
+    if state
+        .as_ref()
+        .is_some_and(|state| state.chars().count() > MAX_TEXT_LENGTH)
+    {
+        return Err(HolidayError::InvalidHoliday(format!(
+            "state must be at most {} characters",
+            MAX_TEXT_LENGTH
+        )));
+    }
+    let new_holiday = NewPublicHoliday {
+        holiday_date: holiday.holiday_date,
+        state,
+        name,
+    };
+
+    web::block(move || -> Result<PublicHoliday, HolidayError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| HolidayError::DatabasePoolError(e.to_string()))?;
+
+        diesel::insert_into(public_holidays::table)
+            .values(&new_holiday)
+            .returning(PublicHoliday::as_returning())
+            .get_result(&mut conn)
+            .map_err(|e| match e {
+                Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                    HolidayError::DuplicateHoliday
+                }
+                e => HolidayError::DieselError(e),
+            })
+    })
+    .await
+    .map_err(|e| HolidayError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+pub async fn delete_holiday(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    holiday_id: i32,
+) -> Result<(), HolidayError> {
+    web::block(move || -> Result<(), HolidayError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| HolidayError::DatabasePoolError(e.to_string()))?;
+
+        let deleted = diesel::delete(public_holidays::table.find(holiday_id)).execute(&mut conn)?;
+        if deleted == 0 {
+            return Err(HolidayError::NotFound);
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| HolidayError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Dates between `from` and `to` that are holidays in `state`. Without a
+/// state only nationwide holidays count.
+pub fn get_holiday_dates(
+    conn: &mut PgConnection,
+    state: Option<&str>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> QueryResult<Vec<NaiveDate>> {
+    let mut query = public_holidays::table
+        .filter(public_holidays::holiday_date.between(from, to))
+        .select(public_holidays::holiday_date)
+        .distinct()
+        .into_boxed();
+    query = match state {
+        Some(state) => query.filter(
+            public_holidays::state
+                .is_null()
+                .or(public_holidays::state.eq(state.to_string())),
+        ),
+        None => query.filter(public_holidays::state.is_null()),
+    };
+    query.order(public_holidays::holiday_date).load(conn)
+}
@@ -1,6 +1,9 @@
+use crate::dal::role_db;
 use crate::models::professional_aggregate::{
     professional::NewProfessional, professional::Professional,
 };
+use crate::models::user_aggregate::role::Role;
+use crate::schema::schema::professional_profiles;
 use crate::schema::schema::professionals::dsl::*;
 use diesel::prelude::*;
 use diesel::result::Error;
@@ -27,10 +30,35 @@ pub fn save_professional_to_database(
         user_uid: professional_uid_to_save.to_string(),
     };
 
-    // Insert the new user into the database
-    diesel::insert_into(professionals)
-        .values(&new_professional)
+    // Insert the new professional into the database along with their role
+    conn.transaction(|conn| {
+        diesel::insert_into(professionals)
+            .values(&new_professional)
+            .execute(conn)?;
+        role_db::add_role(conn, professional_uid_to_save, Role::Professional)
+    })
+}
+
+/// Renames the professional and the copy of their name on each of their
+/// profiles. Fails with `NotFound` when no professional has the uid.
+pub fn rename_professional(
+    conn: &mut PgConnection,
+    professional_uid: &str,
+    new_name: &str,
+) -> Result<(), Error> {
+    conn.transaction(|conn| {
+        let professional_id = diesel::update(professionals.filter(user_uid.eq(professional_uid)))
+            .set(name.eq(new_name))
+            .returning(id)
+            .get_result::<i32>(conn)?;
+
+        diesel::update(
+            professional_profiles::table
+                .filter(professional_profiles::professional_id.eq(professional_id)),
+        )
+        .set(professional_profiles::professional_name.eq(new_name))
         .execute(conn)?;
 
-    Ok(())
+        Ok(())
+    })
 }
@@ -1,16 +1,25 @@
+use super::{address_db, business_hours_db, review_db};
+use crate::errors::professional_profile_errors::ProfessionalProfileError;
 use crate::models::{
     address::*,
     address_assignments::*,
     category_aggregate::category::*,
     dtos::address_dto::*,
     dtos::professional_profile_detail_dto::*,
+    dtos::professional_profile_edit_dto::{
+        CreateProfessionalProfileDTO, OwnProfessionalProfileDTO, ServiceOfferingInputDTO,
+        UpdateProfessionalProfileDTO,
+    },
     dtos::professional_profiles_dto::*,
-    dtos::review_dto::*,
-    professional_aggregate::business_hour::*,
+    professional_aggregate::availability,
+    professional_aggregate::day_of_week::DayOfWeek,
     professional_aggregate::professional_profile::*,
+    professional_aggregate::profile_search::{
+        SearchCursor, SearchSort, BEST_MATCH_DISTANCE_WEIGHT, BEST_MATCH_RATING_WEIGHT,
+        BEST_MATCH_REVIEWS_WEIGHT, BEST_MATCH_REVIEW_SATURATION,
+    },
     professional_aggregate::service_offering::*,
-    review_aggregate::review::*,
-    review_aggregate::review_content_assignments::*, // professional_aggregate::service_offering::*
+    review_aggregate::review::*, // professional_aggregate::service_offering::*
 };
 // use crate::schema::schema::{
 //     professional_profiles,
@@ -27,90 +36,323 @@ use crate::models::{
 //     service_offerings::dsl::*
 // };
 use crate::schema::schema::{
-    addresses, business_hours, categories, professional_profiles, subcategories,
+    address_assignments, addresses, bookings, categories, professional_profiles, professionals,
+    review, service_offerings, subcategories,
 };
-use chrono::{Datelike, Utc};
+use actix_web::web;
+use chrono::Utc;
+use diesel::pg::Pg;
 use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool};
 use diesel::result::Error;
+use diesel::sql_types::{Array, BigInt, Bool, Float8, Integer, Text, Timestamptz};
+
+/// Number of most recent reviews embedded in the profile detail.
+const PROFILE_REVIEW_PREVIEW: i64 = 5;
+
+/// Bound of `professional_profiles.image_url`.
+const MAX_IMAGE_URL_LENGTH: usize = 255;
+/// Longest estimated duration of a single offering, five days.
+const MAX_OFFERING_DURATION_MINUTES: i32 = 5 * 24 * 60;
+
+/// Text a search phrase is matched against: the professional's name and
+/// offered subcategories weigh most, then the category, then the free-text
+/// credentials and category description. The `simple` configuration does no
+/// stemming, so it works the same for every language users type in.
+const SEARCH_DOCUMENT_SQL: &str = r#"
+    setweight(to_tsvector('simple', professional_profiles.professional_name || ' ' || string_agg(subcategories.name, ' ')), 'A')
+    || setweight(to_tsvector('simple', categories.name), 'B')
+    || setweight(to_tsvector('simple', COALESCE(professional_profiles.credentials, '') || ' ' || COALESCE(categories.description, '')), 'C')"#;
+
+/// Filters for the professional search. Prices apply to the offerings in the
+/// requested subcategories.
+pub struct ProfileSearchFilter {
+    /// Offered subcategories; `None` searches all of them.
+    pub subcategory_ids: Option<Vec<i32>>,
+    /// Search phrase in `websearch_to_tsquery` syntax.
+    pub text: Option<String>,
+    pub lat: f64,
+    pub lng: f64,
+    pub radius_km: f64,
+    /// Only profiles whose business hours cover the current time.
+    pub open_now: bool,
+    pub min_rating: Option<f64>,
+    pub remote_available: Option<bool>,
+    pub delivery_enabled: Option<bool>,
+    pub min_price: Option<f64>,
+    pub max_price: Option<f64>,
+    pub sort: SearchSort,
+    pub cursor: Option<SearchCursor>,
+    pub limit: i64,
+}
 
 pub async fn search_services(
-    subcategory_ids_from_user: Vec<i32>,
-    lat_from_user: f64,
-    lng_from_user: f64,
+    filter: &ProfileSearchFilter,
     conn: &mut PgConnection,
-) -> Result<Vec<ProfessionalProfileDTO>, Error> {
-    let radius = 5000.0; // 5 km in meters
-    let subcategory_ids_str = subcategory_ids_from_user
-        .iter()
-        .map(|id| id.to_string())
-        .collect::<Vec<_>>()
-        .join(", ");
-    let today = Utc::now().naive_utc().date();
-    let current_day_of_week = today.weekday().num_days_from_sunday() as i32; // Sunday is 0, Saturday is 6
-
-    let raw_sql = format!(
-        r#"
+) -> Result<ProfessionalSearchPageDTO, Error> {
+    // Boxed raw queries append their binds in order, so each one gets the
+    // next numbered placeholder.
+    let mut param_index = 0;
+    let mut next_param = || {
+        param_index += 1;
+        format!("${}", param_index)
+    };
+
+    let text_query = filter.text.as_ref().map(|_| next_param());
+    let mut query = match (&filter.text, &text_query) {
+        (Some(text), Some(text_query)) => diesel::sql_query(format!(
+            r#"
     WITH RelevantProfiles AS (
-        SELECT DISTINCT professional_profiles.id
-        FROM professional_profiles
-        INNER JOIN service_offerings ON professional_profiles.id = service_offerings.professional_profile_id
-        WHERE service_offerings.subcategory_id IN ({})
-    ), RelevantBusinessHours AS (
-        SELECT
-            business_hours.professional_profile_id,
-            business_hours.opening_time,
-            business_hours.closing_time
-        FROM business_hours
-        WHERE business_hours.day_of_week = {}
-        AND business_hours.is_available = TRUE
-    )
+        SELECT service_offerings.professional_profile_id AS id,
+            MIN(service_offerings.price) AS price,
+            ts_rank({SEARCH_DOCUMENT_SQL}, websearch_to_tsquery('simple', {text_query}))::float8 AS relevance
+        FROM service_offerings
+        INNER JOIN professional_profiles ON service_offerings.professional_profile_id = professional_profiles.id
+        INNER JOIN categories ON professional_profiles.category_id = categories.id
+        INNER JOIN subcategories ON service_offerings.subcategory_id = subcategories.id
+        WHERE TRUE"#
+        ))
+        .into_boxed::<Pg>()
+        .bind::<Text, _>(text),
+        _ => diesel::sql_query(
+            r#"
+    WITH RelevantProfiles AS (
+        SELECT service_offerings.professional_profile_id AS id,
+            MIN(service_offerings.price) AS price,
+            0::float8 AS relevance
+        FROM service_offerings
+        WHERE TRUE"#,
+        )
+        .into_boxed::<Pg>(),
+    };
+    if let Some(subcategory_ids) = &filter.subcategory_ids {
+        query = query
+            .sql(format!(
+                " AND service_offerings.subcategory_id = ANY({})",
+                next_param()
+            ))
+            .bind::<Array<Integer>, _>(subcategory_ids);
+    }
+    if let Some(min_price) = filter.min_price {
+        query = query
+            .sql(format!(" AND service_offerings.price >= {}", next_param()))
+            .bind::<Float8, _>(min_price);
+    }
+    if let Some(max_price) = filter.max_price {
+        query = query
+            .sql(format!(" AND service_offerings.price <= {}", next_param()))
+            .bind::<Float8, _>(max_price);
+    }
+    query = query.sql(" GROUP BY service_offerings.professional_profile_id");
+    if let Some(text_query) = &text_query {
+        query = query.sql(format!(
+            r#", professional_profiles.id, categories.id
+        HAVING {SEARCH_DOCUMENT_SQL} @@ websearch_to_tsquery('simple', {text_query})"#
+        ));
+    }
+
+    // Hours are evaluated at each profile's local date and time. Exceptions
+    // covering that date replace the weekly hours, and a closure among them
+    // closes the day. Otherwise holiday hours replace the weekday's on
+    // holidays at the profile's address when the profile has any.
+    query = query
+        .sql(format!(
+            r#"
+    ), Candidates AS (
     SELECT
         professional_profiles.id,
         professional_profiles.image_url,
         professional_profiles.delivery_enabled,
         professional_profiles.remote_available,
         professional_profiles.average_rating,
-        professional_profile_uid,
-        addresses.street,
-        addresses.city,
-        addresses.zip,
-        addresses.lng,
-        addresses.lat,
+        professional_profiles.professional_profile_uid,
+        profile_address.street,
+        profile_address.city,
+        profile_address.zip,
+        profile_address.lng,
+        profile_address.lat,
         categories.name AS category_name,
         professionals.name AS professional_name,
-        COUNT(DISTINCT review.id) AS review_count,
-        RelevantBusinessHours.opening_time,
-        RelevantBusinessHours.closing_time
+        professional_profiles.review_count::bigint AS review_count,
+        today_hours.opening_time,
+        today_hours.closing_time,
+        COALESCE(
+            today_hours.opening_time <= profile_clock.local_time AND today_hours.closing_time > profile_clock.local_time,
+            FALSE
+        ) AS is_open_now,
+        RelevantProfiles.price,
+        RelevantProfiles.relevance,
+        ST_Distance(
+            geography(ST_MakePoint(profile_address.lng::double precision, profile_address.lat::double precision)),
+            geography(ST_MakePoint({lng}, {lat}))
+        ) / 1000.0 AS distance_km
     FROM RelevantProfiles
     INNER JOIN professional_profiles ON RelevantProfiles.id = professional_profiles.id
     INNER JOIN professionals ON professional_profiles.professional_id = professionals.id
-    INNER JOIN address_assignments ON professional_profiles.id = address_assignments.professional_profile_id
-    INNER JOIN addresses ON address_assignments.address_id = addresses.id
-    INNER JOIN service_offerings ON professional_profiles.id = service_offerings.professional_profile_id
-    INNER JOIN subcategories ON service_offerings.subcategory_id = subcategories.id
-    INNER JOIN categories ON subcategories.category_id = categories.id
-    LEFT JOIN review ON professional_profiles.id = review.professional_profile_id
-    INNER JOIN RelevantBusinessHours ON professional_profiles.id = RelevantBusinessHours.professional_profile_id
+    INNER JOIN categories ON professional_profiles.category_id = categories.id
+    CROSS JOIN LATERAL (
+        SELECT local_now::date AS local_date,
+            local_now::time AS local_time,
+            EXTRACT(DOW FROM local_now)::int AS local_weekday
+        FROM (SELECT {now} AT TIME ZONE professional_profiles.timezone AS local_now) AS clock
+    ) AS profile_clock
+    CROSS JOIN LATERAL (
+        SELECT COUNT(*) > 0 AS has_exceptions,
+            COALESCE(BOOL_OR(business_hour_exceptions.opening_time IS NULL), FALSE) AS is_closed,
+            ARRAY_AGG(business_hour_exceptions.opening_time) AS opening_times,
+            ARRAY_AGG(business_hour_exceptions.closing_time) AS closing_times
+        FROM business_hour_exceptions
+        WHERE business_hour_exceptions.professional_profile_id = professional_profiles.id
+        AND profile_clock.local_date BETWEEN business_hour_exceptions.start_date AND business_hour_exceptions.end_date
+    ) AS day_exceptions
+    INNER JOIN LATERAL (
+        SELECT addresses.street, addresses.city, addresses.state, addresses.zip, addresses.lng, addresses.lat
+        FROM address_assignments
+        INNER JOIN addresses ON address_assignments.address_id = addresses.id
+        WHERE address_assignments.professional_profile_id = professional_profiles.id
+        ORDER BY address_assignments.id
+        LIMIT 1
+    ) AS profile_address ON TRUE
+    LEFT JOIN LATERAL (
+        SELECT day_hours.opening_time, day_hours.closing_time
+        FROM (
+            SELECT exception_hours.opening_time, exception_hours.closing_time
+            FROM unnest(day_exceptions.opening_times, day_exceptions.closing_times)
+                AS exception_hours(opening_time, closing_time)
+            UNION ALL
+            SELECT business_hours.opening_time, business_hours.closing_time
+            FROM business_hours
+            WHERE business_hours.professional_profile_id = professional_profiles.id
+            AND business_hours.is_available = TRUE
+            AND business_hours.day_of_week = CASE
+                WHEN EXISTS (
+                    SELECT 1 FROM public_holidays
+                    WHERE public_holidays.holiday_date = profile_clock.local_date
+                    AND (public_holidays.state IS NULL OR public_holidays.state = profile_address.state)
+                ) AND EXISTS (
+                    SELECT 1 FROM business_hours AS holiday_hours
+                    WHERE holiday_hours.professional_profile_id = professional_profiles.id
+                    AND holiday_hours.day_of_week = {holiday}
+                ) THEN {holiday}
+                ELSE profile_clock.local_weekday
+            END
+            AND NOT day_exceptions.has_exceptions
+        ) AS day_hours
+        WHERE day_hours.opening_time < day_hours.closing_time
+        AND NOT day_exceptions.is_closed
+        ORDER BY (day_hours.opening_time <= profile_clock.local_time AND day_hours.closing_time > profile_clock.local_time) DESC,
+            day_hours.opening_time
+        LIMIT 1
+    ) AS today_hours ON TRUE
     WHERE
-        ST_DWithin(
-            geography(ST_MakePoint(addresses.lng::double precision, addresses.lat::double precision)),
-            geography(ST_MakePoint({}, {})),
-            {}
-        )
-    GROUP BY
-        professional_profiles.id,
-        addresses.street, addresses.city, addresses.zip, addresses.lng, addresses.lat,
-        categories.name, professionals.name,
-        RelevantBusinessHours.opening_time,
-        RelevantBusinessHours.closing_time;
-    "#,
-        subcategory_ids_str, current_day_of_week, lng_from_user, lat_from_user, radius
-    );
+        professional_profiles.is_active
+        AND ST_DWithin(
+            geography(ST_MakePoint(profile_address.lng::double precision, profile_address.lat::double precision)),
+            geography(ST_MakePoint({lng}, {lat})),
+            {radius}
+        )"#,
+            now = next_param(),
+            holiday = next_param(),
+            lng = next_param(),
+            lat = next_param(),
+            radius = next_param(),
+        ))
+        .bind::<Timestamptz, _>(Utc::now())
+        .bind::<Integer, _>(DayOfWeek::Holiday.to_i32())
+        .bind::<Float8, _>(filter.lng)
+        .bind::<Float8, _>(filter.lat)
+        .bind::<Float8, _>(filter.radius_km * 1000.0);
+
+    if filter.open_now {
+        query = query.sql(
+            " AND today_hours.opening_time <= profile_clock.local_time AND today_hours.closing_time > profile_clock.local_time",
+        );
+    }
+    if let Some(min_rating) = filter.min_rating {
+        query = query
+            .sql(format!(
+                " AND professional_profiles.average_rating >= {}",
+                next_param()
+            ))
+            .bind::<Float8, _>(min_rating);
+    }
+    if let Some(remote_available) = filter.remote_available {
+        query = query
+            .sql(format!(
+                " AND professional_profiles.remote_available = {}",
+                next_param()
+            ))
+            .bind::<Bool, _>(remote_available);
+    }
+    if let Some(delivery_enabled) = filter.delivery_enabled {
+        query = query
+            .sql(format!(
+                " AND professional_profiles.delivery_enabled = {}",
+                next_param()
+            ))
+            .bind::<Bool, _>(delivery_enabled);
+    }
+
+    query = query
+        .sql(format!(
+            r#"
+    )
+    SELECT ranked.* FROM (
+        SELECT Candidates.*,
+            {rating_weight} * COALESCE(Candidates.average_rating, 0) / 5
+            + {reviews_weight} * LEAST(LN(1 + Candidates.review_count) / LN(1 + {saturation}), 1)
+            + {distance_weight} * GREATEST(1 - Candidates.distance_km / {radius_km}, 0) AS match_score
+        FROM Candidates
+    ) AS ranked"#,
+            rating_weight = next_param(),
+            reviews_weight = next_param(),
+            saturation = next_param(),
+            distance_weight = next_param(),
+            radius_km = next_param(),
+        ))
+        .bind::<Float8, _>(BEST_MATCH_RATING_WEIGHT)
+        .bind::<Float8, _>(BEST_MATCH_REVIEWS_WEIGHT)
+        .bind::<Float8, _>(BEST_MATCH_REVIEW_SATURATION)
+        .bind::<Float8, _>(BEST_MATCH_DISTANCE_WEIGHT)
+        .bind::<Float8, _>(filter.radius_km);
 
-    let professional_profiles_from_db: Vec<ProfessionalProfileDTO> =
-        diesel::sql_query(raw_sql).load::<ProfessionalProfileDTO>(conn)?;
+    let key = filter.sort.key_sql();
+    let (direction, comparison) = if filter.sort.is_ascending() {
+        ("ASC", ">")
+    } else {
+        ("DESC", "<")
+    };
+    if let Some(cursor) = filter.cursor {
+        let (cursor_key, cursor_id) = (next_param(), next_param());
+        query = query
+            .sql(format!(
+                " WHERE ({key} {comparison} {cursor_key} OR ({key} = {cursor_key} AND ranked.id > {cursor_id}))"
+            ))
+            .bind::<Float8, _>(cursor.key)
+            .bind::<Integer, _>(cursor.id);
+    }
+    // One extra row tells whether another page follows.
+    query = query
+        .sql(format!(
+            " ORDER BY {key} {direction}, ranked.id ASC LIMIT {}",
+            next_param()
+        ))
+        .bind::<BigInt, _>(filter.limit + 1);
 
-    Ok(professional_profiles_from_db)
+    let mut profiles = query.load::<ProfessionalProfileDTO>(conn)?;
+    let next_cursor = if profiles.len() as i64 > filter.limit {
+        profiles.truncate(filter.limit as usize);
+        profiles
+            .last()
+            .map(|profile| SearchCursor::after(filter.sort, profile).encode())
+    } else {
+        None
+    };
+
+    Ok(ProfessionalSearchPageDTO {
+        profiles,
+        next_cursor,
+    })
 }
 
 pub async fn get_profile(
@@ -119,6 +361,7 @@ pub async fn get_profile(
 ) -> Result<ProfessionalProfileDetailDTO, Error> {
     let profile = professional_profiles::table
         .find(profile_id)
+        .filter(professional_profiles::is_active.eq(true))
         .select(ProfessionalProfile::as_select())
         .first::<ProfessionalProfile>(conn)?;
 
@@ -157,59 +400,28 @@ pub async fn get_profile(
         })
         .collect();
 
-    let reviews_from_db = Review::belonging_to(&profile)
+    // Only a preview; the full list is paged through `/profiles/{id}/reviews`.
+    let latest_reviews = Review::belonging_to(&profile)
+        .filter(review::is_hidden.eq(false))
+        .order((review::published_at.desc(), review::id.desc()))
+        .limit(PROFILE_REVIEW_PREVIEW)
         .select(Review::as_select())
-        .load::<Review>(conn)
-        .optional()?;
-
-    let review_count = reviews_from_db.as_ref().map_or(0, |reviews| reviews.len()) as i64;
-
-    let reviews = reviews_from_db.map(|reviews| {
-        reviews
-            .iter()
-            .map(|review| {
-                let review_content_assignments = ReviewContentAssignment::belonging_to(review)
-                    .select(ReviewContentAssignment::as_select())
-                    .load::<ReviewContentAssignment>(conn)
-                    .unwrap_or_default();
+        .load::<Review>(conn)?;
+    let reviews = Some(review_db::load_review_dtos(conn, latest_reviews)?);
+    let review_count = i64::from(profile.review_count);
 
-                ReviewDTO::review_to_dto(review, &review_content_assignments)
-            })
-            .collect::<Vec<ReviewDTO>>()
-    });
-    let today = Utc::now().naive_utc().date();
-    let day_of_week = today.weekday().num_days_from_sunday() as i32; // Sunday is 0, Saturday is 6
-
-    let country_code = address.as_ref().map(|addr| &addr.state); // Replace with actual field
-
-    // Determine if today is a holiday
-    // let is_today_holiday = match country_code {
-    //     Some(code) => is_holiday(code, today),
-    //     None => false,
-    // };
-    let is_today_holiday = false;
-    // Fetch business hours
-    let business_hour = if is_today_holiday {
-        // Fetch business hours for holidays (7)
-        business_hours::table
-            .filter(business_hours::professional_profile_id.eq(profile.id))
-            .filter(business_hours::day_of_week.eq(&7))
-            .first::<BusinessHours>(conn) // Use BusinessHours here
-            .optional()?
-    } else {
-        // Fetch business hours for the current day of the week
-        business_hours::table
-            .filter(business_hours::professional_profile_id.eq(profile.id))
-            .filter(business_hours::day_of_week.eq(&day_of_week))
-            .first::<BusinessHours>(conn) // Use BusinessHours here
-            .optional()?
-    };
-
-    // Check if the professional is available today
-    let (opening_time, closing_time) = match business_hour {
-        Some(bh) if bh.is_available => (bh.opening_time, bh.closing_time),
-        _ => (None, None), // Not available today
-    };
+    // Today's hours at the profile's local date, with exceptions and the
+    // holidays at its address.
+    let now = Utc::now();
+    let calendar = business_hours_db::load_calendar(conn, profile.id, now, now)?;
+    let local_now = calendar.local(now);
+    let opening_hours = calendar.intervals_on(local_now.date());
+    let is_open_now = calendar.is_open_at(now);
+    let (opening_time, closing_time) =
+        match availability::interval_at(&opening_hours, local_now.time()) {
+            Some(interval) => (Some(interval.opening_time), Some(interval.closing_time)),
+            None => (None, None), // Not available today
+        };
     let final_profile = ProfessionalProfileDetailDTO {
         id: profile.id,
         uid: profile.professional_profile_uid,
@@ -217,6 +429,9 @@ pub async fn get_profile(
         professional_name: profile.professional_name,
         opening_time: opening_time,
         closing_time: closing_time,
+        opening_hours,
+        is_open_now,
+        timezone: profile.timezone,
         image_url: profile.image_url,
         category_name: category.name,
         credentials: profile.credentials,
@@ -230,3 +445,617 @@ pub async fn get_profile(
     };
     Ok(final_profile)
 }
+
+/// Creates a profile for the calling professional in a category they have no
+/// profile in yet, optionally with an address.
+pub async fn create_profile(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    user_uid: String,
+    profile_dto: CreateProfessionalProfileDTO,
+) -> Result<OwnProfessionalProfileDTO, ProfessionalProfileError> {
+    validate_image_url(&profile_dto.image_url)?;
+    let timezone = match &profile_dto.timezone {
+        Some(name) => validate_timezone(name)?,
+        None => availability::DEFAULT_TIMEZONE.name().to_string(),
+    };
+    if let Some(address_dto) = &profile_dto.address {
+        validate_address(address_dto)?;
+    }
+
+    web::block(
+        move || -> Result<OwnProfessionalProfileDTO, ProfessionalProfileError> {
+            let mut conn = db_pool
+                .get()
+                .map_err(|e| ProfessionalProfileError::DatabasePoolError(e.to_string()))?;
+
+            conn.transaction::<_, ProfessionalProfileError, _>(|conn| {
+                // Locking the professional serialises their profile creations, so
+                // two requests cannot both pass the one-per-category check.
+                let (professional_id, professional_name) = professionals::table
+                    .filter(professionals::user_uid.eq(&user_uid))
+                    .select((professionals::id, professionals::name))
+                    .for_update()
+                    .first::<(i32, String)>(conn)
+                    .optional()?
+                    .ok_or(ProfessionalProfileError::NotProfessional)?;
+
+                let category_exists = diesel::select(diesel::dsl::exists(
+                    categories::table.find(profile_dto.category_id),
+                ))
+                .get_result::<bool>(conn)?;
+                if !category_exists {
+                    return Err(ProfessionalProfileError::CategoryNotFound);
+                }
+                let has_category_profile = diesel::select(diesel::dsl::exists(
+                    professional_profiles::table
+                        .filter(professional_profiles::professional_id.eq(professional_id))
+                        .filter(professional_profiles::category_id.eq(profile_dto.category_id)),
+                ))
+                .get_result::<bool>(conn)?;
+                if has_category_profile {
+                    return Err(ProfessionalProfileError::DuplicateCategory);
+                }
+
+                let profile = diesel::insert_into(professional_profiles::table)
+                    .values(&NewProfessionalProfile {
+                        professional_id,
+                        professional_name,
+                        category_id: profile_dto.category_id,
+                        credentials: profile_dto.credentials,
+                        delivery_enabled: profile_dto.delivery_enabled,
+                        image_url: profile_dto.image_url,
+                        remote_available: profile_dto.remote_available,
+                        professional_profile_uid: uuid::Uuid::new_v4().to_string(),
+                        timezone,
+                    })
+                    .returning(ProfessionalProfile::as_returning())
+                    .get_result(conn)?;
+
+                let address = match profile_dto.address {
+                    Some(address_dto) => Some(assign_address(conn, profile.id, &address_dto)?),
+                    None => None,
+                };
+                Ok(OwnProfessionalProfileDTO { profile, address })
+            })
+        },
+    )
+    .await
+    .map_err(|e| ProfessionalProfileError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// All profiles of the calling professional.
+pub async fn get_own_profiles(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    user_uid: String,
+) -> Result<Vec<OwnProfessionalProfileDTO>, ProfessionalProfileError> {
+    web::block(
+        move || -> Result<Vec<OwnProfessionalProfileDTO>, ProfessionalProfileError> {
+            let mut conn = db_pool
+                .get()
+                .map_err(|e| ProfessionalProfileError::DatabasePoolError(e.to_string()))?;
+
+            let profiles = professional_profiles::table
+                .inner_join(professionals::table)
+                .filter(professionals::user_uid.eq(&user_uid))
+                .order(professional_profiles::id)
+                .select(ProfessionalProfile::as_select())
+                .load::<ProfessionalProfile>(&mut conn)?;
+
+            profiles
+                .into_iter()
+                .map(|profile| {
+                    let address = load_address(&mut conn, profile.id)?;
+                    Ok(OwnProfessionalProfileDTO { profile, address })
+                })
+                .collect()
+        },
+    )
+    .await
+    .map_err(|e| ProfessionalProfileError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Replaces the editable fields of an own profile and refreshes its copy of
+/// the professional's name.
+pub async fn update_profile(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    profile_id: i32,
+    user_uid: String,
+    profile_dto: UpdateProfessionalProfileDTO,
+) -> Result<OwnProfessionalProfileDTO, ProfessionalProfileError> {
+    validate_image_url(&profile_dto.image_url)?;
+    let timezone = profile_dto
+        .timezone
+        .as_deref()
+        .map(validate_timezone)
+        .transpose()?;
+
+    web::block(
+        move || -> Result<OwnProfessionalProfileDTO, ProfessionalProfileError> {
+            let mut conn = db_pool
+                .get()
+                .map_err(|e| ProfessionalProfileError::DatabasePoolError(e.to_string()))?;
+
+            conn.transaction::<_, ProfessionalProfileError, _>(|conn| {
+                let professional_name = find_own_profile(conn, profile_id, &user_uid)?;
+                let profile = diesel::update(professional_profiles::table.find(profile_id))
+                    .set(&ProfessionalProfileChangeset {
+                        professional_name,
+                        credentials: profile_dto.credentials,
+                        delivery_enabled: profile_dto.delivery_enabled,
+                        image_url: profile_dto.image_url,
+                        remote_available: profile_dto.remote_available,
+                        timezone,
+                    })
+                    .returning(ProfessionalProfile::as_returning())
+                    .get_result(conn)?;
+
+                let address = load_address(conn, profile.id)?;
+                Ok(OwnProfessionalProfileDTO { profile, address })
+            })
+        },
+    )
+    .await
+    .map_err(|e| ProfessionalProfileError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Makes `address_dto` the address of an own profile, replacing the previous
+/// one.
+pub async fn set_profile_address(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    profile_id: i32,
+    user_uid: String,
+    address_dto: AddressDTO,
+) -> Result<AddressDTO, ProfessionalProfileError> {
+    validate_address(&address_dto)?;
+
+    web::block(move || -> Result<AddressDTO, ProfessionalProfileError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| ProfessionalProfileError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, ProfessionalProfileError, _>(|conn| {
+            find_own_profile(conn, profile_id, &user_uid)?;
+            diesel::delete(
+                address_assignments::table
+                    .filter(address_assignments::professional_profile_id.eq(profile_id)),
+            )
+            .execute(conn)?;
+            assign_address(conn, profile_id, &address_dto)
+        })
+    })
+    .await
+    .map_err(|e| ProfessionalProfileError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Offerings of a profile, cheapest first.
+pub async fn get_offerings(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    profile_id: i32,
+) -> Result<Vec<ServiceOfferingDTO>, ProfessionalProfileError> {
+    web::block(
+        move || -> Result<Vec<ServiceOfferingDTO>, ProfessionalProfileError> {
+            let mut conn = db_pool
+                .get()
+                .map_err(|e| ProfessionalProfileError::DatabasePoolError(e.to_string()))?;
+
+            let category_id = professional_profiles::table
+                .find(profile_id)
+                .filter(professional_profiles::is_active.eq(true))
+                .select(professional_profiles::category_id)
+                .first::<i32>(&mut conn)
+                .optional()?
+                .ok_or(ProfessionalProfileError::NotFound)?;
+            let offerings = service_offerings::table
+                .filter(service_offerings::professional_profile_id.eq(profile_id))
+                .order((service_offerings::price, service_offerings::id))
+                .select(ServiceOffering::as_select())
+                .load::<ServiceOffering>(&mut conn)?;
+
+            Ok(offerings
+                .iter()
+                .map(|offering| ServiceOfferingDTO::service_offering_to_dto(offering, category_id))
+                .collect())
+        },
+    )
+    .await
+    .map_err(|e| ProfessionalProfileError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+pub async fn create_offering(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    profile_id: i32,
+    user_uid: String,
+    offering_dto: ServiceOfferingInputDTO,
+) -> Result<ServiceOfferingDTO, ProfessionalProfileError> {
+    validate_offering(&offering_dto)?;
+
+    web::block(
+        move || -> Result<ServiceOfferingDTO, ProfessionalProfileError> {
+            let mut conn = db_pool
+                .get()
+                .map_err(|e| ProfessionalProfileError::DatabasePoolError(e.to_string()))?;
+
+            conn.transaction::<_, ProfessionalProfileError, _>(|conn| {
+                find_own_profile(conn, profile_id, &user_uid)?;
+                let (category_id, subcategory_name) =
+                    find_profile_subcategory(conn, profile_id, offering_dto.subcategory_id)?;
+
+                let offering = diesel::insert_into(service_offerings::table)
+                    .values(&NewServiceOffering {
+                        professional_profile_id: profile_id,
+                        subcategory_id: offering_dto.subcategory_id,
+                        price: offering_dto.price,
+                        subcategory_name,
+                        pricing_unit: offering_dto.pricing_unit.into(),
+                        estimated_duration_minutes: offering_dto.estimated_duration_minutes,
+                    })
+                    .returning(ServiceOffering::as_returning())
+                    .get_result(conn)?;
+                Ok(ServiceOfferingDTO::service_offering_to_dto(
+                    &offering,
+                    category_id,
+                ))
+            })
+        },
+    )
+    .await
+    .map_err(|e| ProfessionalProfileError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Replaces an offering of an own profile. Bookings keep the name and price
+/// they were made with.
+pub async fn update_offering(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    profile_id: i32,
+    offering_id: i32,
+    user_uid: String,
+    offering_dto: ServiceOfferingInputDTO,
+) -> Result<ServiceOfferingDTO, ProfessionalProfileError> {
+    validate_offering(&offering_dto)?;
+
+    web::block(
+        move || -> Result<ServiceOfferingDTO, ProfessionalProfileError> {
+            let mut conn = db_pool
+                .get()
+                .map_err(|e| ProfessionalProfileError::DatabasePoolError(e.to_string()))?;
+
+            conn.transaction::<_, ProfessionalProfileError, _>(|conn| {
+                find_own_profile(conn, profile_id, &user_uid)?;
+                let (category_id, subcategory_name) =
+                    find_profile_subcategory(conn, profile_id, offering_dto.subcategory_id)?;
+
+                let offering = diesel::update(
+                    service_offerings::table
+                        .filter(service_offerings::id.eq(offering_id))
+                        .filter(service_offerings::professional_profile_id.eq(profile_id)),
+                )
+                .set(&ServiceOfferingChangeset {
+                    subcategory_id: offering_dto.subcategory_id,
+                    price: offering_dto.price,
+                    subcategory_name,
+                    pricing_unit: offering_dto.pricing_unit.into(),
+                    estimated_duration_minutes: offering_dto.estimated_duration_minutes,
+                })
+                .returning(ServiceOffering::as_returning())
+                .get_result(conn)
+                .optional()?
+                .ok_or(ProfessionalProfileError::OfferingNotFound)?;
+                Ok(ServiceOfferingDTO::service_offering_to_dto(
+                    &offering,
+                    category_id,
+                ))
+            })
+        },
+    )
+    .await
+    .map_err(|e| ProfessionalProfileError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Deletes an offering of an own profile that no booking refers to.
+pub async fn delete_offering(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    profile_id: i32,
+    offering_id: i32,
+    user_uid: String,
+) -> Result<(), ProfessionalProfileError> {
+    web::block(move || -> Result<(), ProfessionalProfileError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| ProfessionalProfileError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, ProfessionalProfileError, _>(|conn| {
+            find_own_profile(conn, profile_id, &user_uid)?;
+            let offering_exists = diesel::select(diesel::dsl::exists(
+                service_offerings::table
+                    .filter(service_offerings::id.eq(offering_id))
+                    .filter(service_offerings::professional_profile_id.eq(profile_id)),
+            ))
+            .get_result::<bool>(conn)?;
+            if !offering_exists {
+                return Err(ProfessionalProfileError::OfferingNotFound);
+            }
+
+            let is_booked = diesel::select(diesel::dsl::exists(
+                bookings::table
+                    .filter(bookings::service_offering_id.eq(offering_id))
+                    .select(bookings::id),
+            ))
+            .get_result::<bool>(conn)?;
+            if is_booked {
+                return Err(ProfessionalProfileError::OfferingInUse);
+            }
+
+            diesel::delete(service_offerings::table.find(offering_id)).execute(conn)?;
+            Ok(())
+        })
+    })
+    .await
+    .map_err(|e| ProfessionalProfileError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Checks that the profile belongs to the caller and locks it. Returns the
+/// professional's current name.
+pub fn find_own_profile(
+    conn: &mut PgConnection,
+    profile_id: i32,
+    user_uid: &str,
+) -> Result<String, ProfessionalProfileError> {
+    let (owner_uid, professional_name): (String, String) = professional_profiles::table
+        .inner_join(professionals::table)
+        .filter(professional_profiles::id.eq(profile_id))
+        .select((professionals::user_uid, professionals::name))
+        .for_update()
+        .first(conn)
+        .optional()?
+        .ok_or(ProfessionalProfileError::NotFound)?;
+
+    if owner_uid != user_uid {
+        return Err(ProfessionalProfileError::NotOwner);
+    }
+    Ok(professional_name)
+}
+
+/// The profile's category and the name of `subcategory_id`, which must be
+/// one of that category's subcategories.
+fn find_profile_subcategory(
+    conn: &mut PgConnection,
+    profile_id: i32,
+    subcategory_id: i32,
+) -> Result<(i32, String), ProfessionalProfileError> {
+    let category_id = professional_profiles::table
+        .find(profile_id)
+        .select(professional_profiles::category_id)
+        .first::<i32>(conn)?;
+    let (subcategory_category_id, subcategory_name) = subcategories::table
+        .find(subcategory_id)
+        .select((subcategories::category_id, subcategories::name))
+        .first::<(i32, String)>(conn)
+        .optional()?
+        .ok_or_else(|| {
+            ProfessionalProfileError::InvalidOffering("subcategory not found".to_string())
+        })?;
+
+    if subcategory_category_id != category_id {
+        return Err(ProfessionalProfileError::InvalidOffering(
+            "subcategory does not belong to the profile's category".to_string(),
+        ));
+    }
+    Ok((category_id, subcategory_name))
+}
+
+/// Reuses a matching address row or inserts one, and assigns it to the
+/// profile.
+fn assign_address(
+    conn: &mut PgConnection,
+    profile_id: i32,
+    address_dto: &AddressDTO,
+) -> Result<AddressDTO, ProfessionalProfileError> {
+    let address_id = match address_db::find_address(conn, address_dto)? {
+        Some(address_id) => address_id,
+        None => address_db::insert_address(conn, address_dto)?,
+    };
+    diesel::insert_into(address_assignments::table)
+        .values(&NewAddressAssignment {
+            address_id,
+            professional_profile_id: profile_id,
+        })
+        .execute(conn)?;
+
+    let address = addresses::table
+        .find(address_id)
+        .select(Address::as_select())
+        .first::<Address>(conn)?;
+    Ok(AddressDTO::address_to_dto(&address))
+}
+
+fn load_address(conn: &mut PgConnection, profile_id: i32) -> QueryResult<Option<AddressDTO>> {
+    let address = address_assignments::table
+        .inner_join(addresses::table)
+        .filter(address_assignments::professional_profile_id.eq(profile_id))
+        .order(address_assignments::id)
+        .select(Address::as_select())
+        .first::<Address>(conn)
+        .optional()?;
+    Ok(address.as_ref().map(AddressDTO::address_to_dto))
+}
+
+fn validate_image_url(image_url: &Option<String>) -> Result<(), ProfessionalProfileError> {
+    if image_url
+        .as_ref()
+        .is_some_and(|url| url.chars().count() > MAX_IMAGE_URL_LENGTH)
+    {
+        return Err(ProfessionalProfileError::InvalidProfile(format!(
+            "image_url must be at most {} characters",
+            MAX_IMAGE_URL_LENGTH
+        )));
+    }
+    Ok(())
+}
+
+/// Canonical name of the zone `name`.
+fn validate_timezone(name: &str) -> Result<String, ProfessionalProfileError> {
+    let timezone = availability::parse_timezone(name.trim())
+        .map_err(ProfessionalProfileError::InvalidProfile)?;
+    Ok(timezone.name().to_string())
+}
+
+fn validate_offering(
+    offering_dto: &ServiceOfferingInputDTO,
+) -> Result<(), ProfessionalProfileError> {
+    if !offering_dto.price.is_finite() || offering_dto.price <= 0.0 {
+        return Err(ProfessionalProfileError::InvalidOffering(
+            "price must be positive".to_string(),
+        ));
+    }
+    if offering_dto
+        .estimated_duration_minutes
+        .is_some_and(|minutes| !(1..=MAX_OFFERING_DURATION_MINUTES).contains(&minutes))
+    {
+        return Err(ProfessionalProfileError::InvalidOffering(format!(
+            "estimated_duration_minutes must be between 1 and {}",
+            MAX_OFFERING_DURATION_MINUTES
+        )));
+    }
+    Ok(())
+}
+
+/// Profiles are found by distance, so their address needs coordinates.
+fn validate_address(address_dto: &AddressDTO) -> Result<(), ProfessionalProfileError> {
+    let invalid = |message: &str| {
+        Err(ProfessionalProfileError::InvalidProfile(
+            message.to_string(),
+        ))
+    };
+    if [&address_dto.street, &address_dto.city, &address_dto.zip]
+        .iter()
+        .any(|field| field.trim().is_empty())
+    {
+        return invalid("address needs a street, city and zip");
+    }
+    match (address_dto.lat, address_dto.lng) {
+        (Some(lat), Some(lng))
+            if (-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lng) =>
+        {
+            Ok(())
+        }
+        _ => invalid("address needs valid lat and lng"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::professional_aggregate::pricing_unit::PricingUnit;
+    use crate::test_support::{
+        insert_fixture, request_as, status_of, test_app, test_pool, PROFESSIONAL,
+    };
+    use actix_web::http::{Method, StatusCode};
+
+    fn address(street: &str, lat: Option<f64>, lng: Option<f64>) -> AddressDTO {
+        AddressDTO {
+            street: street.to_string(),
+            city: "Berlin".to_string(),
+            state: "BE".to_string(),
+            zip: "10115".to_string(),
+            lat,
+            lng,
+        }
+    }
+
+    #[test]
+    fn addresses_need_fields_and_valid_coordinates() {
+        assert!(validate_address(&address("Invalidenstr. 1", Some(52.5), Some(13.4))).is_ok());
+        assert!(validate_address(&address("Edge", Some(-90.0), Some(180.0))).is_ok());
+
+        for invalid in [
+            address("  ", Some(52.5), Some(13.4)),
+            address("Invalidenstr. 1", None, Some(13.4)),
+            address("Invalidenstr. 1", Some(52.5), None),
+            address("Invalidenstr. 1", Some(90.5), Some(13.4)),
+            address("Invalidenstr. 1", Some(52.5), Some(-180.5)),
+        ] {
+            assert!(matches!(
+                validate_address(&invalid),
+                Err(ProfessionalProfileError::InvalidProfile(_))
+            ));
+        }
+    }
+
+    fn offering(price: f64, estimated_duration_minutes: Option<i32>) -> ServiceOfferingInputDTO {
+        ServiceOfferingInputDTO {
+            subcategory_id: 1,
+            price,
+            pricing_unit: PricingUnit::Hourly,
+            estimated_duration_minutes,
+        }
+    }
+
+    #[test]
+    fn offerings_need_a_positive_price_and_a_bounded_duration() {
+        assert!(validate_offering(&offering(40.0, None)).is_ok());
+        assert!(validate_offering(&offering(40.0, Some(1))).is_ok());
+        assert!(validate_offering(&offering(40.0, Some(MAX_OFFERING_DURATION_MINUTES))).is_ok());
+
+        for invalid in [
+            offering(0.0, None),
+            offering(-5.0, None),
+            offering(f64::NAN, None),
+            offering(f64::INFINITY, None),
+            offering(40.0, Some(0)),
+            offering(40.0, Some(MAX_OFFERING_DURATION_MINUTES + 1)),
+        ] {
+            assert!(matches!(
+                validate_offering(&invalid),
+                Err(ProfessionalProfileError::InvalidOffering(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn image_urls_are_limited_in_length() {
+        assert!(validate_image_url(&None).is_ok());
+        assert!(validate_image_url(&Some("x".repeat(MAX_IMAGE_URL_LENGTH))).is_ok());
+        assert!(matches!(
+            validate_image_url(&Some("x".repeat(MAX_IMAGE_URL_LENGTH + 1))),
+            Err(ProfessionalProfileError::InvalidProfile(_))
+        ));
+    }
+
+    #[actix_web::test]
+    async fn profile_updates_keep_the_timezone_unless_given() {
+        let Some(pool) = test_pool() else {
+            return;
+        };
+        let fixture = insert_fixture(&pool);
+        diesel::update(professional_profiles::table.find(fixture.profile_id))
+            .set(professional_profiles::timezone.eq("America/New_York"))
+            .execute(&mut pool.get().unwrap())
+            .unwrap();
+        let app = test_app!(pool);
+
+        let profile = format!("/profiles/{}", fixture.profile_id);
+        for (timezone, expected) in [
+            (None, "America/New_York"),
+            (Some("Asia/Tokyo"), "Asia/Tokyo"),
+        ] {
+            let update = serde_json::json!({
+                "delivery_enabled": false,
+                "remote_available": true,
+                "timezone": timezone,
+            });
+            assert_eq!(
+                status_of!(
+                    app,
+                    request_as(Method::PUT, &profile, PROFESSIONAL).set_json(update)
+                ),
+                StatusCode::OK
+            );
+            let stored: String = professional_profiles::table
+                .find(fixture.profile_id)
+                .select(professional_profiles::timezone)
+                .get_result(&mut pool.get().unwrap())
+                .unwrap();
+            assert_eq!(stored, expected);
+        }
+    }
+}
@@ -0,0 +1,721 @@
+use crate::errors::review_errors::ReviewError;
+use crate::models::booking_aggregate::booking::Booking;
+use crate::models::booking_aggregate::booking_status::BookingStatus;
+use crate::models::dtos::review_dto::{
+    CreateReviewDTO, ModerationAction, ReportedReviewDTO, ReviewDTO, ReviewPageDTO, ReviewReplyDTO,
+    ReviewReportDTO, UpdateReviewDTO,
+};
+use crate::models::professional_aggregate::professional_profile::ProfessionalProfile;
+use crate::models::review_aggregate::review::{NewReview, Review};
+use crate::models::review_aggregate::review_content_assignments::{
+    NewReviewContentAssignment, ReviewContentAssignment,
+};
+use crate::models::review_aggregate::review_query::{self, ReviewCursor, ReviewSort};
+use crate::models::review_aggregate::review_reply::{NewReviewReply, ReviewReply};
+use crate::models::review_aggregate::review_report::{NewReviewReport, ReportStatus, ReviewReport};
+use crate::schema::schema::{
+    bookings, professional_profiles, professionals, review, review_content_assignments,
+    review_replies, review_reports, users,
+};
+use actix_web::web;
+use chrono::Utc;
+use diesel::dsl::count_star;
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use std::collections::HashMap;
+
+const MIN_RATING: f64 = 1.0;
+const MAX_RATING: f64 = 5.0;
+/// Pending reports from this many users hide a review until a moderator
+/// resolves them.
+const AUTO_HIDE_REPORT_COUNT: i64 = 3;
+
+/// The unique index allowing one review per booking.
+const REVIEW_BOOKING_KEY: &str = "review_booking_id_key";
+
+/// Options for paging through a profile's reviews.
+pub struct ReviewPageFilter {
+    pub sort: ReviewSort,
+    /// Only reviews with this many stars, 1 to 5.
+    pub rating: Option<i32>,
+    pub cursor: Option<ReviewCursor>,
+    pub limit: i64,
+}
+
+/// Visible reviews of a profile, one page at a time.
+pub async fn get_profile_reviews(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    profile_id: i32,
+    filter: ReviewPageFilter,
+) -> Result<ReviewPageDTO, ReviewError> {
+    web::block(move || -> Result<ReviewPageDTO, ReviewError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| ReviewError::DatabasePoolError(e.to_string()))?;
+
+        let profile_exists = diesel::select(diesel::dsl::exists(
+            professional_profiles::table
+                .find(profile_id)
+                .filter(professional_profiles::is_active.eq(true)),
+        ))
+        .get_result::<bool>(&mut conn)?;
+        if !profile_exists {
+            return Err(ReviewError::ProfileNotFound);
+        }
+
+        let mut query = review::table
+            .filter(review::professional_profile_id.eq(profile_id))
+            .filter(review::is_hidden.eq(false))
+            .select(Review::as_select())
+            .into_boxed();
+
+        if let Some(stars) = filter.rating {
+            query = query.filter(review::rate.ge(f64::from(stars)));
+            if stars < 5 {
+                query = query.filter(review::rate.lt(f64::from(stars + 1)));
+            }
+        }
+        query = match filter.cursor {
+            Some(ReviewCursor::Published { published_at, id }) => query.filter(
+                review::published_at
+                    .lt(published_at)
+                    .or(review::published_at.eq(published_at).and(review::id.lt(id))),
+            ),
+            Some(ReviewCursor::Rate { rate, id }) if filter.sort == ReviewSort::Lowest => query
+                .filter(
+                    review::rate
+                        .gt(rate)
+                        .or(review::rate.eq(rate).and(review::id.lt(id))),
+                ),
+            Some(ReviewCursor::Rate { rate, id }) => query.filter(
+                review::rate
+                    .lt(rate)
+                    .or(review::rate.eq(rate).and(review::id.lt(id))),
+            ),
+            None => query,
+        };
+        query = match filter.sort {
+            ReviewSort::Newest => query.order((review::published_at.desc(), review::id.desc())),
+            ReviewSort::Highest => query.order((review::rate.desc(), review::id.desc())),
+            ReviewSort::Lowest => query.order((review::rate.asc(), review::id.desc())),
+        };
+
+        // One extra row tells whether another page follows.
+        let mut reviews = query.limit(filter.limit + 1).load::<Review>(&mut conn)?;
+        let next_cursor = if reviews.len() as i64 > filter.limit {
+            reviews.truncate(filter.limit as usize);
+            reviews.last().map(|last| {
+                ReviewCursor::after(filter.sort, last.rate, last.published_at, last.id).encode()
+            })
+        } else {
+            None
+        };
+
+        let rate_counts: Vec<(f64, i64)> = review::table
+            .filter(review::professional_profile_id.eq(profile_id))
+            .filter(review::is_hidden.eq(false))
+            .group_by(review::rate)
+            .select((review::rate, count_star()))
+            .load(&mut conn)?;
+        let histogram = review_query::rating_histogram(&rate_counts);
+
+        Ok(ReviewPageDTO {
+            reviews: load_review_dtos(&mut conn, reviews)?,
+            next_cursor,
+            review_count: histogram.iter().sum(),
+            histogram,
+        })
+    })
+    .await
+    .map_err(|e| ReviewError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Builds DTOs for `reviews`, loading all their images and replies with one
+/// query each.
+pub fn load_review_dtos(
+    conn: &mut PgConnection,
+    reviews: Vec<Review>,
+) -> QueryResult<Vec<ReviewDTO>> {
+    let review_ids: Vec<i32> = reviews.iter().map(|review| review.id).collect();
+
+    let mut images: HashMap<i32, Vec<ReviewContentAssignment>> = HashMap::new();
+    for assignment in review_content_assignments::table
+        .filter(review_content_assignments::review_id.eq_any(&review_ids))
+        .order(review_content_assignments::id.asc())
+        .select(ReviewContentAssignment::as_select())
+        .load::<ReviewContentAssignment>(conn)?
+    {
+        images
+            .entry(assignment.review_id)
+            .or_default()
+            .push(assignment);
+    }
+
+    let mut replies: HashMap<i32, ReviewReply> = review_replies::table
+        .filter(review_replies::review_id.eq_any(&review_ids))
+        .select(ReviewReply::as_select())
+        .load::<ReviewReply>(conn)?
+        .into_iter()
+        .map(|reply| (reply.review_id, reply))
+        .collect();
+
+    Ok(reviews
+        .iter()
+        .map(|review| {
+            let review_images = images.remove(&review.id).unwrap_or_default();
+            ReviewDTO::review_to_dto(review, &review_images, replies.remove(&review.id))
+        })
+        .collect())
+}
+
+/// Reviews a completed booking on behalf of its customer and refreshes the
+/// profile's rating in the same transaction.
+pub async fn create_review(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    user_uid: String,
+    review_dto: CreateReviewDTO,
+) -> Result<ReviewDTO, ReviewError> {
+    validate_rating(review_dto.rate)?;
+
+    web::block(move || -> Result<ReviewDTO, ReviewError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| ReviewError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, ReviewError, _>(|conn| {
+            let booking = bookings::table
+                .find(review_dto.booking_id)
+                .first::<Booking>(conn)
+                .optional()?
+                .ok_or(ReviewError::BookingNotFound)?;
+            if booking.customer_uid != user_uid {
+                return Err(ReviewError::NotBookingCustomer);
+            }
+            if booking.status != i32::from(BookingStatus::Completed) {
+                return Err(ReviewError::BookingNotCompleted);
+            }
+
+            let already_reviewed = diesel::select(diesel::dsl::exists(
+                review::table.filter(review::booking_id.eq(booking.id)),
+            ))
+            .get_result::<bool>(conn)?;
+            if already_reviewed {
+                return Err(ReviewError::AlreadyReviewed);
+            }
+
+            let profile_id: i32 = professional_profiles::table
+                .filter(
+                    professional_profiles::professional_profile_uid
+                        .eq(&booking.professional_profile_uid),
+                )
+                .select(professional_profiles::id)
+                .first(conn)?;
+            let (user_id, user_name): (i32, String) = users::table
+                .filter(users::user_uid.eq(&user_uid))
+                .select((users::id, users::name))
+                .first(conn)
+                .optional()?
+                .ok_or(ReviewError::UserNotFound)?;
+
+            let new_review = NewReview {
+                user_id,
+                user_name,
+                professional_profile_id: profile_id,
+                message: review_dto.message,
+                rate: review_dto.rate,
+                published_at: Utc::now(),
+                booking_id: Some(booking.id),
+            };
+            // A concurrent request for the same booking can pass the check
+            // above; the unique index on the booking settles the race.
+            let created_review = diesel::insert_into(review::table)
+                .values(&new_review)
+                .returning(Review::as_returning())
+                .get_result(conn)
+                .map_err(|e| match e {
+                    DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, ref info)
+                        if info.constraint_name() == Some(REVIEW_BOOKING_KEY) =>
+                    {
+                        ReviewError::AlreadyReviewed
+                    }
+                    e => ReviewError::DieselError(e),
+                })?;
+
+            let images = replace_images(
+                conn,
+                created_review.id,
+                review_dto.image_urls.unwrap_or_default(),
+            )?;
+            refresh_profile_rating(conn, profile_id)?;
+
+            Ok(ReviewDTO::review_to_dto(&created_review, &images, None))
+        })
+    })
+    .await
+    .map_err(|e| ReviewError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+pub async fn update_review(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    review_id: i32,
+    user_uid: String,
+    review_dto: UpdateReviewDTO,
+) -> Result<ReviewDTO, ReviewError> {
+    validate_rating(review_dto.rate)?;
+
+    web::block(move || -> Result<ReviewDTO, ReviewError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| ReviewError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, ReviewError, _>(|conn| {
+            let existing_review = find_own_review(conn, review_id, &user_uid)?;
+
+            let updated_review = diesel::update(review::table.find(review_id))
+                .set((
+                    review::rate.eq(review_dto.rate),
+                    review::message.eq(&review_dto.message),
+                ))
+                .returning(Review::as_returning())
+                .get_result(conn)?;
+
+            let images = match review_dto.image_urls {
+                Some(image_urls) => replace_images(conn, review_id, image_urls)?,
+                None => review_content_assignments::table
+                    .filter(review_content_assignments::review_id.eq(review_id))
+                    .select(ReviewContentAssignment::as_select())
+                    .load(conn)?,
+            };
+            refresh_profile_rating(conn, existing_review.professional_profile_id)?;
+            let reply = review_replies::table
+                .filter(review_replies::review_id.eq(review_id))
+                .select(ReviewReply::as_select())
+                .first(conn)
+                .optional()?;
+
+            Ok(ReviewDTO::review_to_dto(&updated_review, &images, reply))
+        })
+    })
+    .await
+    .map_err(|e| ReviewError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+pub async fn delete_review(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    review_id: i32,
+    user_uid: String,
+) -> Result<(), ReviewError> {
+    web::block(move || -> Result<(), ReviewError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| ReviewError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, ReviewError, _>(|conn| {
+            let existing_review = find_own_review(conn, review_id, &user_uid)?;
+
+            diesel::delete(
+                review_content_assignments::table
+                    .filter(review_content_assignments::review_id.eq(review_id)),
+            )
+            .execute(conn)?;
+            diesel::delete(review::table.find(review_id)).execute(conn)?;
+            refresh_profile_rating(conn, existing_review.professional_profile_id)?;
+
+            Ok(())
+        })
+    })
+    .await
+    .map_err(|e| ReviewError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Adds or replaces the public reply of the reviewed professional.
+pub async fn reply_to_review(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    review_id: i32,
+    user_uid: String,
+    reply_dto: ReviewReplyDTO,
+) -> Result<ReviewReply, ReviewError> {
+    if reply_dto.message.trim().is_empty() {
+        return Err(ReviewError::InvalidMessage);
+    }
+
+    web::block(move || -> Result<ReviewReply, ReviewError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| ReviewError::DatabasePoolError(e.to_string()))?;
+
+        let profile_id = find_reviewed_profile_of_owner(&mut conn, review_id, &user_uid)?;
+        let new_reply = NewReviewReply {
+            review_id,
+            professional_profile_id: profile_id,
+            message: reply_dto.message,
+            created_at: Utc::now(),
+        };
+
+        diesel::insert_into(review_replies::table)
+            .values(&new_reply)
+            .on_conflict(review_replies::review_id)
+            .do_update()
+            .set((
+                review_replies::message.eq(&new_reply.message),
+                review_replies::updated_at.eq(Some(Utc::now())),
+            ))
+            .returning(ReviewReply::as_returning())
+            .get_result(&mut conn)
+            .map_err(ReviewError::from)
+    })
+    .await
+    .map_err(|e| ReviewError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+pub async fn delete_reply(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    review_id: i32,
+    user_uid: String,
+) -> Result<(), ReviewError> {
+    web::block(move || -> Result<(), ReviewError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| ReviewError::DatabasePoolError(e.to_string()))?;
+
+        find_reviewed_profile_of_owner(&mut conn, review_id, &user_uid)?;
+        let deleted =
+            diesel::delete(review_replies::table.filter(review_replies::review_id.eq(review_id)))
+                .execute(&mut conn)?;
+
+        if deleted == 0 {
+            return Err(ReviewError::ReplyNotFound);
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| ReviewError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Files a report against a review. Once enough users have reported it, the
+/// review is hidden until a moderator resolves the reports.
+pub async fn report_review(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    review_id: i32,
+    user_uid: String,
+    report_dto: ReviewReportDTO,
+) -> Result<ReviewReport, ReviewError> {
+    if report_dto.reason.trim().is_empty() {
+        return Err(ReviewError::InvalidMessage);
+    }
+
+    web::block(move || -> Result<ReviewReport, ReviewError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| ReviewError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, ReviewError, _>(|conn| {
+            let reported_review = review::table
+                .find(review_id)
+                .select(Review::as_select())
+                .for_update()
+                .first(conn)
+                .optional()?
+                .ok_or(ReviewError::NotFound)?;
+
+            let new_report = NewReviewReport {
+                review_id,
+                reporter_uid: user_uid.clone(),
+                reason: report_dto.reason,
+                status: ReportStatus::Pending.into(),
+                created_at: Utc::now(),
+            };
+            let report = diesel::insert_into(review_reports::table)
+                .values(&new_report)
+                .returning(ReviewReport::as_returning())
+                .get_result(conn)
+                .map_err(|e| match e {
+                    DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                        ReviewError::AlreadyReported
+                    }
+                    e => ReviewError::DieselError(e),
+                })?;
+
+            let pending_reports: i64 = review_reports::table
+                .filter(review_reports::review_id.eq(review_id))
+                .filter(review_reports::status.eq(i32::from(ReportStatus::Pending)))
+                .count()
+                .get_result(conn)?;
+            if !reported_review.is_hidden && pending_reports >= AUTO_HIDE_REPORT_COUNT {
+                set_hidden(conn, &reported_review, true)?;
+            }
+
+            Ok(report)
+        })
+    })
+    .await
+    .map_err(|e| ReviewError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Pending reports, oldest first, with the reported reviews.
+pub async fn get_moderation_queue(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<ReportedReviewDTO>, ReviewError> {
+    web::block(move || -> Result<Vec<ReportedReviewDTO>, ReviewError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| ReviewError::DatabasePoolError(e.to_string()))?;
+
+        let reports = review_reports::table
+            .inner_join(review::table)
+            .filter(review_reports::status.eq(i32::from(ReportStatus::Pending)))
+            .order((review_reports::created_at.asc(), review_reports::id.asc()))
+            .limit(limit)
+            .offset(offset)
+            .select((ReviewReport::as_select(), Review::as_select()))
+            .load::<(ReviewReport, Review)>(&mut conn)?;
+
+        let review_ids: Vec<i32> = reports.iter().map(|(report, _)| report.review_id).collect();
+        let mut images: HashMap<i32, Vec<ReviewContentAssignment>> = HashMap::new();
+        for assignment in review_content_assignments::table
+            .filter(review_content_assignments::review_id.eq_any(&review_ids))
+            .select(ReviewContentAssignment::as_select())
+            .load::<ReviewContentAssignment>(&mut conn)?
+        {
+            images
+                .entry(assignment.review_id)
+                .or_default()
+                .push(assignment);
+        }
+
+        let no_images = Vec::new();
+        Ok(reports
+            .into_iter()
+            .map(|(report, reported_review)| {
+                let review_images = images.get(&reported_review.id).unwrap_or(&no_images);
+                ReportedReviewDTO {
+                    review: ReviewDTO::review_to_dto(&reported_review, review_images, None),
+                    report,
+                }
+            })
+            .collect())
+    })
+    .await
+    .map_err(|e| ReviewError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Hides the reported review while its reports are still being looked at.
+pub async fn hide_reported_review(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    report_id: i32,
+) -> Result<(), ReviewError> {
+    web::block(move || -> Result<(), ReviewError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| ReviewError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, ReviewError, _>(|conn| {
+            let (_, reported_review) = find_pending_report(conn, report_id)?;
+            if !reported_review.is_hidden {
+                set_hidden(conn, &reported_review, true)?;
+            }
+            Ok(())
+        })
+    })
+    .await
+    .map_err(|e| ReviewError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Settles all pending reports of the reported review. `Remove` keeps the
+/// review hidden, `Dismiss` shows it again.
+pub async fn resolve_report(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    report_id: i32,
+    moderator_uid: String,
+    action: ModerationAction,
+) -> Result<ReviewReport, ReviewError> {
+    web::block(move || -> Result<ReviewReport, ReviewError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| ReviewError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, ReviewError, _>(|conn| {
+            let (_, reported_review) = find_pending_report(conn, report_id)?;
+            let (hidden, status) = match action {
+                ModerationAction::Remove => (true, ReportStatus::Upheld),
+                ModerationAction::Dismiss => (false, ReportStatus::Dismissed),
+            };
+            if reported_review.is_hidden != hidden {
+                set_hidden(conn, &reported_review, hidden)?;
+            }
+
+            diesel::update(
+                review_reports::table
+                    .filter(review_reports::review_id.eq(reported_review.id))
+                    .filter(review_reports::status.eq(i32::from(ReportStatus::Pending))),
+            )
+            .set((
+                review_reports::status.eq(i32::from(status)),
+                review_reports::resolved_at.eq(Some(Utc::now())),
+                review_reports::resolved_by_uid.eq(Some(&moderator_uid)),
+            ))
+            .execute(conn)?;
+
+            review_reports::table
+                .find(report_id)
+                .select(ReviewReport::as_select())
+                .first(conn)
+                .map_err(ReviewError::from)
+        })
+    })
+    .await
+    .map_err(|e| ReviewError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+fn find_pending_report(
+    conn: &mut PgConnection,
+    report_id: i32,
+) -> Result<(ReviewReport, Review), ReviewError> {
+    let (report, reported_review) = review_reports::table
+        .inner_join(review::table)
+        .filter(review_reports::id.eq(report_id))
+        .select((ReviewReport::as_select(), Review::as_select()))
+        .for_update()
+        .first::<(ReviewReport, Review)>(conn)
+        .optional()?
+        .ok_or(ReviewError::ReportNotFound)?;
+
+    if report.status != i32::from(ReportStatus::Pending) {
+        return Err(ReviewError::ReportResolved);
+    }
+    Ok((report, reported_review))
+}
+
+/// Hidden reviews do not count towards the profile's rating.
+fn set_hidden(
+    conn: &mut PgConnection,
+    target_review: &Review,
+    hidden: bool,
+) -> Result<(), ReviewError> {
+    diesel::update(review::table.find(target_review.id))
+        .set(review::is_hidden.eq(hidden))
+        .execute(conn)?;
+    refresh_profile_rating(conn, target_review.professional_profile_id)
+}
+
+/// Checks that `user_uid` owns the profile the review is about and returns
+/// that profile's id.
+fn find_reviewed_profile_of_owner(
+    conn: &mut PgConnection,
+    review_id: i32,
+    user_uid: &str,
+) -> Result<i32, ReviewError> {
+    let (profile_id, owner_uid): (i32, String) = review::table
+        .inner_join(professional_profiles::table.inner_join(professionals::table))
+        .filter(review::id.eq(review_id))
+        .select((professional_profiles::id, professionals::user_uid))
+        .first(conn)
+        .optional()?
+        .ok_or(ReviewError::NotFound)?;
+
+    if owner_uid != user_uid {
+        return Err(ReviewError::NotProfileOwner);
+    }
+    Ok(profile_id)
+}
+
+fn validate_rating(rate: f64) -> Result<(), ReviewError> {
+    if (MIN_RATING..=MAX_RATING).contains(&rate) {
+        Ok(())
+    } else {
+        Err(ReviewError::InvalidRating)
+    }
+}
+
+fn find_own_review(
+    conn: &mut PgConnection,
+    review_id: i32,
+    user_uid: &str,
+) -> Result<Review, ReviewError> {
+    let (existing_review, author_uid) = review::table
+        .inner_join(users::table)
+        .filter(review::id.eq(review_id))
+        .select((Review::as_select(), users::user_uid))
+        .for_update()
+        .first::<(Review, String)>(conn)
+        .optional()?
+        .ok_or(ReviewError::NotFound)?;
+
+    if author_uid != user_uid {
+        return Err(ReviewError::NotAuthor);
+    }
+    Ok(existing_review)
+}
+
+fn replace_images(
+    conn: &mut PgConnection,
+    review_id: i32,
+    image_urls: Vec<String>,
+) -> Result<Vec<ReviewContentAssignment>, ReviewError> {
+    diesel::delete(
+        review_content_assignments::table
+            .filter(review_content_assignments::review_id.eq(review_id)),
+    )
+    .execute(conn)?;
+
+    let new_assignments: Vec<NewReviewContentAssignment> = image_urls
+        .into_iter()
+        .map(|image_url| NewReviewContentAssignment {
+            review_id,
+            image_url,
+        })
+        .collect();
+
+    diesel::insert_into(review_content_assignments::table)
+        .values(&new_assignments)
+        .returning(ReviewContentAssignment::as_returning())
+        .get_results(conn)
+        .map_err(ReviewError::from)
+}
+
+/// Recomputes the profile's average rating and review count. The profile row
+/// is locked first so concurrent review changes are applied one at a time.
+fn refresh_profile_rating(conn: &mut PgConnection, profile_id: i32) -> Result<(), ReviewError> {
+    let mut profile = professional_profiles::table
+        .find(profile_id)
+        .select(ProfessionalProfile::as_select())
+        .for_update()
+        .first::<ProfessionalProfile>(conn)?;
+
+    let ratings: Vec<f64> = review::table
+        .filter(review::professional_profile_id.eq(profile_id))
+        .filter(review::is_hidden.eq(false))
+        .select(review::rate)
+        .load(conn)?;
+    profile.update_average_rating(&ratings);
+
+    diesel::update(professional_profiles::table.find(profile_id))
+        .set((
+            professional_profiles::average_rating.eq(profile.average_rating),
+            professional_profiles::review_count.eq(profile.review_count),
+        ))
+        .execute(conn)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ratings_must_be_within_the_star_range() {
+        for rate in [MIN_RATING, 3.5, MAX_RATING] {
+            assert!(validate_rating(rate).is_ok(), "{}", rate);
+        }
+        for rate in [MIN_RATING - 0.5, MAX_RATING + 0.5, f64::NAN] {
+            assert!(matches!(
+                validate_rating(rate),
+                Err(ReviewError::InvalidRating)
+            ));
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use super::account_db;
+use crate::errors::role_errors::RoleError;
+use crate::models::user_aggregate::role::Role;
+use crate::schema::schema::{professionals, user_roles, users};
+use actix_web::web;
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool};
+
+/// The roles stored for `user_uid`, in role order.
+pub fn roles_of(conn: &mut PgConnection, user_uid: &str) -> Result<Vec<Role>, RoleError> {
+    user_roles::table
+        .filter(user_roles::user_uid.eq(user_uid))
+        .select(user_roles::role)
+        .order(user_roles::role)
+        .load::<i32>(conn)?
+        .into_iter()
+        .map(Role::try_from)
+        .collect()
+}
+
+/// Gives `user_uid` the role `role`; granting a role twice is a no-op.
+pub fn add_role(conn: &mut PgConnection, user_uid: &str, role: Role) -> QueryResult<()> {
+    diesel::insert_into(user_roles::table)
+        .values((
+            user_roles::user_uid.eq(user_uid),
+            user_roles::role.eq(i32::from(role)),
+        ))
+        .on_conflict_do_nothing()
+        .execute(conn)?;
+    Ok(())
+}
+
+pub async fn get_roles(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    user_uid: String,
+) -> Result<Vec<Role>, RoleError> {
+    web::block(move || -> Result<Vec<Role>, RoleError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| RoleError::DatabasePoolError(e.to_string()))?;
+
+        roles_of(&mut conn, &user_uid)
+    })
+    .await
+    .map_err(|e| RoleError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// The roles of the caller `user_uid`, or `None` when they deleted their
+/// account.
+pub async fn get_caller_roles(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    user_uid: String,
+) -> Result<Option<Vec<Role>>, RoleError> {
+    web::block(move || -> Result<Option<Vec<Role>>, RoleError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| RoleError::DatabasePoolError(e.to_string()))?;
+
+        if account_db::is_deleted(&mut conn, &user_uid)? {
+            return Ok(None);
+        }
+        roles_of(&mut conn, &user_uid).map(Some)
+    })
+    .await
+    .map_err(|e| RoleError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Grants `role` to a registered user and returns all of their roles.
+pub async fn grant_role(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    user_uid: String,
+    role: Role,
+) -> Result<Vec<Role>, RoleError> {
+    web::block(move || -> Result<Vec<Role>, RoleError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| RoleError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, RoleError, _>(|conn| {
+            if !is_registered(conn, &user_uid)? {
+                return Err(RoleError::UserNotFound);
+            }
+            add_role(conn, &user_uid, role)?;
+            roles_of(conn, &user_uid)
+        })
+    })
+    .await
+    .map_err(|e| RoleError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Takes `role` away from `user_uid` and returns the roles they keep.
+/// Revoking a role the user does not hold is a no-op.
+pub async fn revoke_role(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    user_uid: String,
+    role: Role,
+) -> Result<Vec<Role>, RoleError> {
+    web::block(move || -> Result<Vec<Role>, RoleError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| RoleError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, RoleError, _>(|conn| {
+            diesel::delete(
+                user_roles::table
+                    .filter(user_roles::user_uid.eq(&user_uid))
+                    .filter(user_roles::role.eq(i32::from(role))),
+            )
+            .execute(conn)?;
+            roles_of(conn, &user_uid)
+        })
+    })
+    .await
+    .map_err(|e| RoleError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Whether `user_uid` signed up as a user or as a professional.
+fn is_registered(conn: &mut PgConnection, user_uid: &str) -> QueryResult<bool> {
+    let is_user = diesel::select(diesel::dsl::exists(
+        users::table.filter(users::user_uid.eq(user_uid)),
+    ))
+    .get_result::<bool>(conn)?;
+    if is_user {
+        return Ok(true);
+    }
+    diesel::select(diesel::dsl::exists(
+        professionals::table.filter(professionals::user_uid.eq(user_uid)),
+    ))
+    .get_result(conn)
+}
@@ -1,19 +1,56 @@
 use super::address_db;
+use super::booking_db;
 use crate::errors::task_errors::TaskError;
-use crate::models::dtos::task_dto::TaskDto;
-use crate::models::task_aggregate::task::{NewTask, Task};
+use crate::models::address::Address;
+use crate::models::booking_aggregate::booking::Booking;
+use crate::models::dtos::task_dto::{
+    TaskDto, TaskProposalDTO, TaskProposalDetailDTO, TaskSummaryDTO,
+};
+use crate::models::task_aggregate::task::{NewTask, Task, TaskChangeset};
 use crate::models::task_aggregate::task_assignment::NewTaskAssignments;
-use crate::schema::schema::{task, task_assignments};
+use crate::models::task_aggregate::task_proposal::{NewTaskProposal, ProposalStatus, TaskProposal};
+use crate::models::task_aggregate::task_status::TaskStatus;
+use crate::policies::ownership;
+use crate::schema::schema::{
+    addresses, professional_profiles, professionals, task, task_assignments, task_proposals,
+};
 use actix_web::web;
 use chrono::{NaiveDate, NaiveTime, Utc};
+use diesel::dsl::sql;
 use diesel::prelude::*;
 use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use diesel::sql_types::{Bool, Float8};
+use std::collections::HashMap;
+
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// A point to measure task distances from, optionally limiting results to
+/// `radius_km` around it.
+pub struct Location {
+    pub lat: f64,
+    pub lng: f64,
+    pub radius_km: Option<f64>,
+}
+
+/// Filters for professionals browsing open tasks.
+pub struct TaskFilter {
+    pub category_id: Option<i32>,
+    pub min_price: Option<f64>,
+    pub max_price: Option<f64>,
+    pub scheduled_date: Option<NaiveDate>,
+    pub location: Option<Location>,
+    pub limit: i64,
+    pub offset: i64,
+}
 
 pub async fn place_task(
     db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
     user_uid: String,
     task_dto: TaskDto,
 ) -> Result<Task, TaskError> {
+    validate_prices(task_dto.min_price, task_dto.max_price)?;
+
     let task_creation_result = web::block({
         let db_pool = db_pool.clone();
         move || -> Result<Task, TaskError> {
@@ -98,7 +135,7 @@ fn insert_task_assignment(
     Ok(())
 }
 
-fn parse_date(date_str: &Option<String>) -> Result<Option<NaiveDate>, TaskError> {
+pub fn parse_date(date_str: &Option<String>) -> Result<Option<NaiveDate>, TaskError> {
     if let Some(date_str) = date_str.as_deref() {
         NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
             .map(Some)
@@ -118,36 +155,729 @@ fn parse_time(time_str: &Option<String>) -> Result<Option<NaiveTime>, TaskError>
     }
 }
 
-// pub fn get_tasks_by_user(
-//     conn: &mut PgConnection,
-//     user_uid: &str,
-// ) -> Result<Vec<TaskDto>, TaskError> {
-//     let tasks: Vec<(Task, Option<TaskAssignments>, Option<Address>)> = task::table
-//         .filter(task::user_uid.eq(user_uid))
-//         .left_join(task_assignments::table.on(task::id.eq(task_assignments::task_id)))
-//         .left_join(addresses::table.on(task::address_id.eq(addresses::id.nullable())))
-//         .load(conn)?;
-
-//     // Map the results to TaskDto
-//     let task_dtos: Vec<TaskDto> = tasks
-//         .into_iter()
-//         .map(|(task, task_assignment, address)| {
-//             let image_strings = task_assignment.map(|assignment| vec![assignment.image_url]);
-
-//             TaskDto {
-//                 title: task.title.clone(),
-//                 description: task.description.clone(),
-//                 image_strings,
-//                 category_id: task.category_id,
-//                 is_flexible_timing: task.is_flexible_timing,
-//                 scheduled_date: task.scheduled_date.map(|d| d.to_string()),
-//                 scheduled_time: task.scheduled_time.map(|t| t.to_string()),
-//                 min_price: task.min_price,
-//                 max_price: task.max_price,
-//                 address: address.map(|addr| AddressDTO::address_to_dto(&addr)),
-//             }
-//         })
-//         .collect();
-
-//     Ok(task_dtos)
-// }
+pub async fn get_tasks_for_user(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    user_uid: String,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<TaskSummaryDTO>, TaskError> {
+    web::block(move || -> Result<Vec<TaskSummaryDTO>, TaskError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| TaskError::DatabasePoolError(e.to_string()))?;
+
+        let tasks = task::table
+            .left_join(addresses::table.on(task::address_id.eq(addresses::id.nullable())))
+            .filter(task::user_uid.eq(&user_uid))
+            .order((task::creation_time.desc(), task::id.desc()))
+            .limit(limit)
+            .offset(offset)
+            .load::<(Task, Option<Address>)>(&mut conn)?;
+
+        with_details(&mut conn, tasks, None)
+    })
+    .await
+    .map_err(|e| TaskError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Open tasks other users posted, soonest first.
+pub async fn browse_tasks(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    user_uid: String,
+    filter: TaskFilter,
+) -> Result<Vec<TaskSummaryDTO>, TaskError> {
+    web::block(move || -> Result<Vec<TaskSummaryDTO>, TaskError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| TaskError::DatabasePoolError(e.to_string()))?;
+
+        // Tasks past their date count as expired before the expiry job
+        // gets to them.
+        let today = Utc::now().date_naive();
+        let mut query = task::table
+            .left_join(addresses::table.on(task::address_id.eq(addresses::id.nullable())))
+            .filter(task::user_uid.ne(&user_uid))
+            .filter(task::status.eq(i32::from(TaskStatus::Open)))
+            .filter(
+                task::scheduled_date
+                    .is_null()
+                    .or(task::scheduled_date.ge(today)),
+            )
+            .into_boxed();
+
+        if let Some(category_id) = filter.category_id {
+            query = query.filter(task::category_id.eq(category_id));
+        }
+        // A task matches when its budget range overlaps the requested one.
+        if let Some(min_price) = filter.min_price {
+            query = query.filter(
+                task::max_price
+                    .is_null()
+                    .or(task::max_price.ge(min_price)),
+            );
+        }
+        if let Some(max_price) = filter.max_price {
+            query = query.filter(
+                task::min_price
+                    .is_null()
+                    .or(task::min_price.le(max_price)),
+            );
+        }
+        if let Some(scheduled_date) = filter.scheduled_date {
+            query = query.filter(task::scheduled_date.eq(scheduled_date));
+        }
+        if let Some(Location {
+            lat,
+            lng,
+            radius_km: Some(radius_km),
+        }) = filter.location
+        {
+            query = query.filter(
+                sql::<Bool>(
+                    "ST_DWithin(geography(ST_MakePoint(addresses.lng::double precision, addresses.lat::double precision)), geography(ST_MakePoint(",
+                )
+                .bind::<Float8, _>(lng)
+                .sql(", ")
+                .bind::<Float8, _>(lat)
+                .sql(")), ")
+                .bind::<Float8, _>(radius_km * 1000.0)
+                .sql(")"),
+            );
+        }
+
+        let tasks = query
+            .order((
+                task::scheduled_date.asc().nulls_last(),
+                task::creation_time.desc(),
+                task::id.desc(),
+            ))
+            .limit(filter.limit)
+            .offset(filter.offset)
+            .load::<(Task, Option<Address>)>(&mut conn)?;
+
+        let origin = filter.location.map(|location| (location.lat, location.lng));
+        with_details(&mut conn, tasks, origin)
+    })
+    .await
+    .map_err(|e| TaskError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Replaces the editable fields of an open task. When `image_strings` is given the
+/// task's images are replaced as well; otherwise they are kept.
+pub async fn update_task(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    task_id: i32,
+    user_uid: String,
+    task_dto: TaskDto,
+) -> Result<Task, TaskError> {
+    validate_prices(task_dto.min_price, task_dto.max_price)?;
+
+    web::block(move || -> Result<Task, TaskError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| TaskError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, TaskError, _>(|conn| {
+            let existing_task = find_owned_task(conn, task_id, &user_uid)?;
+            if TaskStatus::try_from(existing_task.status)? != TaskStatus::Open {
+                return Err(TaskError::TaskNotOpen);
+            }
+
+            let address_id = match task_dto.address {
+                Some(ref address) => match address_db::find_address(conn, address)? {
+                    Some(id) => Some(id),
+                    None => Some(address_db::insert_address(conn, address)?),
+                },
+                None => None,
+            };
+            let changes = TaskChangeset {
+                description: task_dto.description.clone(),
+                address_id,
+                title: task_dto.title.clone(),
+                min_price: task_dto.min_price,
+                max_price: task_dto.max_price,
+                is_flexible_timing: task_dto.is_flexible_timing,
+                scheduled_date: parse_date(&task_dto.scheduled_date)?,
+                scheduled_time: parse_time(&task_dto.scheduled_time)?,
+                category_id: task_dto.category_id,
+            };
+
+            let updated_task = diesel::update(task::table.find(task_id))
+                .set(&changes)
+                .get_result::<Task>(conn)?;
+
+            if let Some(ref image_urls) = task_dto.image_strings {
+                diesel::delete(
+                    task_assignments::table.filter(task_assignments::task_id.eq(task_id)),
+                )
+                .execute(conn)?;
+                let new_assignments: Vec<NewTaskAssignments> = image_urls
+                    .iter()
+                    .map(|image_url| NewTaskAssignments {
+                        task_id,
+                        image_url: image_url.clone(),
+                    })
+                    .collect();
+                diesel::insert_into(task_assignments::table)
+                    .values(&new_assignments)
+                    .execute(conn)?;
+            }
+
+            Ok(updated_task)
+        })
+    })
+    .await
+    .map_err(|e| TaskError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Deletes a task together with its images.
+pub async fn delete_task(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    task_id: i32,
+    user_uid: String,
+) -> Result<(), TaskError> {
+    web::block(move || -> Result<(), TaskError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| TaskError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, TaskError, _>(|conn| {
+            find_owned_task(conn, task_id, &user_uid)?;
+            diesel::delete(task::table.find(task_id)).execute(conn)?;
+            Ok(())
+        })
+    })
+    .await
+    .map_err(|e| TaskError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Closes an open task and rejects its pending proposals.
+pub async fn close_task(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    task_id: i32,
+    user_uid: String,
+) -> Result<Task, TaskError> {
+    web::block(move || -> Result<Task, TaskError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| TaskError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, TaskError, _>(|conn| {
+            let existing_task = find_owned_task(conn, task_id, &user_uid)?;
+            if TaskStatus::try_from(existing_task.status)? != TaskStatus::Open {
+                return Err(TaskError::TaskNotOpen);
+            }
+
+            reject_pending_proposals(conn, task_id)?;
+            diesel::update(task::table.find(task_id))
+                .set(task::status.eq(i32::from(TaskStatus::Closed)))
+                .get_result::<Task>(conn)
+                .map_err(TaskError::from)
+        })
+    })
+    .await
+    .map_err(|e| TaskError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Sends a proposal for an open task on behalf of one of the caller's
+/// professional profiles. Each profile may propose once per task.
+pub async fn submit_proposal(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    task_id: i32,
+    user_uid: String,
+    proposal_dto: TaskProposalDTO,
+) -> Result<TaskProposal, TaskError> {
+    validate_proposal_price(proposal_dto.price)?;
+
+    web::block(move || -> Result<TaskProposal, TaskError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| TaskError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, TaskError, _>(|conn| {
+            let existing_task = task::table
+                .find(task_id)
+                .first::<Task>(conn)
+                .optional()?
+                .ok_or(TaskError::NotFound)?;
+            if existing_task.user_uid == user_uid {
+                return Err(TaskError::OwnTask);
+            }
+            if TaskStatus::try_from(existing_task.status)? != TaskStatus::Open
+                || is_past_due(&existing_task, Utc::now().date_naive())
+            {
+                return Err(TaskError::TaskNotOpen);
+            }
+
+            let owner_uid: String = professional_profiles::table
+                .inner_join(professionals::table)
+                .filter(professional_profiles::id.eq(proposal_dto.professional_profile_id))
+                .select(professionals::user_uid)
+                .first(conn)
+                .optional()?
+                .ok_or(TaskError::NotProfileOwner)?;
+            if owner_uid != user_uid {
+                return Err(TaskError::NotProfileOwner);
+            }
+
+            let new_proposal = NewTaskProposal {
+                task_id,
+                professional_profile_id: proposal_dto.professional_profile_id,
+                price: proposal_dto.price,
+                message: proposal_dto.message,
+                status: ProposalStatus::Pending.into(),
+                created_at: Utc::now(),
+            };
+
+            diesel::insert_into(task_proposals::table)
+                .values(&new_proposal)
+                .get_result::<TaskProposal>(conn)
+                .map_err(|e| match e {
+                    DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                        TaskError::DuplicateProposal
+                    }
+                    e => TaskError::DieselError(e),
+                })
+        })
+    })
+    .await
+    .map_err(|e| TaskError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// All proposals for a task, oldest first. Only the task owner may see them.
+pub async fn get_task_proposals(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    task_id: i32,
+    user_uid: String,
+) -> Result<Vec<TaskProposalDetailDTO>, TaskError> {
+    web::block(move || -> Result<Vec<TaskProposalDetailDTO>, TaskError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| TaskError::DatabasePoolError(e.to_string()))?;
+
+        let owner_uid: String = task::table
+            .find(task_id)
+            .select(task::user_uid)
+            .first(&mut conn)
+            .optional()?
+            .ok_or(TaskError::NotFound)?;
+        if !ownership::owns_task(&owner_uid, &user_uid) {
+            return Err(TaskError::NotOwner);
+        }
+
+        let proposals = task_proposals::table
+            .inner_join(professional_profiles::table)
+            .filter(task_proposals::task_id.eq(task_id))
+            .order(task_proposals::created_at.asc())
+            .select((
+                TaskProposal::as_select(),
+                professional_profiles::professional_profile_uid,
+                professional_profiles::professional_name,
+                professional_profiles::image_url,
+                professional_profiles::average_rating,
+            ))
+            .load::<(TaskProposal, String, String, Option<String>, Option<f64>)>(&mut conn)?;
+
+        Ok(proposals
+            .into_iter()
+            .map(
+                |(
+                    proposal,
+                    professional_profile_uid,
+                    professional_name,
+                    image_url,
+                    average_rating,
+                )| {
+                    TaskProposalDetailDTO {
+                        proposal,
+                        professional_profile_uid,
+                        professional_name,
+                        image_url,
+                        average_rating,
+                    }
+                },
+            )
+            .collect())
+    })
+    .await
+    .map_err(|e| TaskError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Accepts a pending proposal: books the professional for the task, rejects
+/// the remaining proposals and marks the task as assigned.
+pub async fn accept_proposal(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    task_id: i32,
+    proposal_id: i32,
+    user_uid: String,
+) -> Result<Booking, TaskError> {
+    web::block(move || -> Result<Booking, TaskError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| TaskError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, TaskError, _>(|conn| {
+            let existing_task = find_owned_task(conn, task_id, &user_uid)?;
+            if TaskStatus::try_from(existing_task.status)? != TaskStatus::Open {
+                return Err(TaskError::TaskNotOpen);
+            }
+            let proposal = find_pending_proposal(conn, task_id, proposal_id)?;
+
+            let professional_profile_uid: String = professional_profiles::table
+                .find(proposal.professional_profile_id)
+                .select(professional_profiles::professional_profile_uid)
+                .first(conn)?;
+            let image_urls: Vec<String> = task_assignments::table
+                .filter(task_assignments::task_id.eq(task_id))
+                .order(task_assignments::id.asc())
+                .select(task_assignments::image_url)
+                .load(conn)?;
+
+            let booking = booking_db::create_booking_for_task(
+                conn,
+                &existing_task,
+                &proposal,
+                &professional_profile_uid,
+                &image_urls,
+            )?;
+
+            diesel::update(task_proposals::table.find(proposal_id))
+                .set((
+                    task_proposals::status.eq(i32::from(ProposalStatus::Accepted)),
+                    task_proposals::booking_id.eq(booking.id),
+                ))
+                .execute(conn)?;
+            reject_pending_proposals(conn, task_id)?;
+            diesel::update(task::table.find(task_id))
+                .set(task::status.eq(i32::from(TaskStatus::Assigned)))
+                .execute(conn)?;
+
+            Ok(booking)
+        })
+    })
+    .await
+    .map_err(|e| TaskError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+pub async fn reject_proposal(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    task_id: i32,
+    proposal_id: i32,
+    user_uid: String,
+) -> Result<TaskProposal, TaskError> {
+    web::block(move || -> Result<TaskProposal, TaskError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| TaskError::DatabasePoolError(e.to_string()))?;
+
+        conn.transaction::<_, TaskError, _>(|conn| {
+            find_owned_task(conn, task_id, &user_uid)?;
+            find_pending_proposal(conn, task_id, proposal_id)?;
+
+            diesel::update(task_proposals::table.find(proposal_id))
+                .set(task_proposals::status.eq(i32::from(ProposalStatus::Rejected)))
+                .get_result::<TaskProposal>(conn)
+                .map_err(TaskError::from)
+        })
+    })
+    .await
+    .map_err(|e| TaskError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+fn find_pending_proposal(
+    conn: &mut PgConnection,
+    task_id: i32,
+    proposal_id: i32,
+) -> Result<TaskProposal, TaskError> {
+    let proposal = task_proposals::table
+        .find(proposal_id)
+        .filter(task_proposals::task_id.eq(task_id))
+        .select(TaskProposal::as_select())
+        .for_update()
+        .first(conn)
+        .optional()?
+        .ok_or(TaskError::ProposalNotFound)?;
+
+    if proposal.status != i32::from(ProposalStatus::Pending) {
+        return Err(TaskError::ProposalNotPending);
+    }
+    Ok(proposal)
+}
+
+fn reject_pending_proposals(conn: &mut PgConnection, task_id: i32) -> QueryResult<usize> {
+    diesel::update(
+        task_proposals::table
+            .filter(task_proposals::task_id.eq(task_id))
+            .filter(task_proposals::status.eq(i32::from(ProposalStatus::Pending))),
+    )
+    .set(task_proposals::status.eq(i32::from(ProposalStatus::Rejected)))
+    .execute(conn)
+}
+
+/// Closes the open tasks of `user_uid` and rejects the proposals pending on
+/// them. Returns how many tasks were closed.
+pub fn close_open_tasks(conn: &mut PgConnection, user_uid: &str) -> QueryResult<usize> {
+    let open_task_ids: Vec<i32> = task::table
+        .filter(task::user_uid.eq(user_uid))
+        .filter(task::status.eq(i32::from(TaskStatus::Open)))
+        .select(task::id)
+        .for_update()
+        .load(conn)?;
+
+    for task_id in &open_task_ids {
+        reject_pending_proposals(conn, *task_id)?;
+    }
+    diesel::update(task::table.filter(task::id.eq_any(&open_task_ids)))
+        .set(task::status.eq(i32::from(TaskStatus::Closed)))
+        .execute(conn)
+}
+
+/// Withdraws the pending proposals sent from the profiles of `user_uid`.
+pub fn withdraw_pending_proposals(conn: &mut PgConnection, user_uid: &str) -> QueryResult<usize> {
+    let profile_ids = professional_profiles::table
+        .inner_join(professionals::table)
+        .filter(professionals::user_uid.eq(user_uid))
+        .select(professional_profiles::id);
+
+    diesel::update(
+        task_proposals::table
+            .filter(task_proposals::professional_profile_id.eq_any(profile_ids))
+            .filter(task_proposals::status.eq(i32::from(ProposalStatus::Pending))),
+    )
+    .set(task_proposals::status.eq(i32::from(ProposalStatus::Rejected)))
+    .execute(conn)
+}
+
+/// Marks open tasks whose scheduled date has passed as expired. Returns how
+/// many tasks expired.
+pub async fn expire_stale_tasks(
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+) -> Result<usize, TaskError> {
+    web::block(move || -> Result<usize, TaskError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| TaskError::DatabasePoolError(e.to_string()))?;
+
+        let today = Utc::now().date_naive();
+        diesel::update(
+            task::table
+                .filter(task::status.eq(i32::from(TaskStatus::Open)))
+                .filter(task::scheduled_date.lt(today)),
+        )
+        .set(task::status.eq(i32::from(TaskStatus::Expired)))
+        .execute(&mut conn)
+        .map_err(TaskError::DieselError)
+    })
+    .await
+    .map_err(|e| TaskError::BlockingError(format!("Blocking error: {}", e)))?
+}
+
+/// Whether `task` was scheduled for a day before `today`.
+fn is_past_due(task: &Task, today: NaiveDate) -> bool {
+    task.scheduled_date.is_some_and(|date| date < today)
+}
+
+fn find_owned_task(
+    conn: &mut PgConnection,
+    task_id: i32,
+    user_uid: &str,
+) -> Result<Task, TaskError> {
+    let existing_task = task::table
+        .find(task_id)
+        .for_update()
+        .first::<Task>(conn)
+        .optional()?
+        .ok_or(TaskError::NotFound)?;
+
+    if !ownership::owns_task(&existing_task.user_uid, user_uid) {
+        return Err(TaskError::NotOwner);
+    }
+    Ok(existing_task)
+}
+
+fn validate_proposal_price(price: f64) -> Result<(), TaskError> {
+    if !price.is_finite() || price <= 0.0 {
+        return Err(TaskError::InvalidTask(
+            "price must be a positive number".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_prices(min_price: Option<f64>, max_price: Option<f64>) -> Result<(), TaskError> {
+    for price in [min_price, max_price].into_iter().flatten() {
+        if !price.is_finite() || price < 0.0 {
+            return Err(TaskError::InvalidTask(
+                "prices must be non-negative numbers".to_string(),
+            ));
+        }
+    }
+    if let (Some(min_price), Some(max_price)) = (min_price, max_price) {
+        if min_price > max_price {
+            return Err(TaskError::InvalidTask(
+                "min_price must not exceed max_price".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Attaches images in one query and, given an origin, the distance to each
+/// task's address.
+fn with_details(
+    conn: &mut PgConnection,
+    tasks: Vec<(Task, Option<Address>)>,
+    origin: Option<(f64, f64)>,
+) -> Result<Vec<TaskSummaryDTO>, TaskError> {
+    let task_ids: Vec<i32> = tasks.iter().map(|(task, _)| task.id).collect();
+
+    let assignments: Vec<(i32, String)> = task_assignments::table
+        .filter(task_assignments::task_id.eq_any(&task_ids))
+        .order(task_assignments::id.asc())
+        .select((task_assignments::task_id, task_assignments::image_url))
+        .load(conn)?;
+
+    let mut images: HashMap<i32, Vec<String>> = HashMap::new();
+    for (task_id, image_url) in assignments {
+        images.entry(task_id).or_default().push(image_url);
+    }
+
+    Ok(tasks
+        .into_iter()
+        .map(|(task, address)| {
+            let distance_km = match (origin, address.as_ref()) {
+                (Some((lat, lng)), Some(address)) => {
+                    Some(haversine_km(lat, lng, address.lat, address.lng))
+                }
+                _ => None,
+            };
+            let image_urls = images.remove(&task.id).unwrap_or_default();
+            TaskSummaryDTO::task_to_dto(task, address, image_urls, distance_km)
+        })
+        .collect())
+}
+
+fn haversine_km(lat1: f64, lng1: f64, lat2: f64, lng2: f64) -> f64 {
+    let d_lat = (lat2 - lat1).to_radians();
+    let d_lng = (lng2 - lng1).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2)
+        + lat1.to_radians().cos() * lat2.to_radians().cos() * (d_lng / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{
+        insert_fixture, request_as, status_of, test_app, test_pool, PROFESSIONAL,
+    };
+    use actix_web::http::{Method, StatusCode};
+    use chrono::Duration;
+
+    #[test]
+    fn parses_task_dates_and_times() {
+        assert_eq!(
+            parse_date(&Some("2026-10-18".to_string())).unwrap(),
+            NaiveDate::from_ymd_opt(2026, 10, 18)
+        );
+        assert_eq!(
+            parse_time(&Some("09:30".to_string())).unwrap(),
+            NaiveTime::from_hms_opt(9, 30, 0)
+        );
+        assert_eq!(parse_date(&None).unwrap(), None);
+        assert!(matches!(
+            parse_date(&Some("18.10.2026".to_string())),
+            Err(TaskError::InvalidDate(_))
+        ));
+        assert!(parse_time(&Some("9.30".to_string())).is_err());
+    }
+
+    #[test]
+    fn prices_must_be_non_negative_and_ordered() {
+        assert!(validate_prices(None, None).is_ok());
+        assert!(validate_prices(Some(0.0), Some(10.0)).is_ok());
+        for (min_price, max_price) in [
+            (Some(-1.0), None),
+            (None, Some(f64::INFINITY)),
+            (Some(20.0), Some(10.0)),
+        ] {
+            assert!(matches!(
+                validate_prices(min_price, max_price),
+                Err(TaskError::InvalidTask(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn proposals_need_a_positive_price() {
+        assert!(validate_proposal_price(45.0).is_ok());
+        for price in [0.0, -10.0, f64::NAN] {
+            assert!(matches!(
+                validate_proposal_price(price),
+                Err(TaskError::InvalidTask(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn haversine_distance_between_cities() {
+        // Berlin to Munich is about 504 km as the crow flies.
+        let distance = haversine_km(52.52, 13.405, 48.1351, 11.582);
+        assert!((distance - 504.0).abs() < 5.0, "{}", distance);
+        assert_eq!(haversine_km(52.52, 13.405, 52.52, 13.405), 0.0);
+    }
+
+    #[actix_web::test]
+    async fn past_due_tasks_are_closed_to_proposals_before_they_expire() {
+        let Some(pool) = test_pool() else {
+            return;
+        };
+        let fixture = insert_fixture(&pool);
+        let yesterday = Utc::now().date_naive() - Duration::days(1);
+        diesel::update(task::table.find(fixture.task_id))
+            .set(task::scheduled_date.eq(yesterday))
+            .execute(&mut pool.get().unwrap())
+            .unwrap();
+        let app = test_app!(pool);
+
+        let tasks: serde_json::Value = actix_web::test::call_and_read_body_json(
+            &app,
+            request_as(Method::GET, "/task/browse", PROFESSIONAL).to_request(),
+        )
+        .await;
+        assert!(!tasks
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|task| task["id"] == fixture.task_id));
+
+        let proposals = format!("/task/{}/proposals", fixture.task_id);
+        let proposal = serde_json::json!({
+            "professional_profile_id": fixture.profile_id,
+            "price": 40.0,
+        });
+        assert_eq!(
+            status_of!(
+                app,
+                request_as(Method::POST, &proposals, PROFESSIONAL).set_json(&proposal)
+            ),
+            StatusCode::CONFLICT
+        );
+
+        let status = |pool: &Pool<ConnectionManager<PgConnection>>| -> i32 {
+            task::table
+                .find(fixture.task_id)
+                .select(task::status)
+                .first(&mut pool.get().unwrap())
+                .unwrap()
+        };
+        assert_eq!(status(&pool), i32::from(TaskStatus::Open));
+        assert!(
+            expire_stale_tasks(web::Data::new(pool.clone()))
+                .await
+                .unwrap()
+                >= 1
+        );
+        assert_eq!(status(&pool), i32::from(TaskStatus::Expired));
+    }
+}
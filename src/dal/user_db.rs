@@ -1,4 +1,6 @@
+use crate::dal::role_db;
 use crate::models::dtos::user_dto::UserDTO;
+use crate::models::user_aggregate::role::Role;
 use crate::models::user_aggregate::{user::NewUser, user::User};
 use crate::schema::schema::bookings::{self, customer_uid};
 use crate::schema::schema::message::dsl::*;
@@ -39,10 +41,12 @@ pub fn save_user_to_database(
         user_uid: user_uid_to_save.to_string(),
     };
 
-    // Insert the new user into the database
-    diesel::insert_into(users).values(&new_user).execute(conn)?;
-
-    Ok(())
+    // Insert the new user into the database; signing up as a user makes
+    // them a customer.
+    conn.transaction(|conn| {
+        diesel::insert_into(users).values(&new_user).execute(conn)?;
+        role_db::add_role(conn, user_uid_to_save, Role::Customer)
+    })
 }
 
 #[cfg(test)]
@@ -0,0 +1,61 @@
+use crate::errors::booking_errors::BookingError;
+use crate::errors::firebase_errors::FirebaseServiceError;
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
+use diesel::result::Error as DieselError;
+use log::error;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum AccountError {
+    #[error("Database error: {0}")]
+    DieselError(#[from] DieselError),
+
+    #[error("Booking error: {0}")]
+    BookingError(#[from] BookingError),
+
+    #[error("Identity provider error: {0}")]
+    IdentityError(#[from] FirebaseServiceError),
+
+    #[error("Blocking error: {0}")]
+    BlockingError(String),
+
+    #[error("Database pool error: {0}")]
+    DatabasePoolError(String),
+
+    #[error("Invalid profile: {0}")]
+    InvalidProfile(String),
+
+    #[error("Invalid email address")]
+    InvalidEmail,
+
+    #[error("User not found")]
+    UserNotFound,
+
+    #[error("This action needs a Firebase ID token of the account, not a session token")]
+    IdentityTokenRequired,
+}
+
+impl ResponseError for AccountError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AccountError::InvalidProfile(_)
+            | AccountError::InvalidEmail
+            | AccountError::IdentityError(FirebaseServiceError::FirebaseApiError(_)) => {
+                StatusCode::BAD_REQUEST
+            }
+            AccountError::IdentityTokenRequired => StatusCode::UNAUTHORIZED,
+            AccountError::UserNotFound => StatusCode::NOT_FOUND,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        if status.is_server_error() {
+            error!("Account error: {:?}", self);
+            HttpResponse::build(status).finish()
+        } else {
+            HttpResponse::build(status).body(self.to_string())
+        }
+    }
+}
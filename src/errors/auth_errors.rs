@@ -0,0 +1,87 @@
+use crate::errors::firebase_errors::FirebaseServiceError;
+use crate::errors::role_errors::RoleError;
+use crate::models::user_aggregate::role::Role;
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
+use log::error;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum AuthError {
+    #[error("No token")]
+    MissingToken,
+
+    #[error("Invalid token")]
+    InvalidToken,
+
+    #[error("Admin access required")]
+    AdminRequired,
+
+    #[error("The {0} role is required")]
+    RoleRequired(Role),
+
+    #[error("Token does not belong to this user")]
+    UserMismatch,
+
+    #[error("The account has been deleted")]
+    AccountDeleted,
+
+    #[error("Session tokens are not enabled")]
+    SessionTokensDisabled,
+
+    #[error("Could not issue session token: {0}")]
+    TokenIssueFailed(String),
+
+    #[error("Authentication is not configured: {0}")]
+    Configuration(String),
+
+    #[error("Could not verify token: {0}")]
+    VerificationFailed(FirebaseServiceError),
+
+    #[error("Could not load roles: {0}")]
+    RoleLookupFailed(#[from] RoleError),
+
+    #[error("Could not check who the token may act as: {0}")]
+    PartyLookupFailed(String),
+}
+
+impl From<FirebaseServiceError> for AuthError {
+    fn from(e: FirebaseServiceError) -> Self {
+        match e {
+            // Anything wrong with the token itself is the caller's problem;
+            // failing to fetch keys or read the config is ours.
+            FirebaseServiceError::JwtDecodeError
+            | FirebaseServiceError::MissingKidError
+            | FirebaseServiceError::InvalidKidError => AuthError::InvalidToken,
+            FirebaseServiceError::EnvVarError(e) => AuthError::Configuration(e.to_string()),
+            FirebaseServiceError::MissingConfig(name) => {
+                AuthError::Configuration(format!("{} is not set", name))
+            }
+            e => AuthError::VerificationFailed(e),
+        }
+    }
+}
+
+impl ResponseError for AuthError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AuthError::MissingToken | AuthError::InvalidToken | AuthError::AccountDeleted => {
+                StatusCode::UNAUTHORIZED
+            }
+            AuthError::AdminRequired | AuthError::RoleRequired(_) | AuthError::UserMismatch => {
+                StatusCode::FORBIDDEN
+            }
+            AuthError::SessionTokensDisabled => StatusCode::NOT_FOUND,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        if status.is_server_error() {
+            error!("Auth error: {:?}", self);
+            HttpResponse::build(status).finish()
+        } else {
+            HttpResponse::build(status).body(self.to_string())
+        }
+    }
+}
@@ -1,5 +1,12 @@
+use crate::models::booking_aggregate::booking_status::{
+    BookingAction, BookingParty, BookingStatus,
+};
+use crate::models::professional_aggregate::availability::SlotConflict;
+use crate::models::user_aggregate::role::Role;
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
 use chrono::ParseError;
 use diesel::result::Error as DieselError;
+use log::error;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -15,4 +22,77 @@ pub enum BookingError {
 
     #[error("Database pool error: {0}")]
     DatabasePoolError(String),
+
+    #[error("Booking not found")]
+    NotFound,
+
+    #[error("Invalid booking status: {0}")]
+    InvalidStatus(i32),
+
+    #[error("Invalid offer: {0}")]
+    InvalidOffer(String),
+
+    #[error("Invalid query: {0}")]
+    InvalidQuery(String),
+
+    #[error("End time must be after the start time")]
+    InvalidTimeRange,
+
+    #[error("The offering is not offered by the booked profile")]
+    UnknownOffering,
+
+    #[error("Requested time slot is not available: {0:?}")]
+    SlotUnavailable(SlotConflict),
+
+    #[error("Caller is not a party of this booking")]
+    NotParticipant,
+
+    #[error("The {0} role is required to act on this booking")]
+    RoleRequired(Role),
+
+    #[error("{party:?} is not allowed to {action:?} this booking")]
+    ActionNotPermitted {
+        action: BookingAction,
+        party: BookingParty,
+    },
+
+    #[error("Cannot {action:?} a booking in status {from:?}")]
+    IllegalTransition {
+        from: BookingStatus,
+        action: BookingAction,
+    },
+}
+
+impl ResponseError for BookingError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            BookingError::InvalidDate(_)
+            | BookingError::InvalidStatus(_)
+            | BookingError::InvalidOffer(_)
+            | BookingError::InvalidQuery(_)
+            | BookingError::InvalidTimeRange
+            | BookingError::UnknownOffering => StatusCode::BAD_REQUEST,
+            BookingError::NotFound => StatusCode::NOT_FOUND,
+            BookingError::NotParticipant
+            | BookingError::RoleRequired(_)
+            | BookingError::ActionNotPermitted { .. } => StatusCode::FORBIDDEN,
+            BookingError::IllegalTransition { .. } | BookingError::SlotUnavailable(_) => {
+                StatusCode::CONFLICT
+            }
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        if let BookingError::SlotUnavailable(conflict) = self {
+            return HttpResponse::build(status).json(conflict);
+        }
+        if status.is_server_error() {
+            error!("Booking error: {:?}", self);
+            HttpResponse::build(status).finish()
+        } else {
+            HttpResponse::build(status).body(self.to_string())
+        }
+    }
 }
@@ -0,0 +1,52 @@
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
+use diesel::result::Error as DieselError;
+use log::error;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum CategoryError {
+    #[error("Database error: {0}")]
+    DieselError(#[from] DieselError),
+
+    #[error("Blocking error: {0}")]
+    BlockingError(String),
+
+    #[error("Database pool error: {0}")]
+    DatabasePoolError(String),
+
+    #[error("Category not found")]
+    NotFound,
+
+    #[error("Subcategory not found")]
+    SubcategoryNotFound,
+
+    #[error("Invalid name: {0}")]
+    InvalidName(String),
+
+    #[error("A category or subcategory with this name already exists")]
+    DuplicateName,
+
+    #[error("Still referenced by {0}")]
+    InUse(String),
+}
+
+impl ResponseError for CategoryError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            CategoryError::InvalidName(_) => StatusCode::BAD_REQUEST,
+            CategoryError::NotFound | CategoryError::SubcategoryNotFound => StatusCode::NOT_FOUND,
+            CategoryError::DuplicateName | CategoryError::InUse(_) => StatusCode::CONFLICT,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        if status.is_server_error() {
+            error!("Category error: {:?}", self);
+            HttpResponse::build(status).finish()
+        } else {
+            HttpResponse::build(status).body(self.to_string())
+        }
+    }
+}
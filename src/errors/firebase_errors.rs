@@ -13,6 +13,8 @@ pub enum FirebaseServiceError {
     MissingKidError,
     #[error("Invalid 'kid' in JWT header")]
     InvalidKidError,
+    #[error("Firebase is not configured: {0} is not set")]
+    MissingConfig(&'static str),
     #[error("Environment variable not found: {0}")]
     EnvVarError(#[from] VarError),
     #[error("Reqwest error: {0}")]
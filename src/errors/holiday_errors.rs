@@ -0,0 +1,46 @@
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
+use diesel::result::Error as DieselError;
+use log::error;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum HolidayError {
+    #[error("Database error: {0}")]
+    DieselError(#[from] DieselError),
+
+    #[error("Blocking error: {0}")]
+    BlockingError(String),
+
+    #[error("Database pool error: {0}")]
+    DatabasePoolError(String),
+
+    #[error("Holiday not found")]
+    NotFound,
+
+    #[error("Invalid holiday: {0}")]
+    InvalidHoliday(String),
+
+    #[error("A holiday on this date already exists for this state")]
+    DuplicateHoliday,
+}
+
+impl ResponseError for HolidayError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            HolidayError::InvalidHoliday(_) => StatusCode::BAD_REQUEST,
+            HolidayError::NotFound => StatusCode::NOT_FOUND,
+            HolidayError::DuplicateHoliday => StatusCode::CONFLICT,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        if status.is_server_error() {
+            error!("Holiday error: {:?}", self);
+            HttpResponse::build(status).finish()
+        } else {
+            HttpResponse::build(status).body(self.to_string())
+        }
+    }
+}
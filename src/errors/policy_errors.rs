@@ -0,0 +1,36 @@
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
+use diesel::result::Error as DieselError;
+use log::error;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum PolicyError {
+    #[error("Database error: {0}")]
+    DieselError(#[from] DieselError),
+
+    #[error("Not found")]
+    NotFound,
+
+    #[error("You do not have access to this resource")]
+    Forbidden,
+}
+
+impl ResponseError for PolicyError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            PolicyError::NotFound => StatusCode::NOT_FOUND,
+            PolicyError::Forbidden => StatusCode::FORBIDDEN,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        if status.is_server_error() {
+            error!("Policy error: {:?}", self);
+            HttpResponse::build(status).finish()
+        } else {
+            HttpResponse::build(status).body(self.to_string())
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
+use diesel::result::Error as DieselError;
+use log::error;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ProfessionalProfileError {
+    #[error("Database error: {0}")]
+    DieselError(#[from] DieselError),
+
+    #[error("Blocking error: {0}")]
+    BlockingError(String),
+
+    #[error("Database pool error: {0}")]
+    DatabasePoolError(String),
+
+    #[error("Professional profile not found")]
+    NotFound,
+
+    #[error("Caller is not registered as a professional")]
+    NotProfessional,
+
+    #[error("Caller does not own this profile")]
+    NotOwner,
+
+    #[error("Category not found")]
+    CategoryNotFound,
+
+    #[error("A profile for this category already exists")]
+    DuplicateCategory,
+
+    #[error("Invalid profile: {0}")]
+    InvalidProfile(String),
+
+    #[error("Service offering not found")]
+    OfferingNotFound,
+
+    #[error("Invalid service offering: {0}")]
+    InvalidOffering(String),
+
+    #[error("Service offering is still referenced by bookings")]
+    OfferingInUse,
+
+    #[error("Invalid business hours: {0}")]
+    InvalidBusinessHours(String),
+
+    #[error("Business hour exception not found")]
+    ExceptionNotFound,
+}
+
+impl ResponseError for ProfessionalProfileError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ProfessionalProfileError::InvalidProfile(_)
+            | ProfessionalProfileError::InvalidOffering(_)
+            | ProfessionalProfileError::InvalidBusinessHours(_)
+            | ProfessionalProfileError::CategoryNotFound => StatusCode::BAD_REQUEST,
+            ProfessionalProfileError::NotFound
+            | ProfessionalProfileError::OfferingNotFound
+            | ProfessionalProfileError::ExceptionNotFound => StatusCode::NOT_FOUND,
+            ProfessionalProfileError::NotProfessional | ProfessionalProfileError::NotOwner => {
+                StatusCode::FORBIDDEN
+            }
+            ProfessionalProfileError::DuplicateCategory
+            | ProfessionalProfileError::OfferingInUse => StatusCode::CONFLICT,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        if status.is_server_error() {
+            error!("Professional profile error: {:?}", self);
+            HttpResponse::build(status).finish()
+        } else {
+            HttpResponse::build(status).body(self.to_string())
+        }
+    }
+}
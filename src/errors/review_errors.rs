@@ -0,0 +1,117 @@
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
+use diesel::result::Error as DieselError;
+use log::error;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ReviewError {
+    #[error("Database error: {0}")]
+    DieselError(#[from] DieselError),
+
+    #[error("Blocking error: {0}")]
+    BlockingError(String),
+
+    #[error("Database pool error: {0}")]
+    DatabasePoolError(String),
+
+    #[error("Review not found")]
+    NotFound,
+
+    #[error("Professional profile not found")]
+    ProfileNotFound,
+
+    #[error("Booking not found")]
+    BookingNotFound,
+
+    #[error("User not found")]
+    UserNotFound,
+
+    #[error("Only the customer of a booking can review it")]
+    NotBookingCustomer,
+
+    #[error("Only completed bookings can be reviewed")]
+    BookingNotCompleted,
+
+    #[error("This booking has already been reviewed")]
+    AlreadyReviewed,
+
+    #[error("Caller did not write this review")]
+    NotAuthor,
+
+    #[error("Rating must be between 1 and 5")]
+    InvalidRating,
+
+    #[error("Invalid query: {0}")]
+    InvalidQuery(String),
+
+    #[error("Message must not be empty")]
+    InvalidMessage,
+
+    #[error("Caller does not own the reviewed profile")]
+    NotProfileOwner,
+
+    #[error("Reply not found")]
+    ReplyNotFound,
+
+    #[error("You already reported this review")]
+    AlreadyReported,
+
+    #[error("Report not found")]
+    ReportNotFound,
+
+    #[error("Report has already been resolved")]
+    ReportResolved,
+}
+
+impl ResponseError for ReviewError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ReviewError::InvalidRating
+            | ReviewError::InvalidQuery(_)
+            | ReviewError::InvalidMessage => StatusCode::BAD_REQUEST,
+            ReviewError::NotFound
+            | ReviewError::ProfileNotFound
+            | ReviewError::BookingNotFound
+            | ReviewError::UserNotFound
+            | ReviewError::ReplyNotFound
+            | ReviewError::ReportNotFound => StatusCode::NOT_FOUND,
+            ReviewError::NotBookingCustomer
+            | ReviewError::NotAuthor
+            | ReviewError::NotProfileOwner => StatusCode::FORBIDDEN,
+            ReviewError::BookingNotCompleted
+            | ReviewError::AlreadyReviewed
+            | ReviewError::AlreadyReported
+            | ReviewError::ReportResolved => StatusCode::CONFLICT,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        if status.is_server_error() {
+            error!("Review error: {:?}", self);
+            HttpResponse::build(status).finish()
+        } else {
+            HttpResponse::build(status).body(self.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reply_and_report_errors_map_to_client_statuses() {
+        for (error, status) in [
+            (ReviewError::InvalidMessage, StatusCode::BAD_REQUEST),
+            (ReviewError::NotProfileOwner, StatusCode::FORBIDDEN),
+            (ReviewError::ReplyNotFound, StatusCode::NOT_FOUND),
+            (ReviewError::ReportNotFound, StatusCode::NOT_FOUND),
+            (ReviewError::AlreadyReported, StatusCode::CONFLICT),
+            (ReviewError::ReportResolved, StatusCode::CONFLICT),
+        ] {
+            assert_eq!(error.status_code(), status, "{:?}", error);
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use crate::models::user_aggregate::role::Role;
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
+use diesel::result::Error as DieselError;
+use log::error;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum RoleError {
+    #[error("Database error: {0}")]
+    DieselError(#[from] DieselError),
+
+    #[error("Blocking error: {0}")]
+    BlockingError(String),
+
+    #[error("Database pool error: {0}")]
+    DatabasePoolError(String),
+
+    #[error("Invalid role: {0}")]
+    InvalidRole(i32),
+
+    #[error("User not found")]
+    UserNotFound,
+
+    #[error("Admins cannot revoke their own {0} role")]
+    OwnAdminRole(Role),
+}
+
+impl ResponseError for RoleError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            RoleError::UserNotFound => StatusCode::NOT_FOUND,
+            RoleError::OwnAdminRole(_) => StatusCode::CONFLICT,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        if status.is_server_error() {
+            error!("Role error: {:?}", self);
+            HttpResponse::build(status).finish()
+        } else {
+            HttpResponse::build(status).body(self.to_string())
+        }
+    }
+}
@@ -1,6 +1,9 @@
+use crate::errors::booking_errors::BookingError;
 use crate::errors::firebase_errors::FirebaseServiceError;
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
 use chrono::ParseError;
 use diesel::result::Error as DieselError;
+use log::error;
 use std::num::ParseIntError;
 use thiserror::Error;
 
@@ -23,4 +26,72 @@ pub enum TaskError {
 
     #[error("Database pool error: {0}")]
     DatabasePoolError(String),
+
+    #[error("Task not found")]
+    NotFound,
+
+    #[error("Caller does not own this task")]
+    NotOwner,
+
+    #[error("Caller does not own this professional profile")]
+    NotProfileOwner,
+
+    #[error("Invalid task status: {0}")]
+    InvalidStatus(i32),
+
+    #[error("Task is no longer open")]
+    TaskNotOpen,
+
+    #[error("Proposal not found")]
+    ProposalNotFound,
+
+    #[error("Proposal is no longer pending")]
+    ProposalNotPending,
+
+    #[error("This profile already sent a proposal for the task")]
+    DuplicateProposal,
+
+    #[error("Cannot send a proposal for your own task")]
+    OwnTask,
+
+    #[error(transparent)]
+    BookingError(#[from] BookingError),
+
+    #[error("Invalid task: {0}")]
+    InvalidTask(String),
+
+    #[error("Invalid query: {0}")]
+    InvalidQuery(String),
+}
+
+impl ResponseError for TaskError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            TaskError::InvalidDate(_)
+            | TaskError::InvalidCategoryId(_)
+            | TaskError::InvalidTask(_)
+            | TaskError::InvalidQuery(_)
+            | TaskError::OwnTask => StatusCode::BAD_REQUEST,
+            TaskError::NotFound | TaskError::ProposalNotFound => StatusCode::NOT_FOUND,
+            TaskError::NotOwner | TaskError::NotProfileOwner => StatusCode::FORBIDDEN,
+            TaskError::TaskNotOpen
+            | TaskError::ProposalNotPending
+            | TaskError::DuplicateProposal => StatusCode::CONFLICT,
+            TaskError::BookingError(e) => e.status_code(),
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        if let TaskError::BookingError(e) = self {
+            return e.error_response();
+        }
+        let status = self.status_code();
+        if status.is_server_error() {
+            error!("Task error: {:?}", self);
+            HttpResponse::build(status).finish()
+        } else {
+            HttpResponse::build(status).body(self.to_string())
+        }
+    }
 }
@@ -1,10 +1,13 @@
 use actix::Addr;
-use actix_web::{web, HttpRequest, HttpResponse, Error};
+use actix_web::{web, HttpRequest, HttpResponse, Error, ResponseError};
 use actix_web_actors::ws;
 use log::{info, error};
 use crate::websocket::{ChatSession, ChatServer};
-use crate::middleware::auth::Claims;
-use jsonwebtoken::{decode, DecodingKey, Validation, Algorithm};
+use crate::db::Pool;
+use crate::errors::auth_errors::AuthError;
+use crate::middleware::authenticated_user::bearer_token;
+use crate::middleware::token_verifier::TokenVerifier;
+use crate::policies::ownership;
 
 /// WebSocket endpoint handler
 /// Path: /ws/chat/{user_id}
@@ -13,13 +16,15 @@ pub async fn chat_websocket(
     stream: web::Payload,
     path: web::Path<String>,
     chat_server: web::Data<Addr<ChatServer>>,
+    verifier: web::Data<dyn TokenVerifier>,
+    db_pool: web::Data<Pool>,
 ) -> Result<HttpResponse, Error> {
     let user_id = path.into_inner();
     
     // Validate authentication (extract JWT from query params or headers)
-    if let Err(e) = validate_websocket_auth(&req, &user_id) {
+    if let Err(e) = validate_websocket_auth(&req, &user_id, verifier.get_ref(), &db_pool).await {
         error!("WebSocket authentication failed for user {}: {}", user_id, e);
-        return Ok(HttpResponse::Unauthorized().body("Authentication required"));
+        return Ok(e.error_response());
     }
     
     info!("Starting WebSocket for user: {}", user_id);
@@ -31,15 +36,22 @@ pub async fn chat_websocket(
     ws::start(session, &req, stream)
 }
 
-/// Validate WebSocket authentication
-fn validate_websocket_auth(req: &HttpRequest, user_id: &str) -> Result<(), String> {
-    // Try to get token from Authorization header
+/// Resolve the caller of a WebSocket upgrade and check that they connect as
+/// themselves or as one of their professional profiles. Browsers cannot set
+/// headers on WebSocket requests, so the token may also come as the `token`
+/// query parameter.
+pub async fn validate_websocket_auth(
+    req: &HttpRequest,
+    user_id: &str,
+    verifier: &dyn TokenVerifier,
+    db_pool: &web::Data<Pool>,
+) -> Result<(), AuthError> {
     let token = if let Some(auth_header) = req.headers().get("Authorization") {
         auth_header
             .to_str()
-            .map_err(|_| "Invalid authorization header")?
-            .strip_prefix("Bearer ")
-            .ok_or("Invalid authorization format")?
+            .ok()
+            .and_then(bearer_token)
+            .ok_or(AuthError::InvalidToken)?
             .to_string()
     } else if let Some(token_param) = req.uri().query()
         .and_then(|q| {
@@ -50,29 +62,30 @@ fn validate_websocket_auth(req: &HttpRequest, user_id: &str) -> Result<(), Strin
         // Fallback to query parameter for WebSocket
         token_param
     } else {
-        return Err("No authentication token provided".to_string());
+        return Err(AuthError::MissingToken);
     };
     
-    // Validate JWT token
-    let secret = std::env::var("JWT_SECRET")
-        .unwrap_or_else(|_| "your-secret-key".to_string());
-    
-    let validation = Validation::new(Algorithm::HS256);
-    
-    match decode::<Claims>(
-        &token,
-        &DecodingKey::from_secret(secret.as_bytes()),
-        &validation,
-    ) {
-        Ok(token_data) => {
-            // Verify user_id matches token
-            if token_data.claims.sub != user_id {
-                Err("User ID mismatch".to_string())
-            } else {
-                Ok(())
-            }
-        }
-        Err(e) => Err(format!("Invalid token: {}", e))
+    // Verify user_id is the token's user or a profile they own
+    let caller = verifier.verify(&token).await?;
+    if caller == user_id {
+        return Ok(());
+    }
+    let db_pool = db_pool.clone();
+    let user_id = user_id.to_string();
+    let is_party = web::block(move || {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| AuthError::PartyLookupFailed(e.to_string()))?;
+        ownership::is_party(&mut conn, &caller, &user_id)
+            .map_err(|e| AuthError::PartyLookupFailed(e.to_string()))
+    })
+    .await
+    .map_err(|e| AuthError::PartyLookupFailed(format!("Blocking error: {}", e)))??;
+
+    if is_party {
+        Ok(())
+    } else {
+        Err(AuthError::UserMismatch)
     }
 }
 
@@ -2,27 +2,48 @@ mod db;
 mod websocket;
 mod handlers;
 mod middleware;
+mod policies;
 mod errors {
+    pub mod account_errors;
+    pub mod auth_errors;
     pub mod booking_errors;
+    pub mod category_errors;
     pub mod firebase_errors;
+    pub mod holiday_errors;
+    pub mod policy_errors;
+    pub mod professional_profile_errors;
+    pub mod review_errors;
+    pub mod role_errors;
     pub mod task_errors;
 }
 mod dal {
+    pub mod account_db;
     pub mod address_db;
     pub mod booking_db;
+    pub mod business_hours_db;
     pub mod category_db;
     pub mod chat_db;
+    pub mod holiday_db;
     pub mod professional_db;
     pub mod professional_profile_db;
+    pub mod review_db;
+    pub mod role_db;
     pub mod task_db;
     pub mod user_db;
 }
 mod services {
+    pub mod auth_services;
+    pub mod booking_services;
     pub mod categories_services;
     pub mod chat_services;
+    #[cfg(test)]
+    pub mod fake_identity_provider;
     pub mod firebase_service;
+    pub mod holiday_services;
+    pub mod identity_provider;
     pub mod professional_profile_services;
     pub mod professional_services;
+    pub mod review_services;
     pub mod task_services;
     pub mod user_services;
 }
@@ -30,40 +51,59 @@ mod services {
 mod models {
     pub mod address;
     pub mod address_assignments;
+    pub mod public_holiday;
     pub mod dtos {
+        pub mod account_dto;
         pub mod address_dto;
+        pub mod auth_dto;
         pub mod booking_dto;
+        pub mod business_hours_dto;
+        pub mod category_dto;
         pub mod chat_dto;
         pub mod message_dto;
         pub mod professional_profile_detail_dto;
+        pub mod professional_profile_edit_dto;
         pub mod professional_profiles_dto;
         pub mod review_dto;
+        pub mod role_dto;
         pub mod subcategory_dto;
         pub mod task_dto;
         pub mod user_dto;
     }
     pub mod user_aggregate {
         pub mod new_user;
+        pub mod role;
         pub mod user;
     }
     pub mod professional_aggregate {
+        pub mod availability;
         pub mod business_hour;
+        pub mod business_hour_exception;
+        pub mod day_of_week;
         pub mod new_professional;
+        pub mod pricing_unit;
         pub mod professional;
         pub mod professional_profile;
+        pub mod profile_search;
         pub mod service_offering;
     }
     pub mod category_aggregate {
         pub mod category;
+        pub mod category_suggestions;
         pub mod sub_category;
     }
     pub mod review_aggregate {
         pub mod review;
         pub mod review_content_assignments;
+        pub mod review_query;
+        pub mod review_reply;
+        pub mod review_report;
     }
     pub mod booking_aggregate {
         pub mod booking;
         pub mod booking_assignment;
+        pub mod booking_history;
+        pub mod booking_offer;
         pub mod booking_status;
     }
     pub mod chat_aggregate {
@@ -74,11 +114,15 @@ mod models {
     pub mod task_aggregate {
         pub mod task;
         pub mod task_assignment;
+        pub mod task_proposal;
+        pub mod task_status;
     }
 }
 mod schema {
     pub mod schema;
 }
+#[cfg(test)]
+mod test_support;
 
 use actix::Actor;
 use actix_cors::Cors;
@@ -86,12 +130,22 @@ use actix_web::{web, App, HttpServer, middleware::Logger};
 use db::Pool;
 use dotenv::dotenv;
 use env_logger::Env;
-use log::info;
+use log::{info, warn};
+use middleware::session_tokens::SessionTokens;
+use middleware::token_verifier::{BearerTokenVerifier, TokenVerifier};
+use std::sync::Arc;
 
+use services::firebase_service::{FirebaseConfig, FirebaseIdentityProvider};
+use services::identity_provider::IdentityProvider;
 use services::{
-    categories_services::category_endpoints, chat_services::chat_endpoints,
+    auth_services::auth_endpoints,
+    booking_services::booking_endpoints,
+    categories_services::{category_cache::CategoryCache, category_endpoints},
+    chat_services::chat_endpoints,
+    holiday_services::holiday_endpoints,
     professional_profile_services::professional_profile_endpoints,
-    professional_services::professional_endpoints, task_services::task_endpoints,
+    professional_services::professional_endpoints, review_services::review_endpoints,
+    task_services::{task_endpoints, task_expiry},
     user_services::user_endpoints,
 };
 
@@ -103,11 +157,37 @@ async fn main() -> std::io::Result<()> {
     // Load environment variables
     dotenv().ok();
     
+    // Backend session tokens are optional, but never with a guessable secret
+    let session_tokens = SessionTokens::from_env()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?
+        .map(web::Data::new);
+    
     // Database connection pool
     let pool: Pool = db::establish_connection();
+
+    // Mark past-due tasks as expired in the background
+    task_expiry::spawn(web::Data::new(pool.clone()));
     
     // Start chat server actor for WebSocket
     let chat_server = websocket::ChatServer::new().start();
+
+    // Category catalog cache, shared by all workers
+    let category_cache = web::Data::new(CategoryCache::default());
+
+    // Firebase Auth, or its emulator when FIREBASE_AUTH_EMULATOR_HOST is set
+    let firebase_config = FirebaseConfig::from_env();
+    if firebase_config.emulator {
+        warn!(
+            "Using the Firebase Auth emulator at {}; ID tokens are not signature checked",
+            firebase_config.identity_toolkit_url
+        );
+    }
+    let identity: web::Data<dyn IdentityProvider> = web::Data::from(
+        Arc::new(FirebaseIdentityProvider::new(firebase_config)) as Arc<dyn IdentityProvider>,
+    );
+    let token_verifier: web::Data<dyn TokenVerifier> = web::Data::from(Arc::new(
+        BearerTokenVerifier::new(identity.clone(), session_tokens.clone()),
+    ) as Arc<dyn TokenVerifier>);
     
     let host = std::env::var("HOST").unwrap_or_else(|_| "0.0.0.0".to_string());
     let port = std::env::var("PORT").unwrap_or_else(|_| "8080".to_string());
@@ -132,6 +212,14 @@ async fn main() -> std::io::Result<()> {
             // App data
             .app_data(web::Data::new(pool.clone()))
             .app_data(web::Data::new(chat_server.clone()))
+            .app_data(category_cache.clone())
+            .app_data(identity.clone())
+            .app_data(token_verifier.clone())
+            .configure(|cfg| {
+                if let Some(session_tokens) = &session_tokens {
+                    cfg.app_data(session_tokens.clone());
+                }
+            })
             
             // WebSocket routes
             .service(
@@ -150,12 +238,16 @@ async fn main() -> std::io::Result<()> {
             }))
             
             // Existing API routes
+            .configure(auth_endpoints::auth_routes)
             .configure(user_endpoints::user_routes)
             .configure(professional_endpoints::professional_routes)
             .configure(professional_profile_endpoints::professional_profile_routes)
             .configure(category_endpoints::category_routes)
+            .configure(holiday_endpoints::holiday_routes)
             .configure(chat_endpoints::chat_routes)
             .configure(task_endpoints::task_routes)
+            .configure(booking_endpoints::booking_routes)
+            .configure(review_endpoints::review_routes)
     })
     .bind(&bind_address)?
     .run()
@@ -1,26 +1,54 @@
 use serde::{Deserialize, Serialize};
-use chrono::{DateTime, Utc};
+use chrono::{Duration, Utc};
 
-/// JWT Claims structure
+/// Claims of the session tokens the backend issues itself, see
+/// [`SessionTokens`](super::session_tokens::SessionTokens).
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,  // user_id
-    pub email: String,
+    pub iss: String,
     pub exp: usize,   // expiration time
     pub iat: usize,   // issued at
 }
 
 impl Claims {
-    pub fn new(user_id: String, email: String) -> Self {
+    pub fn new(user_id: String, issuer: &str, ttl: Duration) -> Self {
         let now = Utc::now();
-        let exp = (now + chrono::Duration::days(7)).timestamp() as usize;
+        let exp = (now + ttl).timestamp() as usize;
         let iat = now.timestamp() as usize;
         
         Claims {
             sub: user_id,
-            email,
+            iss: issuer.to_string(),
             exp,
             iat,
         }
     }
-}
\ No newline at end of file
+}
+
+/// Whether `user_uid` is listed in the comma separated `ADMIN_UIDS`
+/// environment variable.
+pub fn is_admin(user_uid: &str) -> bool {
+    std::env::var("ADMIN_UIDS")
+        .map(|uids| is_listed(&uids, user_uid))
+        .unwrap_or(false)
+}
+
+fn is_listed(uids: &str, user_uid: &str) -> bool {
+    uids.split(',')
+        .map(str::trim)
+        .any(|uid| !uid.is_empty() && uid == user_uid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn admins_are_matched_against_the_trimmed_list() {
+        assert!(is_listed("alice, bob", "bob"));
+        assert!(!is_listed("alice,bob", "bo"));
+        assert!(!is_listed("alice,,", ""));
+        assert!(!is_listed("", "alice"));
+    }
+}
@@ -0,0 +1,364 @@
+use crate::dal::role_db;
+use crate::db::Pool;
+use crate::errors::auth_errors::AuthError;
+use crate::middleware::auth::is_admin;
+use crate::middleware::token_verifier::TokenVerifier;
+use crate::models::user_aggregate::role::Role;
+use actix_web::{
+    dev::Payload, http::header::AUTHORIZATION, web, FromRequest, HttpMessage, HttpRequest,
+};
+use std::cell::RefCell;
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+type AuthFuture<T> = Pin<Box<dyn Future<Output = Result<T, AuthError>>>>;
+
+/// The caller of a request, identified by the Firebase ID token or session
+/// token in its `Authorization: Bearer <token>` header. Taking this as a handler argument
+/// rejects unauthenticated requests with 401 before the handler runs.
+///
+/// The caller's roles are loaded once and kept in the request extensions, so
+/// every extractor below sees the same user. Extractors of one handler run
+/// concurrently; while the token is being verified they share the pending
+/// [`Authentication`] instead of verifying it again.
+#[derive(Debug, Clone)]
+pub struct AuthenticatedUser {
+    pub uid: String,
+    pub roles: Vec<Role>,
+}
+
+impl AuthenticatedUser {
+    pub fn has_role(&self, role: Role) -> bool {
+        self.roles.contains(&role)
+    }
+}
+
+/// An [`AuthenticatedUser`] with the admin role; anyone else gets 403. Users
+/// listed in `ADMIN_UIDS` always have it, so the first admin can grant it to
+/// others.
+#[derive(Debug, Clone)]
+pub struct AdminUser {
+    pub uid: String,
+}
+
+/// An [`AuthenticatedUser`] with the customer role; anyone else gets 403.
+#[derive(Debug, Clone)]
+pub struct CustomerUser {
+    pub uid: String,
+}
+
+/// An [`AuthenticatedUser`] with the professional role; anyone else gets 403.
+#[derive(Debug, Clone)]
+pub struct ProfessionalUser {
+    pub uid: String,
+}
+
+/// The authentication of a request that is still running, kept in its
+/// extensions until it finishes.
+#[derive(Clone)]
+struct Authentication(Rc<RefCell<Option<AuthFuture<AuthenticatedUser>>>>);
+
+impl Authentication {
+    /// Drives the authentication. Only the extractor that sees it finish
+    /// gets the result, the others find `None`.
+    fn poll_result(
+        &self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<AuthenticatedUser, AuthError>>> {
+        let mut pending = self.0.borrow_mut();
+        let Some(future) = pending.as_mut() else {
+            return Poll::Ready(None);
+        };
+        future.as_mut().poll(cx).map(|result| {
+            *pending = None;
+            Some(result)
+        })
+    }
+}
+
+impl FromRequest for AuthenticatedUser {
+    type Error = AuthError;
+    type Future = AuthFuture<Self>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        if let Some(user) = req.extensions().get::<AuthenticatedUser>() {
+            let user = user.clone();
+            return Box::pin(async move { Ok(user) });
+        }
+
+        let pending = req.extensions().get::<Authentication>().cloned();
+        let authentication = pending.unwrap_or_else(|| {
+            let authentication = Authentication(Rc::new(RefCell::new(Some(authenticate(req)))));
+            req.extensions_mut().insert(authentication.clone());
+            authentication
+        });
+        let req = req.clone();
+
+        Box::pin(async move {
+            let user = match poll_fn(|cx| authentication.poll_result(cx)).await {
+                Some(result) => {
+                    req.extensions_mut().remove::<Authentication>();
+                    result?
+                }
+                None => {
+                    // Another extractor saw it finish. Had it failed, that
+                    // extractor's error would already reject the request, so
+                    // starting over here is rare.
+                    let user = req.extensions().get::<AuthenticatedUser>().cloned();
+                    match user {
+                        Some(user) => return Ok(user),
+                        None => authenticate(&req).await?,
+                    }
+                }
+            };
+            req.extensions_mut().insert(user.clone());
+            Ok(user)
+        })
+    }
+}
+
+/// Verifies the bearer token of `req` and loads the caller's roles. The
+/// future must not capture `req`, as the request keeps it in its extensions.
+fn authenticate(req: &HttpRequest) -> AuthFuture<AuthenticatedUser> {
+    let token = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(bearer_token)
+        .map(String::from);
+    let verifier = req.app_data::<web::Data<dyn TokenVerifier>>().cloned();
+    let db_pool = req.app_data::<web::Data<Pool>>().cloned();
+
+    Box::pin(async move {
+        let token = token.ok_or(AuthError::MissingToken)?;
+        let verifier = verifier.ok_or_else(|| {
+            AuthError::Configuration("no TokenVerifier is registered".to_string())
+        })?;
+        let uid = verifier.verify(&token).await?;
+
+        let db_pool = db_pool.ok_or_else(|| {
+            AuthError::Configuration("no database pool is registered".to_string())
+        })?;
+        let mut roles = role_db::get_caller_roles(db_pool, uid.clone())
+            .await?
+            .ok_or(AuthError::AccountDeleted)?;
+        if is_admin(&uid) && !roles.contains(&Role::Admin) {
+            roles.push(Role::Admin);
+        }
+
+        Ok(AuthenticatedUser { uid, roles })
+    })
+}
+
+impl FromRequest for AdminUser {
+    type Error = AuthError;
+    type Future = AuthFuture<Self>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let user = with_role(req, payload, Role::Admin);
+        Box::pin(async move {
+            Ok(AdminUser {
+                uid: user.await?.uid,
+            })
+        })
+    }
+}
+
+impl FromRequest for CustomerUser {
+    type Error = AuthError;
+    type Future = AuthFuture<Self>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let user = with_role(req, payload, Role::Customer);
+        Box::pin(async move {
+            Ok(CustomerUser {
+                uid: user.await?.uid,
+            })
+        })
+    }
+}
+
+impl FromRequest for ProfessionalUser {
+    type Error = AuthError;
+    type Future = AuthFuture<Self>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let user = with_role(req, payload, Role::Professional);
+        Box::pin(async move {
+            Ok(ProfessionalUser {
+                uid: user.await?.uid,
+            })
+        })
+    }
+}
+
+/// The caller, if they hold `role`.
+fn with_role(
+    req: &HttpRequest,
+    payload: &mut Payload,
+    role: Role,
+) -> AuthFuture<AuthenticatedUser> {
+    let user = AuthenticatedUser::from_request(req, payload);
+
+    Box::pin(async move {
+        let user = user.await?;
+        match role {
+            _ if user.has_role(role) => Ok(user),
+            Role::Admin => Err(AuthError::AdminRequired),
+            role => Err(AuthError::RoleRequired(role)),
+        }
+    })
+}
+
+/// The token of a `Bearer <token>` authorization header.
+pub fn bearer_token(header: &str) -> Option<&str> {
+    let (scheme, token) = header.trim().split_once(' ')?;
+    let token = token.trim();
+    (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty()).then_some(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::token_verifier::VerifyFuture;
+    use crate::test_support::{
+        insert_fixture, request_as, status_of, test_app, test_pool, ADMIN, CUSTOMER, PROFESSIONAL,
+    };
+    use actix_web::http::{Method, StatusCode};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn parses_bearer_tokens() {
+        assert_eq!(bearer_token("Bearer abc.def.ghi"), Some("abc.def.ghi"));
+        assert_eq!(bearer_token("bearer  abc "), Some("abc"));
+        assert_eq!(bearer_token("Basic dXNlcjpwdw=="), None);
+        assert_eq!(bearer_token("Bearer "), None);
+        assert_eq!(bearer_token("abc.def.ghi"), None);
+    }
+
+    fn request_from(roles: Vec<Role>) -> HttpRequest {
+        let req = actix_web::test::TestRequest::default().to_http_request();
+        req.extensions_mut().insert(AuthenticatedUser {
+            uid: "user-1".to_string(),
+            roles,
+        });
+        req
+    }
+
+    #[actix_web::test]
+    async fn role_extractors_require_their_role() {
+        let customer = request_from(vec![Role::Customer]);
+        assert!(CustomerUser::extract(&customer).await.is_ok());
+        assert!(matches!(
+            ProfessionalUser::extract(&customer).await,
+            Err(AuthError::RoleRequired(Role::Professional))
+        ));
+        assert!(matches!(
+            AdminUser::extract(&customer).await,
+            Err(AuthError::AdminRequired)
+        ));
+
+        let both = request_from(vec![Role::Customer, Role::Professional]);
+        assert!(CustomerUser::extract(&both).await.is_ok());
+        assert_eq!(
+            ProfessionalUser::extract(&both).await.unwrap().uid,
+            "user-1"
+        );
+    }
+
+    /// Rejects every token, after yielding once so extractors overlap.
+    #[derive(Default)]
+    struct CountingVerifier(AtomicUsize);
+
+    impl TokenVerifier for CountingVerifier {
+        fn verify<'a>(&'a self, _token: &'a str) -> VerifyFuture<'a> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async move {
+                actix_web::rt::task::yield_now().await;
+                Err(AuthError::InvalidToken)
+            })
+        }
+    }
+
+    #[actix_web::test]
+    async fn concurrent_extractors_verify_the_token_once() {
+        let verifier = Arc::new(CountingVerifier::default());
+        let req = actix_web::test::TestRequest::default()
+            .insert_header((AUTHORIZATION, "Bearer abc"))
+            .app_data(web::Data::from(verifier.clone() as Arc<dyn TokenVerifier>))
+            .to_http_request();
+
+        let extracted = <(AuthenticatedUser, CustomerUser, AdminUser)>::extract(&req).await;
+        assert!(extracted.is_err());
+        assert_eq!(verifier.0.load(Ordering::SeqCst), 1);
+    }
+
+    #[actix_web::test]
+    async fn role_specific_actions_require_the_role() {
+        let Some(pool) = test_pool() else {
+            return;
+        };
+        let fixture = insert_fixture(&pool);
+        let app = test_app!(pool);
+
+        for (method, uri, user_uid, expected) in [
+            (
+                Method::GET,
+                "/profiles/mine",
+                CUSTOMER,
+                StatusCode::FORBIDDEN,
+            ),
+            (Method::GET, "/profiles/mine", PROFESSIONAL, StatusCode::OK),
+            (Method::GET, "/task/browse", CUSTOMER, StatusCode::FORBIDDEN),
+        ] {
+            assert_eq!(
+                status_of!(app, request_as(method.clone(), uri, user_uid)),
+                expected,
+                "{} {} as {}",
+                method,
+                uri,
+                user_uid
+            );
+        }
+
+        // Extractors run concurrently, so the body must be valid for the role
+        // check to be what rejects the request.
+        let task = serde_json::json!({
+            "title": "Paint the fence",
+            "category_id": 1,
+            "is_flexible_timing": true,
+        });
+        assert_eq!(
+            status_of!(
+                app,
+                request_as(Method::POST, "/task/place", PROFESSIONAL).set_json(&task)
+            ),
+            StatusCode::FORBIDDEN
+        );
+
+        // The customer is still a party of the booking, but without the customer
+        // role they may no longer act for that side.
+        let revoke = format!("/users/{}/roles/customer", CUSTOMER);
+        assert_eq!(
+            status_of!(app, request_as(Method::DELETE, &revoke, ADMIN)),
+            StatusCode::OK
+        );
+        let booking = format!("/bookings/{}", fixture.booking_id);
+        let cancel = format!("{}/cancel", booking);
+        assert_eq!(
+            status_of!(app, request_as(Method::GET, &booking, CUSTOMER)),
+            StatusCode::OK
+        );
+        assert_eq!(
+            status_of!(app, request_as(Method::POST, &cancel, CUSTOMER)),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status_of!(app, request_as(Method::POST, &cancel, PROFESSIONAL)),
+            StatusCode::OK
+        );
+    }
+}
@@ -0,0 +1,231 @@
+use crate::errors::firebase_errors::FirebaseServiceError;
+use crate::services::identity_provider::IdentityClaims;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use reqwest::header::{AGE, CACHE_CONTROL};
+use reqwest::Client;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+pub const FIREBASE_PUBLIC_KEYS_URL: &str =
+    "https://www.googleapis.com/robot/v1/metadata/x509/securetoken@system.gserviceaccount.com";
+
+/// Used when Google's response carries no usable `Cache-Control` header.
+const DEFAULT_KEYS_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Keys are never fetched more often than this, so a stream of tokens with
+/// unknown `kid`s cannot turn every request into a round trip to Google.
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Deserialize)]
+struct FirebaseClaims {
+    sub: String,
+}
+
+struct KeySet {
+    keys: HashMap<String, DecodingKey>,
+    fetched_at: Instant,
+    ttl: Duration,
+}
+
+impl KeySet {
+    fn is_expired(&self) -> bool {
+        self.fetched_at.elapsed() >= self.ttl
+    }
+
+    fn may_refresh(&self) -> bool {
+        self.fetched_at.elapsed() >= MIN_REFRESH_INTERVAL
+    }
+}
+
+/// Google's public keys for Firebase ID tokens, shared by all workers. The
+/// keys rotate every few hours, so they are kept for as long as Google's
+/// cache headers allow and fetched again early when a token names a `kid`
+/// we have not seen yet.
+pub struct FirebaseKeyCache {
+    client: Client,
+    keys_url: String,
+    keys: RwLock<Option<Arc<KeySet>>>,
+}
+
+impl FirebaseKeyCache {
+    /// A cache of the x509 certificates published at `keys_url`.
+    pub fn new(keys_url: &str) -> Self {
+        FirebaseKeyCache {
+            client: Client::new(),
+            keys_url: keys_url.to_string(),
+            keys: RwLock::new(None),
+        }
+    }
+
+    /// Checks the signature, expiry, audience and issuer of an ID token of
+    /// the Firebase project `project_id` and returns its claims.
+    pub async fn verify(
+        &self,
+        token: &str,
+        project_id: &str,
+    ) -> Result<IdentityClaims, FirebaseServiceError> {
+        let header = decode_header(token).map_err(|_| FirebaseServiceError::JwtDecodeError)?;
+        if header.alg != Algorithm::RS256 {
+            return Err(FirebaseServiceError::JwtDecodeError);
+        }
+        let kid = header.kid.ok_or(FirebaseServiceError::MissingKidError)?;
+        let key = self.key(&kid).await?;
+
+        let mut validation = Validation::new(Algorithm::RS256);
+        validation.set_audience(&[project_id]);
+        validation.set_issuer(&[format!("https://securetoken.google.com/{}", project_id)]);
+
+        let token_data = decode::<FirebaseClaims>(token, &key, &validation)
+            .map_err(|_| FirebaseServiceError::JwtDecodeError)?;
+        if token_data.claims.sub.is_empty() {
+            return Err(FirebaseServiceError::JwtDecodeError);
+        }
+        Ok(IdentityClaims {
+            uid: token_data.claims.sub,
+        })
+    }
+
+    /// The key for `kid`, fetching the key set first when it has expired or
+    /// does not know `kid`.
+    async fn key(&self, kid: &str) -> Result<DecodingKey, FirebaseServiceError> {
+        let cached = self.keys.read().unwrap_or_else(|e| e.into_inner()).clone();
+        if let Some(set) = &cached {
+            if !set.is_expired() {
+                if let Some(key) = set.keys.get(kid) {
+                    return Ok(key.clone());
+                }
+                if !set.may_refresh() {
+                    return Err(FirebaseServiceError::InvalidKidError);
+                }
+            }
+        }
+
+        let set = Arc::new(self.fetch().await?);
+        *self.keys.write().unwrap_or_else(|e| e.into_inner()) = Some(set.clone());
+        set.keys
+            .get(kid)
+            .cloned()
+            .ok_or(FirebaseServiceError::InvalidKidError)
+    }
+
+    async fn fetch(&self) -> Result<KeySet, FirebaseServiceError> {
+        let response = self
+            .client
+            .get(&self.keys_url)
+            .send()
+            .await?
+            .error_for_status()?;
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(String::from)
+        };
+        let ttl = keys_ttl(header(CACHE_CONTROL).as_deref(), header(AGE).as_deref());
+
+        let certificates = response.json::<HashMap<String, String>>().await?;
+        let mut keys = HashMap::new();
+        for (kid, certificate) in certificates {
+            keys.insert(kid, DecodingKey::from_rsa_pem(certificate.as_bytes())?);
+        }
+
+        Ok(KeySet {
+            keys,
+            fetched_at: Instant::now(),
+            ttl,
+        })
+    }
+}
+
+/// How long a key set may be served from memory: `max-age` minus the time
+/// the response already spent in upstream caches (`Age`), but never less
+/// than [`MIN_REFRESH_INTERVAL`].
+fn keys_ttl(cache_control: Option<&str>, age: Option<&str>) -> Duration {
+    let max_age = match cache_control.and_then(max_age) {
+        Some(max_age) => max_age,
+        None => return DEFAULT_KEYS_TTL,
+    };
+    let age = age
+        .and_then(|age| age.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or_default();
+    max_age.saturating_sub(age).max(MIN_REFRESH_INTERVAL)
+}
+
+/// The `max-age` directive of a `Cache-Control` header. `no-cache` and
+/// `no-store` count as a max-age of zero.
+fn max_age(cache_control: &str) -> Option<Duration> {
+    let mut max_age = None;
+    for directive in cache_control.split(',').map(str::trim) {
+        let (name, value) = match directive.split_once('=') {
+            Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+            None => (directive, None),
+        };
+        if name.eq_ignore_ascii_case("no-cache") || name.eq_ignore_ascii_case("no-store") {
+            return Some(Duration::ZERO);
+        }
+        if name.eq_ignore_ascii_case("max-age") {
+            max_age = value
+                .and_then(|value| value.parse::<u64>().ok())
+                .map(Duration::from_secs);
+        }
+    }
+    max_age
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_max_age_from_google_headers() {
+        assert_eq!(
+            max_age("public, max-age=19302, must-revalidate, no-transform"),
+            Some(Duration::from_secs(19302))
+        );
+        assert_eq!(max_age("Max-Age=\"60\""), Some(Duration::from_secs(60)));
+        assert_eq!(max_age("public, must-revalidate"), None);
+        assert_eq!(max_age("max-age=soon"), None);
+        assert_eq!(max_age("no-cache, max-age=600"), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn ttl_subtracts_age_and_keeps_a_floor() {
+        assert_eq!(
+            keys_ttl(Some("max-age=3600"), Some("600")),
+            Duration::from_secs(3000)
+        );
+        assert_eq!(
+            keys_ttl(Some("max-age=3600"), None),
+            Duration::from_secs(3600)
+        );
+        assert_eq!(
+            keys_ttl(Some("max-age=10"), Some("600")),
+            MIN_REFRESH_INTERVAL
+        );
+        assert_eq!(keys_ttl(Some("no-store"), None), MIN_REFRESH_INTERVAL);
+        assert_eq!(keys_ttl(None, Some("600")), DEFAULT_KEYS_TTL);
+    }
+
+    #[test]
+    fn unknown_kid_does_not_refetch_a_fresh_key_set() {
+        let fresh = KeySet {
+            keys: HashMap::new(),
+            fetched_at: Instant::now(),
+            ttl: DEFAULT_KEYS_TTL,
+        };
+        assert!(!fresh.is_expired());
+        assert!(!fresh.may_refresh());
+
+        let stale = KeySet {
+            keys: HashMap::new(),
+            fetched_at: Instant::now() - MIN_REFRESH_INTERVAL,
+            ttl: MIN_REFRESH_INTERVAL,
+        };
+        assert!(stale.is_expired());
+        assert!(stale.may_refresh());
+    }
+}
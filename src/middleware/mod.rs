@@ -1 +1,5 @@
-pub mod auth;
\ No newline at end of file
+pub mod auth;
+pub mod authenticated_user;
+pub mod firebase_keys;
+pub mod session_tokens;
+pub mod token_verifier;
@@ -0,0 +1,144 @@
+use crate::errors::auth_errors::AuthError;
+use crate::middleware::auth::Claims;
+use crate::middleware::token_verifier::{TokenVerifier, VerifyFuture};
+use chrono::Duration;
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use std::env;
+
+/// The placeholder secret older configurations fell back to. Anyone can sign
+/// tokens with it, so the server refuses to start when it is configured.
+const DEFAULT_JWT_SECRET: &str = "your-secret-key";
+
+/// HS256 keys shorter than the hash output are easy to brute force.
+const MIN_SECRET_LENGTH: usize = 32;
+
+const SESSION_TOKEN_ISSUER: &str = "goods-backend";
+
+/// How long an exchanged session token stays valid.
+const SESSION_TOKEN_TTL: Duration = Duration::hours(1);
+
+/// Short-lived HS256 tokens the backend mints in exchange for a Firebase ID
+/// token, so clients such as the chat socket can authenticate without
+/// refreshing Firebase tokens. Enabled by setting `JWT_SECRET`.
+pub struct SessionTokens {
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    ttl: Duration,
+}
+
+impl SessionTokens {
+    pub fn new(secret: &str) -> Result<Self, String> {
+        if secret == DEFAULT_JWT_SECRET {
+            return Err("JWT_SECRET must not be the default secret".to_string());
+        }
+        if secret.len() < MIN_SECRET_LENGTH {
+            return Err(format!(
+                "JWT_SECRET must be at least {} bytes long",
+                MIN_SECRET_LENGTH
+            ));
+        }
+
+        Ok(SessionTokens {
+            encoding_key: EncodingKey::from_secret(secret.as_bytes()),
+            decoding_key: DecodingKey::from_secret(secret.as_bytes()),
+            ttl: SESSION_TOKEN_TTL,
+        })
+    }
+
+    /// Session tokens configured through `JWT_SECRET`, or `None` when the
+    /// variable is unset and session tokens are disabled.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        match env::var("JWT_SECRET") {
+            Ok(secret) => SessionTokens::new(&secret).map(Some),
+            Err(env::VarError::NotPresent) => Ok(None),
+            Err(e) => Err(format!("JWT_SECRET: {}", e)),
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// A new session token for `user_uid`.
+    pub fn issue(&self, user_uid: &str) -> Result<String, AuthError> {
+        let claims = Claims::new(user_uid.to_string(), SESSION_TOKEN_ISSUER, self.ttl);
+        encode(&Header::new(Algorithm::HS256), &claims, &self.encoding_key)
+            .map_err(|e| AuthError::TokenIssueFailed(e.to_string()))
+    }
+
+    /// The uid a session token was issued to, if it is ours and unexpired.
+    pub fn verify(&self, token: &str) -> Result<String, AuthError> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_issuer(&[SESSION_TOKEN_ISSUER]);
+        validation.set_required_spec_claims(&["exp", "iss", "sub"]);
+
+        let token_data = decode::<Claims>(token, &self.decoding_key, &validation)
+            .map_err(|_| AuthError::InvalidToken)?;
+        if token_data.claims.sub.is_empty() {
+            return Err(AuthError::InvalidToken);
+        }
+        Ok(token_data.claims.sub)
+    }
+}
+
+impl TokenVerifier for SessionTokens {
+    fn verify<'a>(&'a self, token: &'a str) -> VerifyFuture<'a> {
+        Box::pin(async move { SessionTokens::verify(self, token) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "0123456789abcdef0123456789abcdef";
+
+    #[test]
+    fn refuses_default_and_short_secrets() {
+        assert!(SessionTokens::new(DEFAULT_JWT_SECRET).is_err());
+        assert!(SessionTokens::new("too-short").is_err());
+        assert!(SessionTokens::new(SECRET).is_ok());
+    }
+
+    #[test]
+    fn issued_tokens_verify_to_their_uid() {
+        let tokens = SessionTokens::new(SECRET).unwrap();
+        let token = tokens.issue("user-1").unwrap();
+
+        assert_eq!(tokens.verify(&token).unwrap(), "user-1");
+    }
+
+    #[test]
+    fn rejects_tokens_signed_with_another_secret() {
+        let ours = SessionTokens::new(SECRET).unwrap();
+        let theirs = SessionTokens::new("fedcba9876543210fedcba9876543210").unwrap();
+        let token = theirs.issue("user-1").unwrap();
+
+        assert!(matches!(ours.verify(&token), Err(AuthError::InvalidToken)));
+    }
+
+    #[test]
+    fn rejects_expired_tokens_and_foreign_issuers() {
+        let tokens = SessionTokens::new(SECRET).unwrap();
+        let key = EncodingKey::from_secret(SECRET.as_bytes());
+        let header = Header::new(Algorithm::HS256);
+
+        let expired = Claims::new(
+            "user-1".to_string(),
+            SESSION_TOKEN_ISSUER,
+            -Duration::hours(1),
+        );
+        let expired = encode(&header, &expired, &key).unwrap();
+        assert!(matches!(
+            tokens.verify(&expired),
+            Err(AuthError::InvalidToken)
+        ));
+
+        let foreign = Claims::new("user-1".to_string(), "someone-else", Duration::hours(1));
+        let foreign = encode(&header, &foreign, &key).unwrap();
+        assert!(matches!(
+            tokens.verify(&foreign),
+            Err(AuthError::InvalidToken)
+        ));
+    }
+}
@@ -0,0 +1,88 @@
+use crate::errors::auth_errors::AuthError;
+use crate::middleware::session_tokens::SessionTokens;
+use crate::services::identity_provider::IdentityProvider;
+use actix_web::web;
+use jsonwebtoken::{decode_header, Algorithm};
+use std::future::Future;
+use std::pin::Pin;
+
+pub type VerifyFuture<'a> = Pin<Box<dyn Future<Output = Result<String, AuthError>> + 'a>>;
+
+/// Resolves a bearer token to the uid of the user it was issued to. The REST
+/// extractors and the chat socket look up the `web::Data<dyn TokenVerifier>`
+/// registered in `main`, so tests and other deployments can swap it.
+pub trait TokenVerifier: Send + Sync {
+    fn verify<'a>(&'a self, token: &'a str) -> VerifyFuture<'a>;
+}
+
+/// Accepts ID tokens of the identity provider and, when enabled, the
+/// backend's own session tokens. The two are told apart by their signing
+/// algorithm.
+pub struct BearerTokenVerifier {
+    identity: web::Data<dyn IdentityProvider>,
+    sessions: Option<web::Data<SessionTokens>>,
+}
+
+impl BearerTokenVerifier {
+    pub fn new(
+        identity: web::Data<dyn IdentityProvider>,
+        sessions: Option<web::Data<SessionTokens>>,
+    ) -> Self {
+        BearerTokenVerifier { identity, sessions }
+    }
+}
+
+impl TokenVerifier for BearerTokenVerifier {
+    fn verify<'a>(&'a self, token: &'a str) -> VerifyFuture<'a> {
+        Box::pin(async move {
+            // Anything that is not ours, including the unsigned tokens of the
+            // Firebase Auth emulator, is left to the identity provider.
+            let alg = decode_header(token).map(|header| header.alg).ok();
+            match (alg, &self.sessions) {
+                (Some(Algorithm::HS256), Some(sessions)) => sessions.verify(token),
+                (Some(Algorithm::HS256), None) => Err(AuthError::InvalidToken),
+                _ => Ok(self.identity.uid_from_token(token).await?),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::fake_identity_provider::FakeIdentityProvider;
+    use std::sync::Arc;
+
+    const SECRET: &str = "0123456789abcdef0123456789abcdef";
+
+    fn verifier(sessions: Option<SessionTokens>) -> BearerTokenVerifier {
+        BearerTokenVerifier::new(
+            web::Data::from(Arc::new(FakeIdentityProvider::default()) as Arc<dyn IdentityProvider>),
+            sessions.map(web::Data::new),
+        )
+    }
+
+    #[actix_web::test]
+    async fn accepts_session_tokens_when_enabled() {
+        let sessions = SessionTokens::new(SECRET).unwrap();
+        let token = sessions.issue("user-1").unwrap();
+
+        let enabled = verifier(Some(sessions));
+        assert_eq!(enabled.verify(&token).await.unwrap(), "user-1");
+
+        let disabled = verifier(None);
+        assert!(matches!(
+            disabled.verify(&token).await,
+            Err(AuthError::InvalidToken)
+        ));
+    }
+
+    #[actix_web::test]
+    async fn rejects_malformed_tokens() {
+        let verifier = verifier(None);
+        assert!(matches!(
+            verifier.verify("not-a-jwt").await,
+            Err(AuthError::InvalidToken)
+        ));
+    }
+}
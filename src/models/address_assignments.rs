@@ -17,4 +17,11 @@ pub struct AddressAssignments {
     pub id: i32,
     pub address_id: i32,
     pub professional_profile_id: i32,
-}
\ No newline at end of file
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = address_assignments)]
+pub struct NewAddressAssignment {
+    pub address_id: i32,
+    pub professional_profile_id: i32,
+}
@@ -25,6 +25,7 @@ pub struct NewBooking {
     pub customer_uid: String,
     pub professional_profile_uid: String,
     pub date_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
     pub status: i32,
     pub description: Option<String>,
     pub category_id: i32,
@@ -0,0 +1,28 @@
+use crate::schema::schema::booking_history;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Queryable, Serialize, Deserialize, Debug)]
+pub struct BookingHistory {
+    pub id: i32,
+    pub booking_id: i32,
+    pub from_status: Option<i32>,
+    pub to_status: i32,
+    pub changed_by_uid: String,
+    pub changed_by_role: String,
+    pub note: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = booking_history)]
+pub struct NewBookingHistory {
+    pub booking_id: i32,
+    pub from_status: Option<i32>,
+    pub to_status: i32,
+    pub changed_by_uid: String,
+    pub changed_by_role: String,
+    pub note: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
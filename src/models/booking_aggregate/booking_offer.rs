@@ -0,0 +1,32 @@
+use crate::schema::schema::booking_offers;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Queryable, Serialize, Deserialize, Debug)]
+pub struct BookingOffer {
+    pub id: i32,
+    pub booking_id: i32,
+    pub version: i32,
+    pub offered_by_uid: String,
+    pub offered_by_role: String,
+    pub offering_price: f64,
+    pub date_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub message: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = booking_offers)]
+pub struct NewBookingOffer {
+    pub booking_id: i32,
+    pub version: i32,
+    pub offered_by_uid: String,
+    pub offered_by_role: String,
+    pub offering_price: f64,
+    pub date_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub message: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
@@ -1,6 +1,8 @@
+use crate::errors::booking_errors::BookingError;
+use crate::models::user_aggregate::role::Role;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BookingStatus {
     Proposed = 0,
     Accepted = 1,
@@ -12,18 +14,106 @@ pub enum BookingStatus {
     Warning = 7,
 }
 
-impl From<i32> for BookingStatus {
-    fn from(value: i32) -> Self {
+/// A move one of the booking parties wants to make on a booking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BookingAction {
+    Accept,
+    Reject,
+    CounterOffer,
+    Start,
+    Complete,
+    Cancel,
+    Flag,
+}
+
+/// The side of a booking the caller is acting for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BookingParty {
+    Customer,
+    Professional,
+}
+
+impl BookingParty {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BookingParty::Customer => "customer",
+            BookingParty::Professional => "professional",
+        }
+    }
+
+    /// The role a user needs to act for this side.
+    pub fn role(&self) -> Role {
+        match self {
+            BookingParty::Customer => Role::Customer,
+            BookingParty::Professional => Role::Professional,
+        }
+    }
+}
+
+impl BookingStatus {
+    /// Applies `action` on behalf of `party` and returns the resulting status.
+    ///
+    /// Fails with `ActionNotPermitted` when the move exists from this status but
+    /// belongs to the other party, and with `IllegalTransition` otherwise.
+    pub fn apply(
+        self,
+        action: BookingAction,
+        party: BookingParty,
+    ) -> Result<BookingStatus, BookingError> {
+        if let Some(next) = self.target(action, party) {
+            return Ok(next);
+        }
+
+        let other_party = match party {
+            BookingParty::Customer => BookingParty::Professional,
+            BookingParty::Professional => BookingParty::Customer,
+        };
+        if self.target(action, other_party).is_some() {
+            Err(BookingError::ActionNotPermitted { action, party })
+        } else {
+            Err(BookingError::IllegalTransition { from: self, action })
+        }
+    }
+
+    fn target(self, action: BookingAction, party: BookingParty) -> Option<BookingStatus> {
+        use BookingAction as A;
+        use BookingParty as P;
+        use BookingStatus as S;
+
+        match (self, action, party) {
+            (S::Proposed, A::Accept, P::Professional) | (S::CounterOffer, A::Accept, _) => {
+                Some(S::Accepted)
+            }
+            (S::Proposed, A::Reject, P::Professional) | (S::CounterOffer, A::Reject, _) => {
+                Some(S::Rejected)
+            }
+            (S::Proposed, A::CounterOffer, P::Professional)
+            | (S::CounterOffer, A::CounterOffer, _) => Some(S::CounterOffer),
+            (S::Accepted, A::Start, P::Professional) => Some(S::InProgress),
+            (S::InProgress, A::Complete, P::Professional) => Some(S::Completed),
+            (S::Proposed | S::CounterOffer | S::Accepted, A::Cancel, _) => Some(S::Cancelled),
+            (S::Accepted | S::InProgress | S::Completed, A::Flag, _) => Some(S::Warning),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<i32> for BookingStatus {
+    type Error = BookingError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
         match value {
-            0 => BookingStatus::Proposed,
-            1 => BookingStatus::Accepted,
-            2 => BookingStatus::Rejected,
-            3 => BookingStatus::CounterOffer,
-            4 => BookingStatus::InProgress,
-            5 => BookingStatus::Completed,
-            6 => BookingStatus::Cancelled,
-            7 => BookingStatus::Warning,
-            _ => panic!("Invalid status value: {}", value),
+            0 => Ok(BookingStatus::Proposed),
+            1 => Ok(BookingStatus::Accepted),
+            2 => Ok(BookingStatus::Rejected),
+            3 => Ok(BookingStatus::CounterOffer),
+            4 => Ok(BookingStatus::InProgress),
+            5 => Ok(BookingStatus::Completed),
+            6 => Ok(BookingStatus::Cancelled),
+            7 => Ok(BookingStatus::Warning),
+            _ => Err(BookingError::InvalidStatus(value)),
         }
     }
 }
@@ -33,3 +123,62 @@ impl From<BookingStatus> for i32 {
         status as i32
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_professional_accepts_proposal() {
+        let next = BookingStatus::Proposed
+            .apply(BookingAction::Accept, BookingParty::Professional)
+            .unwrap();
+        assert_eq!(next, BookingStatus::Accepted);
+    }
+
+    #[test]
+    fn test_customer_cannot_start_booking() {
+        match BookingStatus::Accepted.apply(BookingAction::Start, BookingParty::Customer) {
+            Err(BookingError::ActionNotPermitted { action, party }) => {
+                assert_eq!(action, BookingAction::Start);
+                assert_eq!(party, BookingParty::Customer);
+            }
+            other => panic!("Expected ActionNotPermitted, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_completed_booking_cannot_be_reopened() {
+        for action in [
+            BookingAction::Accept,
+            BookingAction::CounterOffer,
+            BookingAction::Start,
+            BookingAction::Cancel,
+        ] {
+            for party in [BookingParty::Customer, BookingParty::Professional] {
+                assert!(matches!(
+                    BookingStatus::Completed.apply(action, party),
+                    Err(BookingError::IllegalTransition { .. })
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn test_either_party_can_flag_completed_booking() {
+        for party in [BookingParty::Customer, BookingParty::Professional] {
+            let next = BookingStatus::Completed
+                .apply(BookingAction::Flag, party)
+                .unwrap();
+            assert_eq!(next, BookingStatus::Warning);
+        }
+    }
+
+    #[test]
+    fn test_unknown_status_value_is_rejected() {
+        assert!(matches!(
+            BookingStatus::try_from(42),
+            Err(BookingError::InvalidStatus(42))
+        ));
+    }
+}
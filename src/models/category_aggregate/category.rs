@@ -1,9 +1,18 @@
 use diesel::prelude::*;
 use crate::schema::schema::categories;
-#[derive(Queryable,Identifiable, Selectable)]
+use serde::Serialize;
+#[derive(Queryable,Identifiable, Selectable, Serialize)]
 #[diesel(table_name = categories)]
 pub struct Category {
     pub id: i32,
     pub name: String,
     pub description: Option<String>,
 }
+
+#[derive(Insertable, AsChangeset)]
+#[diesel(table_name = categories)]
+#[diesel(treat_none_as_null = true)]
+pub struct NewCategory {
+    pub name: String,
+    pub description: Option<String>,
+}
@@ -0,0 +1,121 @@
+use crate::models::dtos::category_dto::{CategoryDTO, CategorySuggestionDTO, SuggestionKind};
+
+/// Categories and subcategories whose name, or a word in it, starts with
+/// `prefix`, ignoring case. Whole-name matches come first, then the ones
+/// with the most offerings.
+pub fn suggest(
+    categories: &[CategoryDTO],
+    prefix: &str,
+    limit: usize,
+) -> Vec<CategorySuggestionDTO> {
+    let prefix = prefix.trim().to_lowercase();
+    if prefix.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches = Vec::new();
+    for category in categories {
+        if let Some(rank) = match_rank(&category.name, &prefix) {
+            matches.push((
+                rank,
+                CategorySuggestionDTO {
+                    kind: SuggestionKind::Category,
+                    id: category.id,
+                    name: category.name.clone(),
+                    category_id: category.id,
+                    offering_count: category.offering_count,
+                },
+            ));
+        }
+        for subcategory in &category.subcategories {
+            if let Some(rank) = match_rank(&subcategory.name, &prefix) {
+                matches.push((
+                    rank,
+                    CategorySuggestionDTO {
+                        kind: SuggestionKind::Subcategory,
+                        id: subcategory.id,
+                        name: subcategory.name.clone(),
+                        category_id: subcategory.category_id,
+                        offering_count: subcategory.offering_count,
+                    },
+                ));
+            }
+        }
+    }
+
+    matches.sort_by(|(rank_a, a), (rank_b, b)| {
+        rank_a
+            .cmp(rank_b)
+            .then(b.offering_count.cmp(&a.offering_count))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    matches
+        .into_iter()
+        .take(limit)
+        .map(|(_, suggestion)| suggestion)
+        .collect()
+}
+
+/// 0 when the whole name starts with the prefix, 1 when a later word does.
+fn match_rank(name: &str, prefix: &str) -> Option<u8> {
+    let name = name.to_lowercase();
+    if name.starts_with(prefix) {
+        return Some(0);
+    }
+    name.split(|c: char| !c.is_alphanumeric())
+        .any(|word| word.starts_with(prefix))
+        .then_some(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::dtos::category_dto::SubcategoryCountDTO;
+
+    fn catalog() -> Vec<CategoryDTO> {
+        let subcategory = |id, name: &str, offering_count| SubcategoryCountDTO {
+            id,
+            name: name.to_string(),
+            category_id: 1,
+            offering_count,
+        };
+        vec![CategoryDTO {
+            id: 1,
+            name: "Home & Garden".to_string(),
+            description: None,
+            offering_count: 12,
+            subcategories: vec![
+                subcategory(10, "Garden design", 2),
+                subcategory(11, "Gardening", 7),
+                subcategory(12, "Pipe repair", 3),
+            ],
+        }]
+    }
+
+    #[test]
+    fn test_whole_name_matches_rank_before_word_matches() {
+        let names: Vec<String> = suggest(&catalog(), " GARD", 10)
+            .into_iter()
+            .map(|suggestion| suggestion.name)
+            .collect();
+        assert_eq!(names, ["Gardening", "Garden design", "Home & Garden"]);
+    }
+
+    #[test]
+    fn test_suggestions_are_limited_and_typed() {
+        let suggestions = suggest(&catalog(), "gard", 1);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].kind, SuggestionKind::Subcategory);
+        assert_eq!(suggestions[0].id, 11);
+
+        let home = suggest(&catalog(), "home", 10);
+        assert_eq!(home.len(), 1);
+        assert_eq!(home[0].kind, SuggestionKind::Category);
+    }
+
+    #[test]
+    fn test_blank_prefix_suggests_nothing() {
+        assert!(suggest(&catalog(), "  ", 10).is_empty());
+        assert!(suggest(&catalog(), "xyz", 10).is_empty());
+    }
+}
@@ -11,3 +11,10 @@ pub struct Subcategory {
     pub name: String,
     pub category_id: i32,
 }
+
+#[derive(Insertable, AsChangeset)]
+#[diesel(table_name = subcategories)]
+pub struct NewSubcategory {
+    pub name: String,
+    pub category_id: i32,
+}
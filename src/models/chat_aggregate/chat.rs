@@ -1,4 +1,7 @@
-use crate::models::{booking_aggregate::booking::Booking, dtos::message_dto::MessageDTO};
+use crate::models::{
+    booking_aggregate::booking::Booking, booking_aggregate::booking_offer::BookingOffer,
+    dtos::message_dto::MessageDTO,
+};
 use crate::schema::schema::chat;
 use chrono::{DateTime, NaiveDateTime, Utc};
 use diesel::prelude::*;
@@ -8,12 +11,14 @@ use serde::{Deserialize, Serialize};
 pub enum ChatItem {
     Message(MessageDTO),
     Booking(Booking),
+    Offer(BookingOffer),
 }
 impl ChatItem {
     pub fn get_time(&self) -> chrono::DateTime<Utc> {
         match self {
             ChatItem::Message(msg) => DateTime::<Utc>::from_utc(msg.timestamp, Utc),
             ChatItem::Booking(booking) => booking.creation_time,
+            ChatItem::Offer(offer) => offer.created_at,
         }
     }
 }
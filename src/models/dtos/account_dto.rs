@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+/// Changes to the caller's own profile. Missing fields stay as they are and
+/// an empty `image_url` removes the picture.
+#[derive(Debug, Deserialize)]
+pub struct ProfileUpdateDTO {
+    pub name: Option<String>,
+    pub image_url: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountProfileDTO {
+    pub user_uid: String,
+    pub name: String,
+    pub image_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PasswordResetDTO {
+    pub email: String,
+}
+
+/// What deleting an account changed besides removing the account itself.
+#[derive(Debug, Serialize)]
+pub struct AccountDeletionDTO {
+    pub user_uid: String,
+    pub anonymized_reviews: usize,
+    pub closed_tasks: usize,
+    pub withdrawn_proposals: usize,
+    pub cancelled_bookings: usize,
+}
@@ -0,0 +1,10 @@
+use serde::Serialize;
+
+/// A backend session token, returned in exchange for a Firebase ID token.
+#[derive(Debug, Serialize)]
+pub struct SessionTokenDTO {
+    pub token: String,
+    pub token_type: String,
+    /// Seconds until the token expires.
+    pub expires_in: i64,
+}
@@ -1,3 +1,6 @@
+use super::professional_profile_detail_dto::ServiceOfferingDTO;
+use crate::models::booking_aggregate::booking::Booking;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -9,9 +12,65 @@ pub struct BookingDTO {
     pub end_time: Option<String>,
     pub status: i32,
     pub description: Option<String>,
-    pub category_id: i32,
     pub offering_id: i32,
-    pub offering_price: f64,
-    pub service_offering_name: String,
     pub image_urls: Option<Vec<String>>,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct BookingTransitionDTO {
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CounterOfferDTO {
+    pub offering_price: f64,
+    pub date_time: Option<String>,
+    pub end_time: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BookingSummaryDTO {
+    pub id: i32,
+    pub customer_uid: String,
+    pub professional_profile_uid: String,
+    pub date_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub status: i32,
+    pub description: Option<String>,
+    pub category_id: i32,
+    pub service_offering_id: Option<i32>,
+    pub service_offering_name: Option<String>,
+    pub offering_price: f64,
+    pub chat_id: i32,
+    pub creation_time: DateTime<Utc>,
+    pub image_urls: Vec<String>,
+}
+
+impl BookingSummaryDTO {
+    pub fn booking_to_dto(booking: Booking, image_urls: Vec<String>) -> Self {
+        BookingSummaryDTO {
+            id: booking.id,
+            customer_uid: booking.customer_uid,
+            professional_profile_uid: booking.professional_profile_uid,
+            date_time: booking.date_time,
+            end_time: booking.end_time,
+            status: booking.status,
+            description: booking.description,
+            category_id: booking.category_id,
+            service_offering_id: booking.service_offering_id,
+            service_offering_name: booking.service_offering_name,
+            offering_price: booking.offering_price,
+            chat_id: booking.chat_id,
+            creation_time: booking.creation_time,
+            image_urls,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct BookingDetailDTO {
+    #[serde(flatten)]
+    pub booking: BookingSummaryDTO,
+    pub service_offering: Option<ServiceOfferingDTO>,
+}
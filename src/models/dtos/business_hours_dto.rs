@@ -0,0 +1,30 @@
+use crate::models::professional_aggregate::business_hour_exception::BusinessHourException;
+use chrono::{NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+
+/// One opening interval of the weekly schedule. `day_of_week` runs from
+/// 0 (Sunday) to 6, and 7 holds the hours used on public holidays.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeeklyHoursDTO {
+    pub day_of_week: i32,
+    pub opening_time: NaiveTime,
+    pub closing_time: NaiveTime,
+}
+
+/// Body of adding an exception. Leaving out the times closes the profile
+/// for the whole range.
+#[derive(Debug, Deserialize)]
+pub struct BusinessHourExceptionInputDTO {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub opening_time: Option<NaiveTime>,
+    pub closing_time: Option<NaiveTime>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BusinessHoursDTO {
+    pub weekly: Vec<WeeklyHoursDTO>,
+    /// Exceptions that have not ended yet, earliest first.
+    pub exceptions: Vec<BusinessHourException>,
+}
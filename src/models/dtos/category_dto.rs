@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+/// A category with its subcategories and how many service offerings each has.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryDTO {
+    pub id: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub offering_count: i64,
+    pub subcategories: Vec<SubcategoryCountDTO>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubcategoryCountDTO {
+    pub id: i32,
+    pub name: String,
+    pub category_id: i32,
+    pub offering_count: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionKind {
+    Category,
+    Subcategory,
+}
+
+/// Type-ahead entry for the search box. `category_id` is the category itself
+/// for category suggestions and the parent for subcategories.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CategorySuggestionDTO {
+    pub kind: SuggestionKind,
+    pub id: i32,
+    pub name: String,
+    pub category_id: i32,
+    pub offering_count: i64,
+}
+
+/// Body of creating or replacing a category.
+#[derive(Debug, Deserialize)]
+pub struct CategoryInputDTO {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/// Body of creating or replacing a subcategory.
+#[derive(Debug, Deserialize)]
+pub struct SubcategoryInputDTO {
+    pub name: String,
+    pub category_id: i32,
+}
@@ -1,5 +1,7 @@
 use super::address_dto::*;
 use super::review_dto::*;
+use crate::models::professional_aggregate::availability::OpeningInterval;
+use crate::models::professional_aggregate::pricing_unit::PricingUnit;
 use crate::models::professional_aggregate::service_offering::*;
 use chrono::NaiveTime;
 use serde::{Deserialize, Serialize};
@@ -20,6 +22,12 @@ pub struct ProfessionalProfileDetailDTO {
 
     pub closing_time: Option<NaiveTime>,
 
+    pub opening_hours: Vec<OpeningInterval>, // All of today's intervals, earliest first
+
+    pub is_open_now: bool,
+
+    pub timezone: String, // IANA zone the hours are local to
+
     pub delivery_enabled: bool,
 
     pub remote_available: bool,
@@ -46,6 +54,8 @@ pub struct ServiceOfferingDTO {
     pub subcategory_name: String,
     pub category_id: i32,
     pub price: f64,
+    pub pricing_unit: PricingUnit,
+    pub estimated_duration_minutes: Option<i32>,
 }
 
 impl ServiceOfferingDTO {
@@ -59,6 +69,9 @@ impl ServiceOfferingDTO {
             subcategory_name: service_offering.subcategory_name.clone(),
             category_id: category_id,
             price: service_offering.price,
+            // The column is constrained to known units.
+            pricing_unit: PricingUnit::try_from(service_offering.pricing_unit).unwrap_or_default(),
+            estimated_duration_minutes: service_offering.estimated_duration_minutes,
         }
     }
 }
@@ -0,0 +1,56 @@
+use super::address_dto::AddressDTO;
+use crate::models::professional_aggregate::pricing_unit::PricingUnit;
+use crate::models::professional_aggregate::professional_profile::ProfessionalProfile;
+use serde::{Deserialize, Serialize};
+
+/// Body of creating a profile. A professional has at most one profile per
+/// category.
+#[derive(Debug, Deserialize)]
+pub struct CreateProfessionalProfileDTO {
+    pub category_id: i32,
+    pub credentials: Option<String>,
+    pub image_url: Option<String>,
+    #[serde(default)]
+    pub delivery_enabled: bool,
+    #[serde(default)]
+    pub remote_available: bool,
+    /// IANA zone of the opening hours, `Europe/Berlin` when left out.
+    pub timezone: Option<String>,
+    pub address: Option<AddressDTO>,
+}
+
+/// Body of editing a profile; replaces all editable fields.
+#[derive(Debug, Deserialize)]
+pub struct UpdateProfessionalProfileDTO {
+    pub credentials: Option<String>,
+    pub image_url: Option<String>,
+    pub delivery_enabled: bool,
+    pub remote_available: bool,
+    /// IANA zone of the opening hours, unchanged when left out.
+    pub timezone: Option<String>,
+}
+
+/// A profile as seen by its owner.
+#[derive(Serialize)]
+pub struct OwnProfessionalProfileDTO {
+    #[serde(flatten)]
+    pub profile: ProfessionalProfile,
+    pub address: Option<AddressDTO>,
+}
+
+/// Body of creating or replacing a service offering. The subcategory must
+/// belong to the profile's category.
+#[derive(Debug, Deserialize)]
+pub struct ServiceOfferingInputDTO {
+    pub subcategory_id: i32,
+    pub price: f64,
+    #[serde(default)]
+    pub pricing_unit: PricingUnit,
+    /// Expected length of one job, used as the booking duration.
+    pub estimated_duration_minutes: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RenameProfessionalDTO {
+    pub name: String,
+}
@@ -23,6 +23,10 @@ pub struct ProfessionalProfileDTO {
     #[diesel(sql_type = Nullable<Time>)]
     pub closing_time: Option<NaiveTime>,
 
+    /// Whether today's hours cover the current time at the profile.
+    #[diesel(sql_type = Bool)]
+    pub is_open_now: bool,
+
     #[diesel(sql_type = Bool)]
     pub remote_available: bool,
 
@@ -52,4 +56,29 @@ pub struct ProfessionalProfileDTO {
 
     #[diesel(sql_type = Text)]
     pub professional_name: String,
+
+    /// Distance from the searched location.
+    #[diesel(sql_type = Float8)]
+    pub distance_km: f64,
+
+    /// Lowest price among the offerings matching the search.
+    #[diesel(sql_type = Float8)]
+    pub price: f64,
+
+    /// Weighted score of rating, review count and distance used by the
+    /// `best_match` sort, between 0 and 1.
+    #[diesel(sql_type = Float8)]
+    pub match_score: f64,
+
+    /// Full-text rank against the search phrase; 0 when there is none.
+    #[diesel(sql_type = Float8)]
+    pub relevance: f64,
+}
+
+/// One page of search results.
+#[derive(Debug, Serialize)]
+pub struct ProfessionalSearchPageDTO {
+    pub profiles: Vec<ProfessionalProfileDTO>,
+    /// Pass as `cursor` to fetch the next page; absent on the last page.
+    pub next_cursor: Option<String>,
 }
@@ -5,7 +5,9 @@ use chrono::{DateTime, Utc};
 use crate::models::
 {
     review_aggregate::review::*,
-    review_aggregate::review_content_assignments::*
+    review_aggregate::review_content_assignments::*,
+    review_aggregate::review_reply::ReviewReply,
+    review_aggregate::review_report::ReviewReport,
 };
 
 #[derive(Debug, Serialize, Deserialize, )]
@@ -17,10 +19,70 @@ pub struct ReviewDTO {
     pub message: String,
     pub rate: f64,
     pub published_at: DateTime<Utc>,
-    pub content_assignments: Option<Vec<ReviewContentAssignmentDTO>>
+    pub content_assignments: Option<Vec<ReviewContentAssignmentDTO>>,
+    pub reply: Option<ReviewReply>,
 }
 
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateReviewDTO {
+    pub booking_id: i32,
+    pub rate: f64,
+    pub message: String,
+    pub image_urls: Option<Vec<String>>,
+}
+
+/// Edits an own review. `image_urls`, when present, replaces all images.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateReviewDTO {
+    pub rate: f64,
+    pub message: String,
+    pub image_urls: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReviewReplyDTO {
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReviewReportDTO {
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationAction {
+    /// Keep the review hidden for good.
+    Remove,
+    /// Show the review again.
+    Dismiss,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResolveReportDTO {
+    pub action: ModerationAction,
+}
+
+/// A queued report together with the review it is about.
+#[derive(Serialize)]
+pub struct ReportedReviewDTO {
+    #[serde(flatten)]
+    pub report: ReviewReport,
+    pub review: ReviewDTO,
+}
+
+/// One page of a profile's reviews plus rating statistics over all of them.
+#[derive(Serialize)]
+pub struct ReviewPageDTO {
+    pub reviews: Vec<ReviewDTO>,
+    /// Pass as `cursor` to fetch the next page; absent on the last page.
+    pub next_cursor: Option<String>,
+    pub review_count: i64,
+    /// Number of one- to five-star reviews, in that order.
+    pub histogram: [i64; 5],
+}
+
 #[derive(Queryable, Debug, Serialize, Deserialize,)]
 pub struct ReviewContentAssignmentDTO {
     pub review_id: i32,
@@ -34,7 +96,7 @@ fn from_review_assignments(review_assignments: &Vec<ReviewContentAssignment>) ->
 }
 
 impl ReviewDTO {
-    pub fn review_to_dto(review: &Review, review_assignments: &Vec<ReviewContentAssignment>) -> ReviewDTO {
+    pub fn review_to_dto(review: &Review, review_assignments: &Vec<ReviewContentAssignment>, reply: Option<ReviewReply>) -> ReviewDTO {
         let content_assignments = if review_assignments.is_empty() {
             None
         } else {
@@ -50,6 +112,7 @@ impl ReviewDTO {
             rate: review.rate,
             published_at: review.published_at,
             content_assignments,
+            reply,
         }
     }
 }
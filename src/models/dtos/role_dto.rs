@@ -0,0 +1,13 @@
+use crate::models::user_aggregate::role::Role;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+pub struct RoleGrantDTO {
+    pub role: Role,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserRolesDTO {
+    pub user_uid: String,
+    pub roles: Vec<Role>,
+}
@@ -1,4 +1,7 @@
 use super::address_dto::AddressDTO;
+use crate::models::address::Address;
+use crate::models::task_aggregate::task::Task;
+use crate::models::task_aggregate::task_proposal::TaskProposal;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize)]
@@ -14,3 +17,68 @@ pub struct TaskDto {
     pub min_price: Option<f64>,
     pub max_price: Option<f64>,
 }
+
+#[derive(Serialize, Deserialize)]
+pub struct TaskSummaryDTO {
+    pub id: i32,
+    pub user_uid: String,
+    pub creation_time: chrono::NaiveDateTime,
+    pub title: String,
+    pub description: Option<String>,
+    pub category_id: i32,
+    pub is_flexible_timing: bool,
+    pub scheduled_date: Option<chrono::NaiveDate>,
+    pub scheduled_time: Option<chrono::NaiveTime>,
+    pub min_price: Option<f64>,
+    pub max_price: Option<f64>,
+    pub status: i32,
+    pub address: Option<AddressDTO>,
+    pub image_urls: Vec<String>,
+    /// Kilometres from the browsing location, when one was given.
+    pub distance_km: Option<f64>,
+}
+
+impl TaskSummaryDTO {
+    pub fn task_to_dto(
+        task: Task,
+        address: Option<Address>,
+        image_urls: Vec<String>,
+        distance_km: Option<f64>,
+    ) -> TaskSummaryDTO {
+        TaskSummaryDTO {
+            id: task.id,
+            user_uid: task.user_uid,
+            creation_time: task.creation_time,
+            title: task.title,
+            description: task.description,
+            category_id: task.category_id,
+            is_flexible_timing: task.is_flexible_timing,
+            scheduled_date: task.scheduled_date,
+            scheduled_time: task.scheduled_time,
+            min_price: task.min_price,
+            max_price: task.max_price,
+            status: task.status,
+            address: address.as_ref().map(AddressDTO::address_to_dto),
+            image_urls,
+            distance_km,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TaskProposalDTO {
+    pub professional_profile_id: i32,
+    pub price: f64,
+    pub message: Option<String>,
+}
+
+/// A proposal as shown to the task owner, with the proposing profile.
+#[derive(Serialize)]
+pub struct TaskProposalDetailDTO {
+    #[serde(flatten)]
+    pub proposal: TaskProposal,
+    pub professional_profile_uid: String,
+    pub professional_name: String,
+    pub image_url: Option<String>,
+    pub average_rating: Option<f64>,
+}
@@ -0,0 +1,613 @@
+use super::business_hour::BusinessHours;
+use super::business_hour_exception::BusinessHourException;
+use super::day_of_week::DayOfWeek;
+use crate::models::dtos::business_hours_dto::{BusinessHourExceptionInputDTO, WeeklyHoursDTO};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// Longest range a single business hour exception may cover.
+const MAX_EXCEPTION_DAYS: i64 = 366;
+
+/// Zone of profiles that did not choose one, matching the column default.
+pub const DEFAULT_TIMEZONE: Tz = chrono_tz::Europe::Berlin;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpeningInterval {
+    pub opening_time: NaiveTime,
+    pub closing_time: NaiveTime,
+}
+
+impl OpeningInterval {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        self.opening_time <= time && time < self.closing_time
+    }
+}
+
+/// An existing booking that blocks part of a professional's calendar.
+#[derive(Debug, Clone, Copy)]
+pub struct BusySlot {
+    pub booking_id: i32,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TimeSlot {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum SlotConflict {
+    OutsideBusinessHours {
+        opening_hours: Vec<OpeningInterval>,
+    },
+    Overlap {
+        booking_id: i32,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    },
+}
+
+/// Everything that decides when a profile is open over a range of dates.
+/// Dates and times are local to `timezone`.
+pub struct ProfileCalendar {
+    pub timezone: Tz,
+    pub hours: Vec<BusinessHours>,
+    pub exceptions: Vec<BusinessHourException>,
+    /// Public holidays at the profile's address.
+    pub holidays: Vec<NaiveDate>,
+}
+
+impl ProfileCalendar {
+    pub fn intervals_on(&self, date: NaiveDate) -> Vec<OpeningInterval> {
+        opening_intervals(
+            &self.hours,
+            &self.exceptions,
+            date,
+            self.holidays.contains(&date),
+        )
+    }
+
+    /// Wall-clock time of the profile at `at`.
+    pub fn local(&self, at: DateTime<Utc>) -> NaiveDateTime {
+        at.with_timezone(&self.timezone).naive_local()
+    }
+
+    /// The instant a local wall-clock time stands for. Times skipped by a
+    /// daylight saving change have none; repeated ones use the first.
+    pub fn instant(&self, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+        self.timezone
+            .from_local_datetime(&local)
+            .earliest()
+            .map(|at| at.with_timezone(&Utc))
+    }
+
+    pub fn is_open_at(&self, at: DateTime<Utc>) -> bool {
+        let local = self.local(at);
+        self.intervals_on(local.date())
+            .iter()
+            .any(|interval| interval.contains(local.time()))
+    }
+}
+
+/// Parses an IANA zone name such as `Europe/Berlin`.
+pub fn parse_timezone(name: &str) -> Result<Tz, String> {
+    name.parse::<Tz>()
+        .map_err(|_| format!("unknown timezone {}", name))
+}
+
+/// Opening intervals that apply on `date`.
+///
+/// Exceptions covering the date replace everything else, and any closure
+/// among them closes the whole day. Otherwise, on holidays the `Holiday` rows
+/// win when the profile has any, and the regular weekday rows are used.
+pub fn opening_intervals(
+    hours: &[BusinessHours],
+    exceptions: &[BusinessHourException],
+    date: NaiveDate,
+    is_holiday: bool,
+) -> Vec<OpeningInterval> {
+    let covering: Vec<&BusinessHourException> = exceptions
+        .iter()
+        .filter(|exception| exception.covers(date))
+        .collect();
+    if !covering.is_empty() {
+        if covering.iter().any(|exception| exception.is_closure()) {
+            return Vec::new();
+        }
+        let mut intervals: Vec<OpeningInterval> = covering
+            .iter()
+            .filter_map(|exception| interval(exception.opening_time, exception.closing_time))
+            .collect();
+        intervals.sort_by_key(|interval| interval.opening_time);
+        return intervals;
+    }
+
+    let weekday = DayOfWeek::from_date(date).to_i32();
+    let holiday = DayOfWeek::Holiday.to_i32();
+    let has_holiday_rows = hours.iter().any(|bh| bh.day_of_week == holiday);
+    let day = if is_holiday && has_holiday_rows {
+        holiday
+    } else {
+        weekday
+    };
+
+    let mut intervals: Vec<OpeningInterval> = hours
+        .iter()
+        .filter(|bh| bh.day_of_week == day && bh.is_available)
+        .filter_map(|bh| interval(bh.opening_time, bh.closing_time))
+        .collect();
+    intervals.sort_by_key(|interval| interval.opening_time);
+    intervals
+}
+
+fn interval(
+    opening_time: Option<NaiveTime>,
+    closing_time: Option<NaiveTime>,
+) -> Option<OpeningInterval> {
+    match (opening_time, closing_time) {
+        (Some(opening_time), Some(closing_time)) if opening_time < closing_time => {
+            Some(OpeningInterval {
+                opening_time,
+                closing_time,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// The interval that is open at `time`, or else the first one of the day.
+pub fn interval_at(intervals: &[OpeningInterval], time: NaiveTime) -> Option<OpeningInterval> {
+    intervals
+        .iter()
+        .find(|interval| interval.contains(time))
+        .or_else(|| intervals.first())
+        .copied()
+}
+
+/// Checks a full weekly schedule: known days, and intervals that are not
+/// empty and do not overlap others of the same day.
+pub fn validate_weekly_hours(hours: &[WeeklyHoursDTO]) -> Result<(), String> {
+    for entry in hours {
+        if DayOfWeek::from_i32(entry.day_of_week).is_none() {
+            return Err(format!("unknown day_of_week {}", entry.day_of_week));
+        }
+        if entry.opening_time >= entry.closing_time {
+            return Err(format!(
+                "opening_time must be before closing_time on day {}",
+                entry.day_of_week
+            ));
+        }
+    }
+
+    let mut sorted: Vec<&WeeklyHoursDTO> = hours.iter().collect();
+    sorted.sort_by_key(|entry| (entry.day_of_week, entry.opening_time));
+    match sorted.windows(2).find(|pair| {
+        pair[0].day_of_week == pair[1].day_of_week && pair[1].opening_time < pair[0].closing_time
+    }) {
+        Some(pair) => Err(format!("intervals on day {} overlap", pair[0].day_of_week)),
+        None => Ok(()),
+    }
+}
+
+pub fn validate_exception(exception: &BusinessHourExceptionInputDTO) -> Result<(), String> {
+    if exception.end_date < exception.start_date {
+        return Err("end_date must not be before start_date".to_string());
+    }
+    if (exception.end_date - exception.start_date).num_days() >= MAX_EXCEPTION_DAYS {
+        return Err(format!(
+            "an exception may cover at most {} days",
+            MAX_EXCEPTION_DAYS
+        ));
+    }
+    match (exception.opening_time, exception.closing_time) {
+        (None, None) => Ok(()),
+        (Some(opening_time), Some(closing_time)) if opening_time < closing_time => Ok(()),
+        (Some(_), Some(_)) => Err("opening_time must be before closing_time".to_string()),
+        _ => Err("opening_time and closing_time must be given together".to_string()),
+    }
+}
+
+/// Checks that `[start, end)` lies within one opening interval of its local
+/// day and does not intersect any busy slot.
+pub fn check_slot(
+    calendar: &ProfileCalendar,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    busy: &[BusySlot],
+) -> Result<(), SlotConflict> {
+    let (local_start, local_end) = (calendar.local(start), calendar.local(end));
+    let intervals = calendar.intervals_on(local_start.date());
+    let same_day = local_start.date() == local_end.date();
+    let within_hours = same_day
+        && intervals.iter().any(|interval| {
+            interval.opening_time <= local_start.time() && local_end.time() <= interval.closing_time
+        });
+    if !within_hours {
+        return Err(SlotConflict::OutsideBusinessHours {
+            opening_hours: intervals,
+        });
+    }
+
+    match busy
+        .iter()
+        .find(|slot| slot.start < end && start < slot.end)
+    {
+        Some(slot) => Err(SlotConflict::Overlap {
+            booking_id: slot.booking_id,
+            start: slot.start,
+            end: slot.end,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Bookable slots of length `duration` between `from` and `to`.
+///
+/// Slots start at each opening time and follow each other back to back; when
+/// a candidate runs into a busy slot the next candidate starts where that
+/// booking ends.
+pub fn open_slots(
+    calendar: &ProfileCalendar,
+    busy: &[BusySlot],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    duration: Duration,
+) -> Vec<TimeSlot> {
+    let mut slots = Vec::new();
+    if duration <= Duration::zero() || to <= from {
+        return slots;
+    }
+
+    let mut date = calendar.local(from).date();
+    while date <= calendar.local(to).date() {
+        let intervals = calendar.intervals_on(date);
+        for interval in intervals {
+            let (mut start, closing) = match (
+                calendar.instant(date.and_time(interval.opening_time)),
+                calendar.instant(date.and_time(interval.closing_time)),
+            ) {
+                (Some(start), Some(closing)) => (start, closing.min(to)),
+                _ => continue,
+            };
+
+            while start + duration <= closing {
+                let end = start + duration;
+                let blocking_end = busy
+                    .iter()
+                    .filter(|slot| slot.start < end && start < slot.end)
+                    .map(|slot| slot.end)
+                    .max();
+
+                match blocking_end {
+                    Some(blocking_end) => start = blocking_end,
+                    None => {
+                        if start >= from {
+                            slots.push(TimeSlot { start, end });
+                        }
+                        start = end;
+                    }
+                }
+            }
+        }
+
+        date = match date.succ_opt() {
+            Some(next) => next,
+            None => break,
+        };
+    }
+
+    slots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn hours(day_of_week: i32, opening: (u32, u32), closing: (u32, u32)) -> BusinessHours {
+        BusinessHours {
+            id: 0,
+            professional_profile_id: 1,
+            day_of_week,
+            opening_time: NaiveTime::from_hms_opt(opening.0, opening.1, 0),
+            closing_time: NaiveTime::from_hms_opt(closing.0, closing.1, 0),
+            is_available: true,
+        }
+    }
+
+    fn calendar(hours: Vec<BusinessHours>, holidays: Vec<NaiveDate>) -> ProfileCalendar {
+        ProfileCalendar {
+            timezone: Tz::UTC,
+            hours,
+            exceptions: Vec::new(),
+            holidays,
+        }
+    }
+
+    fn exception(
+        start_day: u32,
+        end_day: u32,
+        hours: Option<((u32, u32), (u32, u32))>,
+    ) -> BusinessHourException {
+        BusinessHourException {
+            id: 0,
+            professional_profile_id: 1,
+            start_date: at(start_day, 0, 0).date_naive(),
+            end_date: at(end_day, 0, 0).date_naive(),
+            opening_time: hours
+                .and_then(|(opening, _)| NaiveTime::from_hms_opt(opening.0, opening.1, 0)),
+            closing_time: hours
+                .and_then(|(_, closing)| NaiveTime::from_hms_opt(closing.0, closing.1, 0)),
+            reason: None,
+        }
+    }
+
+    fn weekly(day_of_week: i32, opening: (u32, u32), closing: (u32, u32)) -> WeeklyHoursDTO {
+        WeeklyHoursDTO {
+            day_of_week,
+            opening_time: NaiveTime::from_hms_opt(opening.0, opening.1, 0).unwrap(),
+            closing_time: NaiveTime::from_hms_opt(closing.0, closing.1, 0).unwrap(),
+        }
+    }
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        // October 2026: the 5th is a Monday, the 3rd (German Unity Day) a Saturday.
+        Utc.with_ymd_and_hms(2026, 10, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_slot_within_business_hours_is_accepted() {
+        let calendar = calendar(vec![hours(1, (9, 0), (17, 0))], Vec::new());
+
+        assert!(check_slot(&calendar, at(5, 10, 0), at(5, 11, 0), &[]).is_ok());
+    }
+
+    #[test]
+    fn test_slot_after_closing_is_rejected() {
+        let calendar = calendar(vec![hours(1, (9, 0), (17, 0))], Vec::new());
+
+        assert!(matches!(
+            check_slot(&calendar, at(5, 16, 30), at(5, 17, 30), &[]),
+            Err(SlotConflict::OutsideBusinessHours { .. })
+        ));
+    }
+
+    #[test]
+    fn test_overlapping_booking_is_reported() {
+        let calendar = calendar(vec![hours(1, (9, 0), (17, 0))], Vec::new());
+        let busy = [BusySlot {
+            booking_id: 42,
+            start: at(5, 10, 30),
+            end: at(5, 11, 30),
+        }];
+
+        match check_slot(&calendar, at(5, 10, 0), at(5, 11, 0), &busy) {
+            Err(SlotConflict::Overlap { booking_id, .. }) => assert_eq!(booking_id, 42),
+            other => panic!("Expected an overlap, got {:?}", other),
+        }
+        assert!(check_slot(&calendar, at(5, 11, 30), at(5, 12, 0), &busy).is_ok());
+    }
+
+    #[test]
+    fn test_open_slots_skip_busy_bookings() {
+        let calendar = calendar(vec![hours(1, (9, 0), (12, 0))], Vec::new());
+        let busy = [BusySlot {
+            booking_id: 7,
+            start: at(5, 10, 0),
+            end: at(5, 10, 30),
+        }];
+
+        let slots = open_slots(
+            &calendar,
+            &busy,
+            at(5, 0, 0),
+            at(6, 0, 0),
+            Duration::hours(1),
+        );
+
+        let starts: Vec<_> = slots.iter().map(|slot| slot.start).collect();
+        assert_eq!(starts, vec![at(5, 9, 0), at(5, 10, 30)]);
+        assert!(slots
+            .iter()
+            .all(|slot| slot.end - slot.start == Duration::hours(1)));
+    }
+
+    #[test]
+    fn test_open_slots_respect_range_and_closed_days() {
+        let calendar = calendar(
+            vec![hours(1, (9, 0), (12, 0)), hours(2, (9, 0), (10, 0))],
+            Vec::new(),
+        );
+
+        // Sunday the 4th has no hours; slots before `from` on Monday are dropped.
+        let slots = open_slots(
+            &calendar,
+            &[],
+            at(4, 0, 0).max(at(5, 10, 0)),
+            at(6, 9, 30),
+            Duration::minutes(30),
+        );
+
+        let starts: Vec<_> = slots.iter().map(|slot| slot.start).collect();
+        assert_eq!(
+            starts,
+            vec![
+                at(5, 10, 0),
+                at(5, 10, 30),
+                at(5, 11, 0),
+                at(5, 11, 30),
+                at(6, 9, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_open_slots_use_holiday_hours() {
+        let monday = at(5, 0, 0).date_naive();
+        let calendar = calendar(
+            vec![
+                hours(1, (9, 0), (17, 0)),
+                hours(DayOfWeek::Holiday.to_i32(), (10, 0), (11, 0)),
+            ],
+            vec![monday],
+        );
+
+        let slots = open_slots(&calendar, &[], at(5, 0, 0), at(6, 0, 0), Duration::hours(1));
+
+        assert_eq!(
+            slots,
+            vec![TimeSlot {
+                start: at(5, 10, 0),
+                end: at(5, 11, 0)
+            }]
+        );
+    }
+
+    #[test]
+    fn test_holiday_row_replaces_weekday_hours() {
+        let date = at(3, 0, 0).date_naive();
+        let calendar = calendar(
+            vec![
+                hours(6, (9, 0), (17, 0)),
+                hours(DayOfWeek::Holiday.to_i32(), (10, 0), (12, 0)),
+            ],
+            vec![date],
+        );
+
+        assert_eq!(calendar.intervals_on(date).len(), 1);
+        assert!(check_slot(&calendar, at(3, 13, 0), at(3, 14, 0), &[]).is_err());
+        assert!(check_slot(&calendar, at(3, 10, 0), at(3, 11, 0), &[]).is_ok());
+    }
+
+    #[test]
+    fn test_exception_hours_replace_weekday_and_holiday_hours() {
+        let calendar = ProfileCalendar {
+            timezone: Tz::UTC,
+            hours: vec![
+                hours(1, (9, 0), (17, 0)),
+                hours(DayOfWeek::Holiday.to_i32(), (10, 0), (11, 0)),
+            ],
+            exceptions: vec![
+                exception(5, 5, Some(((14, 0), (16, 0)))),
+                exception(5, 5, Some(((8, 0), (10, 0)))),
+            ],
+            holidays: vec![at(5, 0, 0).date_naive()],
+        };
+
+        let starts: Vec<_> = calendar
+            .intervals_on(at(5, 0, 0).date_naive())
+            .iter()
+            .map(|interval| interval.opening_time)
+            .collect();
+        assert_eq!(
+            starts,
+            vec![
+                NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+                NaiveTime::from_hms_opt(14, 0, 0).unwrap()
+            ]
+        );
+        // The exception ends on the 5th, so the following Monday is regular.
+        assert_eq!(calendar.intervals_on(at(12, 0, 0).date_naive()).len(), 1);
+    }
+
+    #[test]
+    fn test_closure_exception_closes_every_covered_day() {
+        let calendar = ProfileCalendar {
+            timezone: Tz::UTC,
+            hours: vec![hours(1, (9, 0), (17, 0)), hours(2, (9, 0), (17, 0))],
+            exceptions: vec![
+                exception(5, 6, None),
+                exception(6, 6, Some(((10, 0), (12, 0)))),
+            ],
+            holidays: Vec::new(),
+        };
+
+        let slots = open_slots(&calendar, &[], at(5, 0, 0), at(8, 0, 0), Duration::hours(1));
+        assert!(slots.is_empty());
+    }
+
+    #[test]
+    fn test_interval_at_prefers_the_open_interval() {
+        let hours = [hours(1, (9, 0), (12, 0)), hours(1, (14, 0), (18, 0))];
+        let intervals = opening_intervals(&hours, &[], at(5, 0, 0).date_naive(), false);
+
+        let at_time = |hour| interval_at(&intervals, NaiveTime::from_hms_opt(hour, 0, 0).unwrap());
+        assert_eq!(at_time(15), Some(intervals[1]));
+        assert_eq!(at_time(13), Some(intervals[0]));
+        assert_eq!(interval_at(&[], NaiveTime::MIN), None);
+    }
+
+    #[test]
+    fn test_weekly_hours_validation() {
+        assert!(validate_weekly_hours(&[
+            weekly(1, (9, 0), (12, 0)),
+            weekly(1, (12, 0), (17, 0)),
+            weekly(DayOfWeek::Holiday.to_i32(), (10, 0), (12, 0)),
+        ])
+        .is_ok());
+        assert!(
+            validate_weekly_hours(&[weekly(1, (9, 0), (12, 0)), weekly(1, (11, 0), (13, 0))])
+                .is_err()
+        );
+        assert!(validate_weekly_hours(&[weekly(8, (9, 0), (12, 0))]).is_err());
+        assert!(validate_weekly_hours(&[weekly(2, (12, 0), (9, 0))]).is_err());
+    }
+
+    #[test]
+    fn test_exception_validation() {
+        let input = |start_day, end_day, opening: Option<u32>, closing: Option<u32>| {
+            BusinessHourExceptionInputDTO {
+                start_date: at(start_day, 0, 0).date_naive(),
+                end_date: at(end_day, 0, 0).date_naive(),
+                opening_time: opening.and_then(|hour| NaiveTime::from_hms_opt(hour, 0, 0)),
+                closing_time: closing.and_then(|hour| NaiveTime::from_hms_opt(hour, 0, 0)),
+                reason: None,
+            }
+        };
+
+        assert!(validate_exception(&input(5, 9, None, None)).is_ok());
+        assert!(validate_exception(&input(5, 5, Some(9), Some(12))).is_ok());
+        assert!(validate_exception(&input(9, 5, None, None)).is_err());
+        assert!(validate_exception(&input(5, 5, Some(9), None)).is_err());
+        assert!(validate_exception(&input(5, 5, Some(12), Some(9))).is_err());
+    }
+
+    #[test]
+    fn test_hours_are_evaluated_in_the_profile_timezone() {
+        // Berlin is two hours ahead of UTC in early October.
+        let calendar = ProfileCalendar {
+            timezone: chrono_tz::Europe::Berlin,
+            ..calendar(
+                vec![hours(1, (0, 0), (2, 0)), hours(1, (9, 0), (17, 0))],
+                Vec::new(),
+            )
+        };
+
+        // 22:30 UTC on Sunday the 4th is already Monday in Berlin.
+        assert!(calendar.is_open_at(at(4, 22, 30)));
+        assert!(!calendar.is_open_at(at(5, 0, 30)));
+        assert!(calendar.is_open_at(at(5, 7, 0)));
+        assert!(!calendar.is_open_at(at(5, 15, 0)));
+
+        assert!(check_slot(&calendar, at(5, 7, 0), at(5, 8, 0), &[]).is_ok());
+        assert!(check_slot(&calendar, at(5, 14, 30), at(5, 15, 30), &[]).is_err());
+
+        let slots = open_slots(&calendar, &[], at(5, 6, 0), at(5, 9, 0), Duration::hours(1));
+        let starts: Vec<_> = slots.iter().map(|slot| slot.start).collect();
+        assert_eq!(starts, vec![at(5, 7, 0), at(5, 8, 0)]);
+    }
+
+    #[test]
+    fn test_timezone_names_are_parsed() {
+        assert_eq!(
+            parse_timezone("Europe/Berlin"),
+            Ok(chrono_tz::Europe::Berlin)
+        );
+        assert!(parse_timezone("Mars/Olympus").is_err());
+    }
+}
@@ -14,3 +14,13 @@ pub struct BusinessHours {
     pub closing_time: Option<NaiveTime>,
     pub is_available: bool,
 }
+
+#[derive(Insertable)]
+#[diesel(table_name = business_hours)]
+pub struct NewBusinessHours {
+    pub professional_profile_id: i32,
+    pub day_of_week: i32,
+    pub opening_time: Option<NaiveTime>,
+    pub closing_time: Option<NaiveTime>,
+    pub is_available: bool,
+}
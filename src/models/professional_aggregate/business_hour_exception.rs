@@ -0,0 +1,44 @@
+use crate::models::professional_aggregate::professional_profile::ProfessionalProfile;
+use crate::schema::schema::business_hour_exceptions;
+use chrono::{NaiveDate, NaiveTime};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Hours that replace a profile's weekly schedule from `start_date` through
+/// `end_date`, such as a vacation or a one-off late opening. Without times
+/// the profile is closed for the whole range.
+#[derive(
+    Queryable, Identifiable, Selectable, Associations, Serialize, Deserialize, Debug, Clone,
+)]
+#[diesel(belongs_to(ProfessionalProfile))]
+#[diesel(table_name = business_hour_exceptions)]
+pub struct BusinessHourException {
+    pub id: i32,
+    pub professional_profile_id: i32,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub opening_time: Option<NaiveTime>,
+    pub closing_time: Option<NaiveTime>,
+    pub reason: Option<String>,
+}
+
+impl BusinessHourException {
+    pub fn covers(&self, date: NaiveDate) -> bool {
+        self.start_date <= date && date <= self.end_date
+    }
+
+    pub fn is_closure(&self) -> bool {
+        self.opening_time.is_none() || self.closing_time.is_none()
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = business_hour_exceptions)]
+pub struct NewBusinessHourException {
+    pub professional_profile_id: i32,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub opening_time: Option<NaiveTime>,
+    pub closing_time: Option<NaiveTime>,
+    pub reason: Option<String>,
+}
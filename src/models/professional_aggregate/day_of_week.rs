@@ -1,4 +1,6 @@
-#[derive(Debug, PartialEq, Eq)]
+use chrono::{Datelike, NaiveDate};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DayOfWeek {
     Sunday = 0,
     Monday = 1,
@@ -25,7 +27,13 @@ impl DayOfWeek {
         }
     }
 
-    pub fn to_i32(&self) -> i32 {
-        *self as i32
+    pub fn from_date(date: NaiveDate) -> DayOfWeek {
+        // Sunday is 0, Saturday is 6, so this never falls through.
+        DayOfWeek::from_i32(date.weekday().num_days_from_sunday() as i32)
+            .unwrap_or(DayOfWeek::Sunday)
+    }
+
+    pub fn to_i32(self) -> i32 {
+        self as i32
     }
 }
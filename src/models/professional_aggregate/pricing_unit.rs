@@ -0,0 +1,59 @@
+use crate::errors::professional_profile_errors::ProfessionalProfileError;
+use serde::{Deserialize, Serialize};
+
+/// What the price of a service offering is charged per.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PricingUnit {
+    /// One price for the whole job.
+    #[default]
+    Fixed = 0,
+    Hourly = 1,
+    PerSquareMeter = 2,
+}
+
+impl TryFrom<i32> for PricingUnit {
+    type Error = ProfessionalProfileError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(PricingUnit::Fixed),
+            1 => Ok(PricingUnit::Hourly),
+            2 => Ok(PricingUnit::PerSquareMeter),
+            _ => Err(ProfessionalProfileError::InvalidOffering(format!(
+                "unknown pricing unit {}",
+                value
+            ))),
+        }
+    }
+}
+
+impl From<PricingUnit> for i32 {
+    fn from(unit: PricingUnit) -> Self {
+        unit as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pricing_units_round_trip_through_their_column_value() {
+        for unit in [
+            PricingUnit::Fixed,
+            PricingUnit::Hourly,
+            PricingUnit::PerSquareMeter,
+        ] {
+            assert_eq!(PricingUnit::try_from(i32::from(unit)).unwrap(), unit);
+        }
+        assert!(matches!(
+            PricingUnit::try_from(3),
+            Err(ProfessionalProfileError::InvalidOffering(_))
+        ));
+        assert_eq!(
+            serde_json::to_string(&PricingUnit::PerSquareMeter).unwrap(),
+            "\"per_square_meter\""
+        );
+    }
+}
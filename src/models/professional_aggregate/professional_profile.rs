@@ -1,7 +1,8 @@
 use crate::schema::schema::professional_profiles;
 use diesel::prelude::*;
+use serde::Serialize;
 
-#[derive(Queryable, Identifiable, Selectable)]
+#[derive(Queryable, Identifiable, Selectable, Serialize)]
 #[diesel(table_name = professional_profiles)]
 pub struct ProfessionalProfile {
     pub id: i32,
@@ -14,12 +15,47 @@ pub struct ProfessionalProfile {
     pub average_rating: Option<f64>,
     pub remote_available: bool,
     pub professional_profile_uid: String,
+    pub review_count: i32,
+    pub timezone: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = professional_profiles)]
+pub struct NewProfessionalProfile {
+    pub professional_id: i32,
+    pub professional_name: String,
+    pub category_id: i32,
+    pub credentials: Option<String>,
+    pub delivery_enabled: bool,
+    pub image_url: Option<String>,
+    pub remote_available: bool,
+    pub professional_profile_uid: String,
+    pub timezone: String,
+}
+
+/// Fields a professional edits on their own profile. `professional_name` is
+/// copied from the professional on every edit.
+#[derive(AsChangeset)]
+#[diesel(table_name = professional_profiles)]
+#[diesel(treat_none_as_null = true)]
+pub struct ProfessionalProfileChangeset {
+    pub professional_name: String,
+    pub credentials: Option<String>,
+    pub delivery_enabled: bool,
+    pub image_url: Option<String>,
+    pub remote_available: bool,
+    /// Keeps the stored zone when `None`.
+    #[diesel(treat_none_as_null = false)]
+    pub timezone: Option<String>,
 }
 
 impl ProfessionalProfile {
+    /// Recomputes `average_rating` and `review_count` from all of the
+    /// profile's ratings.
     pub fn update_average_rating(&mut self, ratings: &[f64]) {
         let sum_ratings: f64 = ratings.iter().sum();
         let count_ratings = ratings.len() as f64;
+        self.review_count = ratings.len() as i32;
         self.average_rating = if count_ratings > 0.0 {
             Some(sum_ratings / count_ratings)
         } else {
@@ -0,0 +1,108 @@
+use crate::models::dtos::professional_profiles_dto::ProfessionalProfileDTO;
+use serde::{Deserialize, Serialize};
+
+/// Weights of the best match score. Rating and review count are normalised
+/// to 0..1, as is proximity relative to the search radius.
+pub const BEST_MATCH_RATING_WEIGHT: f64 = 0.5;
+pub const BEST_MATCH_REVIEWS_WEIGHT: f64 = 0.2;
+pub const BEST_MATCH_DISTANCE_WEIGHT: f64 = 0.3;
+/// Review count at which the review component of the score is maxed out.
+pub const BEST_MATCH_REVIEW_SATURATION: f64 = 50.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchSort {
+    #[default]
+    BestMatch,
+    Distance,
+    Rating,
+    ReviewCount,
+    Price,
+    /// Full-text rank, only available when searching by text.
+    Relevance,
+}
+
+impl SearchSort {
+    /// SQL expression over a search result row that the results are ordered by.
+    pub fn key_sql(&self) -> &'static str {
+        match self {
+            SearchSort::BestMatch => "ranked.match_score",
+            SearchSort::Distance => "ranked.distance_km",
+            SearchSort::Rating => "COALESCE(ranked.average_rating, 0)",
+            SearchSort::ReviewCount => "ranked.review_count::float8",
+            SearchSort::Price => "ranked.price",
+            SearchSort::Relevance => "ranked.relevance",
+        }
+    }
+
+    /// Distance and price sort cheapest first, everything else best first.
+    pub fn is_ascending(&self) -> bool {
+        matches!(self, SearchSort::Distance | SearchSort::Price)
+    }
+
+    /// Value of `key_sql` for a row that was already loaded.
+    pub fn key_of(&self, profile: &ProfessionalProfileDTO) -> f64 {
+        match self {
+            SearchSort::BestMatch => profile.match_score,
+            SearchSort::Distance => profile.distance_km,
+            SearchSort::Rating => profile.average_rating.unwrap_or(0.0),
+            SearchSort::ReviewCount => profile.review_count as f64,
+            SearchSort::Price => profile.price,
+            SearchSort::Relevance => profile.relevance,
+        }
+    }
+}
+
+/// Position after the last profile of a search page: the sort key of that
+/// profile and its id, which breaks ties.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchCursor {
+    pub key: f64,
+    pub id: i32,
+}
+
+impl SearchCursor {
+    pub fn after(sort: SearchSort, profile: &ProfessionalProfileDTO) -> Self {
+        SearchCursor {
+            key: sort.key_of(profile),
+            id: profile.id,
+        }
+    }
+
+    /// Serialises the cursor as `<key>_<id>`.
+    pub fn encode(&self) -> String {
+        format!("{}_{}", self.key, self.id)
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid cursor: {}", cursor);
+        let (key, id) = cursor.rsplit_once('_').ok_or_else(invalid)?;
+        let id = id.parse::<i32>().map_err(|_| invalid())?;
+        let key = key.parse::<f64>().map_err(|_| invalid())?;
+        if !key.is_finite() {
+            return Err(invalid());
+        }
+        Ok(SearchCursor { key, id })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trips() {
+        let cursor = SearchCursor {
+            key: 1.234_567_890_123,
+            id: 42,
+        };
+        assert_eq!(SearchCursor::decode(&cursor.encode()).unwrap(), cursor);
+    }
+
+    #[test]
+    fn test_malformed_cursor_is_rejected() {
+        for cursor in ["", "abc", "1.5_", "_3", "inf_3", "NaN_3"] {
+            assert!(SearchCursor::decode(cursor).is_err());
+        }
+    }
+}
@@ -4,7 +4,6 @@ use crate::models::professional_aggregate::professional_profile::ProfessionalPro
 
 #[derive(Queryable, Identifiable, Selectable, Associations)]
 #[diesel(belongs_to(ProfessionalProfile))]
-#[diesel(primary_key(professional_profile_id))]
 #[diesel(table_name = service_offerings)]
 pub struct ServiceOffering {
     pub id: i32,
@@ -12,4 +11,28 @@ pub struct ServiceOffering {
     pub subcategory_id: i32,
     pub price: f64, // Assuming `decimal` maps to `f64`
     pub subcategory_name: String,
+    pub pricing_unit: i32,
+    pub estimated_duration_minutes: Option<i32>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = service_offerings)]
+pub struct NewServiceOffering {
+    pub professional_profile_id: i32,
+    pub subcategory_id: i32,
+    pub price: f64,
+    pub subcategory_name: String,
+    pub pricing_unit: i32,
+    pub estimated_duration_minutes: Option<i32>,
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = service_offerings)]
+#[diesel(treat_none_as_null = true)]
+pub struct ServiceOfferingChangeset {
+    pub subcategory_id: i32,
+    pub price: f64,
+    pub subcategory_name: String,
+    pub pricing_unit: i32,
+    pub estimated_duration_minutes: Option<i32>,
 }
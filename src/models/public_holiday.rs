@@ -0,0 +1,23 @@
+use crate::schema::schema::public_holidays;
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A day on which profiles use their holiday hours. Holidays without a
+/// state apply everywhere, the others only to addresses in that state.
+#[derive(Queryable, Selectable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = public_holidays)]
+pub struct PublicHoliday {
+    pub id: i32,
+    pub holiday_date: NaiveDate,
+    pub state: Option<String>,
+    pub name: String,
+}
+
+#[derive(Insertable, Deserialize, Debug)]
+#[diesel(table_name = public_holidays)]
+pub struct NewPublicHoliday {
+    pub holiday_date: NaiveDate,
+    pub state: Option<String>,
+    pub name: String,
+}
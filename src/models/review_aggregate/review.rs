@@ -5,7 +5,6 @@ use crate::models::professional_aggregate::professional_profile::ProfessionalPro
 
 #[derive(Queryable, Identifiable, Selectable, Associations)]
 #[diesel(belongs_to(ProfessionalProfile))]
-#[diesel(primary_key(professional_profile_id))]
 #[diesel(table_name = review)]
 pub struct Review {
     pub id: i32,
@@ -15,5 +14,19 @@ pub struct Review {
     pub message: String,
     pub rate: f64,
     pub published_at: DateTime<Utc>,
+    pub booking_id: Option<i32>,
+    pub is_hidden: bool,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = review)]
+pub struct NewReview {
+    pub user_id: i32,
+    pub user_name: String,
+    pub professional_profile_id: i32,
+    pub message: String,
+    pub rate: f64,
+    pub published_at: DateTime<Utc>,
+    pub booking_id: Option<i32>,
 }
 
@@ -0,0 +1,116 @@
+use crate::errors::review_errors::ReviewError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewSort {
+    #[default]
+    Newest,
+    Highest,
+    Lowest,
+}
+
+/// Position after the last review of a page. Newest-first pages are keyed by
+/// publication time, rating-sorted pages by rate; `id` breaks ties.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReviewCursor {
+    Published {
+        published_at: DateTime<Utc>,
+        id: i32,
+    },
+    Rate {
+        rate: f64,
+        id: i32,
+    },
+}
+
+impl ReviewCursor {
+    pub fn after(sort: ReviewSort, rate: f64, published_at: DateTime<Utc>, id: i32) -> Self {
+        match sort {
+            ReviewSort::Newest => ReviewCursor::Published { published_at, id },
+            ReviewSort::Highest | ReviewSort::Lowest => ReviewCursor::Rate { rate, id },
+        }
+    }
+
+    /// Serialises the cursor as `<key>_<id>`, where the key is the
+    /// publication time in microseconds or the rate.
+    pub fn encode(&self) -> String {
+        match self {
+            ReviewCursor::Published { published_at, id } => {
+                format!("{}_{}", published_at.timestamp_micros(), id)
+            }
+            ReviewCursor::Rate { rate, id } => format!("{}_{}", rate, id),
+        }
+    }
+
+    pub fn decode(sort: ReviewSort, cursor: &str) -> Result<Self, ReviewError> {
+        let invalid = || ReviewError::InvalidQuery(format!("Invalid cursor: {}", cursor));
+        let (key, id) = cursor.rsplit_once('_').ok_or_else(invalid)?;
+        let id = id.parse::<i32>().map_err(|_| invalid())?;
+
+        match sort {
+            ReviewSort::Newest => {
+                let micros = key.parse::<i64>().map_err(|_| invalid())?;
+                let published_at = DateTime::from_timestamp_micros(micros).ok_or_else(invalid)?;
+                Ok(ReviewCursor::Published { published_at, id })
+            }
+            ReviewSort::Highest | ReviewSort::Lowest => {
+                let rate = key.parse::<f64>().map_err(|_| invalid())?;
+                if !rate.is_finite() {
+                    return Err(invalid());
+                }
+                Ok(ReviewCursor::Rate { rate, id })
+            }
+        }
+    }
+}
+
+/// Review counts per star, index 0 holding one-star reviews. Fractional rates
+/// are rounded down to their star.
+pub fn rating_histogram(rate_counts: &[(f64, i64)]) -> [i64; 5] {
+    let mut histogram = [0; 5];
+    for &(rate, count) in rate_counts {
+        let star = (rate.floor() as usize).clamp(1, 5);
+        histogram[star - 1] += count;
+    }
+    histogram
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_cursor_round_trips() {
+        let published_at = Utc.with_ymd_and_hms(2026, 10, 5, 9, 30, 0).unwrap();
+        let newest = ReviewCursor::after(ReviewSort::Newest, 4.0, published_at, 17);
+        assert_eq!(
+            ReviewCursor::decode(ReviewSort::Newest, &newest.encode()).unwrap(),
+            newest
+        );
+
+        let highest = ReviewCursor::after(ReviewSort::Highest, 4.5, published_at, 17);
+        assert_eq!(
+            ReviewCursor::decode(ReviewSort::Highest, &highest.encode()).unwrap(),
+            highest
+        );
+    }
+
+    #[test]
+    fn test_malformed_cursor_is_rejected() {
+        for cursor in ["", "abc", "12_", "_3", "NaN_3"] {
+            assert!(matches!(
+                ReviewCursor::decode(ReviewSort::Lowest, cursor),
+                Err(ReviewError::InvalidQuery(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_histogram_buckets_by_star() {
+        let histogram = rating_histogram(&[(5.0, 3), (4.5, 2), (4.0, 1), (1.0, 4)]);
+        assert_eq!(histogram, [4, 0, 0, 3, 3]);
+    }
+}
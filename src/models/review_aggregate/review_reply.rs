@@ -0,0 +1,25 @@
+use crate::schema::schema::review_replies;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A professional's public answer to a review of one of their profiles.
+#[derive(Queryable, Selectable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = review_replies)]
+pub struct ReviewReply {
+    pub id: i32,
+    pub review_id: i32,
+    pub professional_profile_id: i32,
+    pub message: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = review_replies)]
+pub struct NewReviewReply {
+    pub review_id: i32,
+    pub professional_profile_id: i32,
+    pub message: String,
+    pub created_at: DateTime<Utc>,
+}
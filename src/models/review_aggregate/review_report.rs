@@ -0,0 +1,43 @@
+use crate::schema::schema::review_reports;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReportStatus {
+    /// Waiting in the moderation queue.
+    Pending = 0,
+    /// A moderator agreed and removed the review.
+    Upheld = 1,
+    /// A moderator kept the review.
+    Dismissed = 2,
+}
+
+impl From<ReportStatus> for i32 {
+    fn from(status: ReportStatus) -> Self {
+        status as i32
+    }
+}
+
+#[derive(Queryable, Selectable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = review_reports)]
+pub struct ReviewReport {
+    pub id: i32,
+    pub review_id: i32,
+    pub reporter_uid: String,
+    pub reason: String,
+    pub status: i32,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub resolved_by_uid: Option<String>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = review_reports)]
+pub struct NewReviewReport {
+    pub review_id: i32,
+    pub reporter_uid: String,
+    pub reason: String,
+    pub status: i32,
+    pub created_at: DateTime<Utc>,
+}
@@ -16,6 +16,7 @@ pub struct Task {
     pub scheduled_date: Option<chrono::NaiveDate>,
     pub scheduled_time: Option<chrono::NaiveTime>,
     pub category_id: i32,
+    pub status: i32,
 }
 
 #[derive(Insertable, Serialize, Deserialize)]
@@ -33,3 +34,18 @@ pub struct NewTask {
     pub scheduled_time: Option<chrono::NaiveTime>,
     pub category_id: i32,
 }
+
+/// Fields a customer may change on a posted task. `None` clears the column.
+#[derive(AsChangeset)]
+#[diesel(table_name = task, treat_none_as_null = true)]
+pub struct TaskChangeset {
+    pub description: Option<String>,
+    pub address_id: Option<i32>,
+    pub title: String,
+    pub min_price: Option<f64>,
+    pub max_price: Option<f64>,
+    pub is_flexible_timing: bool,
+    pub scheduled_date: Option<chrono::NaiveDate>,
+    pub scheduled_time: Option<chrono::NaiveTime>,
+    pub category_id: i32,
+}
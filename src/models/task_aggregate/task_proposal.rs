@@ -0,0 +1,41 @@
+use crate::schema::schema::task_proposals;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProposalStatus {
+    Pending = 0,
+    Accepted = 1,
+    Rejected = 2,
+}
+
+impl From<ProposalStatus> for i32 {
+    fn from(status: ProposalStatus) -> Self {
+        status as i32
+    }
+}
+
+#[derive(Queryable, Selectable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = task_proposals)]
+pub struct TaskProposal {
+    pub id: i32,
+    pub task_id: i32,
+    pub professional_profile_id: i32,
+    pub price: f64,
+    pub message: Option<String>,
+    pub status: i32,
+    pub booking_id: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = task_proposals)]
+pub struct NewTaskProposal {
+    pub task_id: i32,
+    pub professional_profile_id: i32,
+    pub price: f64,
+    pub message: Option<String>,
+    pub status: i32,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,55 @@
+use crate::errors::task_errors::TaskError;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TaskStatus {
+    /// Accepting proposals.
+    Open = 0,
+    /// A proposal was accepted and turned into a booking.
+    Assigned = 1,
+    /// Withdrawn by the customer.
+    Closed = 2,
+    /// The scheduled date passed while the task was still open.
+    Expired = 3,
+}
+
+impl TryFrom<i32> for TaskStatus {
+    type Error = TaskError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(TaskStatus::Open),
+            1 => Ok(TaskStatus::Assigned),
+            2 => Ok(TaskStatus::Closed),
+            3 => Ok(TaskStatus::Expired),
+            _ => Err(TaskError::InvalidStatus(value)),
+        }
+    }
+}
+
+impl From<TaskStatus> for i32 {
+    fn from(status: TaskStatus) -> Self {
+        status as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn task_statuses_round_trip_through_their_column_value() {
+        for status in [
+            TaskStatus::Open,
+            TaskStatus::Assigned,
+            TaskStatus::Closed,
+            TaskStatus::Expired,
+        ] {
+            assert_eq!(TaskStatus::try_from(i32::from(status)).unwrap(), status);
+        }
+        assert!(matches!(
+            TaskStatus::try_from(4),
+            Err(TaskError::InvalidStatus(4))
+        ));
+    }
+}
@@ -0,0 +1,51 @@
+use crate::errors::role_errors::RoleError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// What a user may do. A user can hold several roles at once, e.g. a
+/// professional who also books services as a customer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Books services and places tasks.
+    Customer = 0,
+    /// Runs professional profiles and answers bookings and tasks.
+    Professional = 1,
+    /// Moderates content and manages categories, holidays and roles.
+    Admin = 2,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Customer => "customer",
+            Role::Professional => "professional",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<i32> for Role {
+    type Error = RoleError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Role::Customer),
+            1 => Ok(Role::Professional),
+            2 => Ok(Role::Admin),
+            _ => Err(RoleError::InvalidRole(value)),
+        }
+    }
+}
+
+impl From<Role> for i32 {
+    fn from(role: Role) -> Self {
+        role as i32
+    }
+}
@@ -0,0 +1,156 @@
+use crate::errors::policy_errors::PolicyError;
+use crate::models::booking_aggregate::booking::Booking;
+use crate::models::booking_aggregate::booking_status::BookingParty;
+use crate::models::chat_aggregate::chat::Chat;
+use crate::schema::schema::{chat, message, professional_profiles, professionals};
+use diesel::prelude::*;
+
+/// The user uid of the professional who owns the profile `profile_uid`.
+pub fn profile_owner(conn: &mut PgConnection, profile_uid: &str) -> QueryResult<Option<String>> {
+    professional_profiles::table
+        .inner_join(professionals::table)
+        .filter(professional_profiles::professional_profile_uid.eq(profile_uid))
+        .select(professionals::user_uid)
+        .first(conn)
+        .optional()
+}
+
+/// Whether `user_uid` is the side named `party_uid`. Chats, bookings and
+/// messages name their sides by user uid or by professional profile uid, and
+/// a profile uid stands for the professional who owns that profile.
+pub fn is_party(conn: &mut PgConnection, user_uid: &str, party_uid: &str) -> QueryResult<bool> {
+    if user_uid == party_uid {
+        return Ok(true);
+    }
+    let owner = profile_owner(conn, party_uid)?;
+    Ok(owner.as_deref() == Some(user_uid))
+}
+
+/// Requires `user_uid` to act as `party_uid`, e.g. for a uid taken from the
+/// request path.
+pub fn authorize_party(
+    conn: &mut PgConnection,
+    user_uid: &str,
+    party_uid: &str,
+) -> Result<(), PolicyError> {
+    if is_party(conn, user_uid, party_uid)? {
+        Ok(())
+    } else {
+        Err(PolicyError::Forbidden)
+    }
+}
+
+/// Requires `user_uid` to be one of the two sides of a conversation.
+pub fn authorize_either_party(
+    conn: &mut PgConnection,
+    user_uid: &str,
+    first_uid: &str,
+    second_uid: &str,
+) -> Result<(), PolicyError> {
+    if is_party(conn, user_uid, first_uid)? || is_party(conn, user_uid, second_uid)? {
+        Ok(())
+    } else {
+        Err(PolicyError::Forbidden)
+    }
+}
+
+/// The chat `chat_id`, if `user_uid` is one of its two sides.
+pub fn authorize_chat(
+    conn: &mut PgConnection,
+    user_uid: &str,
+    chat_id: i32,
+) -> Result<Chat, PolicyError> {
+    let chat = chat::table
+        .find(chat_id)
+        .first::<Chat>(conn)
+        .optional()?
+        .ok_or(PolicyError::NotFound)?;
+
+    authorize_either_party(
+        conn,
+        user_uid,
+        &chat.user_uid,
+        &chat.professional_profile_uid,
+    )?;
+    Ok(chat)
+}
+
+/// Requires `user_uid` to be the receiver of message `message_id`; only the
+/// receiver can mark a message as read.
+pub fn authorize_message_receiver(
+    conn: &mut PgConnection,
+    user_uid: &str,
+    message_id: i32,
+) -> Result<(), PolicyError> {
+    let receiver_uid: String = message::table
+        .find(message_id)
+        .select(message::receiver_uid)
+        .first(conn)
+        .optional()?
+        .ok_or(PolicyError::NotFound)?;
+
+    authorize_party(conn, user_uid, &receiver_uid)
+}
+
+/// Whether `user_uid` is the customer of `booking` or the professional
+/// behind the booked profile.
+pub fn booking_party(
+    conn: &mut PgConnection,
+    booking: &Booking,
+    user_uid: &str,
+) -> QueryResult<Option<BookingParty>> {
+    if booking.customer_uid == user_uid {
+        return Ok(Some(BookingParty::Customer));
+    }
+    let professional_uid = profile_owner(conn, &booking.professional_profile_uid)?;
+    Ok(party_of(
+        &booking.customer_uid,
+        professional_uid.as_deref(),
+        user_uid,
+    ))
+}
+
+/// Whether `user_uid` owns a task created by `owner_uid`.
+pub fn owns_task(owner_uid: &str, user_uid: &str) -> bool {
+    !user_uid.is_empty() && owner_uid == user_uid
+}
+
+fn party_of(
+    customer_uid: &str,
+    professional_uid: Option<&str>,
+    user_uid: &str,
+) -> Option<BookingParty> {
+    if customer_uid == user_uid {
+        Some(BookingParty::Customer)
+    } else if professional_uid == Some(user_uid) {
+        Some(BookingParty::Professional)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn booking_parties_are_the_customer_and_the_profile_owner() {
+        assert!(matches!(
+            party_of("customer", Some("pro"), "customer"),
+            Some(BookingParty::Customer)
+        ));
+        assert!(matches!(
+            party_of("customer", Some("pro"), "pro"),
+            Some(BookingParty::Professional)
+        ));
+        assert!(party_of("customer", Some("pro"), "stranger").is_none());
+        assert!(party_of("customer", None, "stranger").is_none());
+    }
+
+    #[test]
+    fn only_the_creator_owns_a_task() {
+        assert!(owns_task("owner", "owner"));
+        assert!(!owns_task("owner", "someone-else"));
+        assert!(!owns_task("", ""));
+    }
+}
@@ -29,6 +29,38 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    booking_history (id) {
+        id -> Int4,
+        booking_id -> Int4,
+        from_status -> Nullable<Int4>,
+        to_status -> Int4,
+        #[max_length = 255]
+        changed_by_uid -> Varchar,
+        #[max_length = 32]
+        changed_by_role -> Varchar,
+        note -> Nullable<Text>,
+        changed_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    booking_offers (id) {
+        id -> Int4,
+        booking_id -> Int4,
+        version -> Int4,
+        #[max_length = 255]
+        offered_by_uid -> Varchar,
+        #[max_length = 32]
+        offered_by_role -> Varchar,
+        offering_price -> Float8,
+        date_time -> Nullable<Timestamptz>,
+        end_time -> Nullable<Timestamptz>,
+        message -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     booking_status (id) {
         id -> Int4,
@@ -69,6 +101,19 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    business_hour_exceptions (id) {
+        id -> Int4,
+        professional_profile_id -> Int4,
+        start_date -> Date,
+        end_date -> Date,
+        opening_time -> Nullable<Time>,
+        closing_time -> Nullable<Time>,
+        #[max_length = 255]
+        reason -> Nullable<Varchar>,
+    }
+}
+
 diesel::table! {
     categories (id) {
         id -> Int4,
@@ -88,6 +133,14 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    deleted_accounts (user_uid) {
+        #[max_length = 255]
+        user_uid -> Varchar,
+        deleted_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     message (id) {
         id -> Int4,
@@ -125,6 +178,10 @@ diesel::table! {
         professional_name -> Varchar,
         #[max_length = 255]
         professional_profile_uid -> Varchar,
+        review_count -> Int4,
+        #[max_length = 64]
+        timezone -> Varchar,
+        is_active -> Bool,
     }
 }
 
@@ -140,6 +197,17 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    public_holidays (id) {
+        id -> Int4,
+        holiday_date -> Date,
+        #[max_length = 255]
+        state -> Nullable<Varchar>,
+        #[max_length = 255]
+        name -> Varchar,
+    }
+}
+
 diesel::table! {
     review (id) {
         id -> Int4,
@@ -150,6 +218,8 @@ diesel::table! {
         #[max_length = 255]
         user_name -> Varchar,
         published_at -> Timestamptz,
+        booking_id -> Nullable<Int4>,
+        is_hidden -> Bool,
     }
 }
 
@@ -162,6 +232,32 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    review_replies (id) {
+        id -> Int4,
+        review_id -> Int4,
+        professional_profile_id -> Int4,
+        message -> Text,
+        created_at -> Timestamptz,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    review_reports (id) {
+        id -> Int4,
+        review_id -> Int4,
+        #[max_length = 255]
+        reporter_uid -> Varchar,
+        reason -> Text,
+        status -> Int4,
+        created_at -> Timestamptz,
+        resolved_at -> Nullable<Timestamptz>,
+        #[max_length = 255]
+        resolved_by_uid -> Nullable<Varchar>,
+    }
+}
+
 diesel::table! {
     service_offerings (id) {
         id -> Int4,
@@ -170,6 +266,8 @@ diesel::table! {
         price -> Float8,
         #[max_length = 255]
         subcategory_name -> Varchar,
+        pricing_unit -> Int4,
+        estimated_duration_minutes -> Nullable<Int4>,
     }
 }
 
@@ -209,6 +307,7 @@ diesel::table! {
         scheduled_date -> Nullable<Date>,
         scheduled_time -> Nullable<Time>,
         category_id -> Int4,
+        status -> Int4,
     }
 }
 
@@ -220,6 +319,29 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    task_proposals (id) {
+        id -> Int4,
+        task_id -> Int4,
+        professional_profile_id -> Int4,
+        price -> Float8,
+        message -> Nullable<Text>,
+        status -> Int4,
+        booking_id -> Nullable<Int4>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    user_roles (id) {
+        id -> Int4,
+        #[max_length = 255]
+        user_uid -> Varchar,
+        role -> Int4,
+        granted_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     users (id) {
         id -> Int4,
@@ -235,40 +357,59 @@ diesel::table! {
 diesel::joinable!(address_assignments -> addresses (address_id));
 diesel::joinable!(address_assignments -> professional_profiles (professional_profile_id));
 diesel::joinable!(booking_assignments -> bookings (appointment_id));
+diesel::joinable!(booking_history -> bookings (booking_id));
+diesel::joinable!(booking_offers -> bookings (booking_id));
 diesel::joinable!(bookings -> chat (chat_id));
 diesel::joinable!(bookings -> service_offerings (service_offering_id));
+diesel::joinable!(business_hour_exceptions -> professional_profiles (professional_profile_id));
 diesel::joinable!(business_hours -> professional_profiles (professional_profile_id));
 diesel::joinable!(message -> chat (chat_id));
 diesel::joinable!(message_assignments -> message (message_id));
 diesel::joinable!(professional_profiles -> professionals (professional_id));
+diesel::joinable!(review -> bookings (booking_id));
 diesel::joinable!(review -> professional_profiles (professional_profile_id));
 diesel::joinable!(review -> users (user_id));
 diesel::joinable!(review_content_assignments -> review (review_id));
+diesel::joinable!(review_replies -> professional_profiles (professional_profile_id));
+diesel::joinable!(review_replies -> review (review_id));
+diesel::joinable!(review_reports -> review (review_id));
 diesel::joinable!(service_offerings -> professional_profiles (professional_profile_id));
 diesel::joinable!(service_offerings -> subcategories (subcategory_id));
 diesel::joinable!(subcategories -> categories (category_id));
 diesel::joinable!(task -> addresses (address_id));
 diesel::joinable!(task_assignments -> task (task_id));
+diesel::joinable!(task_proposals -> bookings (booking_id));
+diesel::joinable!(task_proposals -> professional_profiles (professional_profile_id));
+diesel::joinable!(task_proposals -> task (task_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     address_assignments,
     addresses,
     booking_assignments,
+    booking_history,
+    booking_offers,
     booking_status,
     bookings,
+    business_hour_exceptions,
     business_hours,
     categories,
     chat,
+    deleted_accounts,
     message,
     message_assignments,
     professional_profiles,
     professionals,
+    public_holidays,
     review,
     review_content_assignments,
+    review_replies,
+    review_reports,
     service_offerings,
     spatial_ref_sys,
     subcategories,
     task,
     task_assignments,
+    task_proposals,
+    user_roles,
     users,
 );
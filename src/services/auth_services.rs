@@ -0,0 +1,2 @@
+pub mod auth_endpoints;
+pub mod auth_service;
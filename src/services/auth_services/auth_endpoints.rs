@@ -0,0 +1,6 @@
+use super::auth_service;
+use actix_web::web;
+
+pub fn auth_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::scope("/auth").route("/token", web::post().to(auth_service::exchange_token)));
+}
@@ -0,0 +1,47 @@
+use crate::errors::auth_errors::AuthError;
+use crate::middleware::authenticated_user::bearer_token;
+use crate::middleware::session_tokens::SessionTokens;
+use crate::models::dtos::auth_dto::SessionTokenDTO;
+use crate::services::identity_provider::IdentityProvider;
+use actix_web::{http::header::AUTHORIZATION, web, HttpRequest, HttpResponse, ResponseError};
+
+/// Exchanges the Firebase ID token in the `Authorization` header for a
+/// backend session token. Only Firebase tokens are accepted here, so a
+/// session cannot be extended without signing in again.
+pub async fn exchange_token(
+    req: HttpRequest,
+    identity: web::Data<dyn IdentityProvider>,
+    sessions: Option<web::Data<SessionTokens>>,
+) -> HttpResponse {
+    match issue_session_token(
+        &req,
+        identity.get_ref(),
+        sessions.as_ref().map(|s| s.get_ref()),
+    )
+    .await
+    {
+        Ok(session) => HttpResponse::Ok().json(session),
+        Err(e) => e.error_response(),
+    }
+}
+
+async fn issue_session_token(
+    req: &HttpRequest,
+    identity: &dyn IdentityProvider,
+    sessions: Option<&SessionTokens>,
+) -> Result<SessionTokenDTO, AuthError> {
+    let sessions = sessions.ok_or(AuthError::SessionTokensDisabled)?;
+    let token = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(bearer_token)
+        .ok_or(AuthError::MissingToken)?;
+    let user_uid = identity.uid_from_token(token).await?;
+
+    Ok(SessionTokenDTO {
+        token: sessions.issue(&user_uid)?,
+        token_type: "Bearer".to_string(),
+        expires_in: sessions.ttl().num_seconds(),
+    })
+}
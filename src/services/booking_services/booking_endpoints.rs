@@ -0,0 +1,51 @@
+use actix_web::web;
+
+use super::booking_service;
+
+pub fn booking_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/bookings")
+            .route("", web::get().to(booking_service::get_my_bookings))
+            .route(
+                "/profile/{profile_id}",
+                web::get().to(booking_service::get_profile_bookings),
+            )
+            .route("/{booking_id}", web::get().to(booking_service::get_booking))
+            .route(
+                "/{booking_id}/accept",
+                web::post().to(booking_service::accept_booking),
+            )
+            .route(
+                "/{booking_id}/reject",
+                web::post().to(booking_service::reject_booking),
+            )
+            .route(
+                "/{booking_id}/counter-offer",
+                web::post().to(booking_service::counter_offer_booking),
+            )
+            .route(
+                "/{booking_id}/start",
+                web::post().to(booking_service::start_booking),
+            )
+            .route(
+                "/{booking_id}/complete",
+                web::post().to(booking_service::complete_booking),
+            )
+            .route(
+                "/{booking_id}/cancel",
+                web::post().to(booking_service::cancel_booking),
+            )
+            .route(
+                "/{booking_id}/flag",
+                web::post().to(booking_service::flag_booking),
+            )
+            .route(
+                "/{booking_id}/offers",
+                web::get().to(booking_service::get_booking_offers),
+            )
+            .route(
+                "/{booking_id}/history",
+                web::get().to(booking_service::get_booking_history),
+            ),
+    );
+}
@@ -1,41 +1,364 @@
-use crate::dal::booking_db;
-use crate::models::dtos::booking_dto::BookingDTO;
-use crate::services::firebase_service::{extract_uid_from_firebase_token, verify_token};
-use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use crate::dal::booking_db::{self, BookingFilter};
+use crate::errors::booking_errors::BookingError;
+use crate::middleware::authenticated_user::{AuthenticatedUser, ProfessionalUser};
+use crate::models::booking_aggregate::booking_status::{BookingAction, BookingStatus};
+use crate::models::dtos::booking_dto::{BookingTransitionDTO, CounterOfferDTO};
+use actix_web::{web, HttpResponse, Responder, ResponseError};
 use diesel::prelude::*;
 use diesel::r2d2::{ConnectionManager, Pool};
+use serde::Deserialize;
 
-pub async fn book_service_handler(
-    req: HttpRequest,
-    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
-    booking_dto: web::Json<BookingDTO>,
-) -> impl Responder {
-    let token = req
-        .headers()
-        .get("Authorization")
-        .and_then(|h| h.to_str().ok())
-        .and_then(|auth_header| auth_header.split_whitespace().nth(1)); // Assuming "Bearer <token>"
-
-    match token {
-        Some(t) => match verify_token(&t).await {
-            Ok(is_valid) if is_valid => match extract_uid_from_firebase_token(&t).await {
-                Ok(user_uid) => {
-                    match booking_db::place_booking(
-                        db_pool.clone(),
-                        user_uid,
-                        booking_dto.into_inner(),
-                    )
-                    .await
-                    {
-                        Ok(booking) => HttpResponse::Ok().json(booking),
-                        Err(_) => HttpResponse::InternalServerError().finish(),
-                    }
-                }
-                Err(_) => HttpResponse::Unauthorized().body("Invalid user"),
-            },
-            Ok(_) => HttpResponse::Unauthorized().body("Invalid token"),
-            Err(_) => HttpResponse::InternalServerError().finish(),
-        },
-        None => HttpResponse::Unauthorized().body("No token"),
+type DbPool = web::Data<Pool<ConnectionManager<PgConnection>>>;
+type TransitionBody = Option<web::Json<BookingTransitionDTO>>;
+
+const DEFAULT_PAGE_SIZE: i64 = 20;
+const MAX_PAGE_SIZE: i64 = 100;
+
+#[derive(Deserialize)]
+pub struct BookingListQuery {
+    /// Comma separated status values, e.g. `1,4`.
+    pub status: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub category_id: Option<i32>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl BookingListQuery {
+    fn into_filter(self) -> Result<BookingFilter, BookingError> {
+        let statuses = match self.status {
+            Some(statuses) => Some(
+                statuses
+                    .split(',')
+                    .map(|s| {
+                        let value = s.trim().parse::<i32>().map_err(|_| {
+                            BookingError::InvalidQuery(format!("Invalid status value: {}", s))
+                        })?;
+                        BookingStatus::try_from(value).map(i32::from)
+                    })
+                    .collect::<Result<Vec<i32>, BookingError>>()?,
+            ),
+            None => None,
+        };
+
+        Ok(BookingFilter {
+            statuses,
+            from: booking_db::parse_timestamp(&self.from)?,
+            to: booking_db::parse_timestamp(&self.to)?,
+            category_id: self.category_id,
+            limit: self
+                .limit
+                .unwrap_or(DEFAULT_PAGE_SIZE)
+                .clamp(1, MAX_PAGE_SIZE),
+            offset: self.offset.unwrap_or(0).max(0),
+        })
+    }
+}
+
+pub async fn get_my_bookings(
+    user: AuthenticatedUser,
+    db_pool: DbPool,
+    query: web::Query<BookingListQuery>,
+) -> impl Responder {
+    let filter = match query.into_inner().into_filter() {
+        Ok(filter) => filter,
+        Err(e) => return e.error_response(),
+    };
+
+    match booking_db::get_bookings_for_customer(db_pool, user.uid, filter).await {
+        Ok(bookings) => HttpResponse::Ok().json(bookings),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn get_profile_bookings(
+    user: ProfessionalUser,
+    db_pool: DbPool,
+    profile_id: web::Path<i32>,
+    query: web::Query<BookingListQuery>,
+) -> impl Responder {
+    let filter = match query.into_inner().into_filter() {
+        Ok(filter) => filter,
+        Err(e) => return e.error_response(),
+    };
+
+    match booking_db::get_bookings_for_profile(db_pool, user.uid, profile_id.into_inner(), filter)
+        .await
+    {
+        Ok(bookings) => HttpResponse::Ok().json(bookings),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn get_booking(
+    user: AuthenticatedUser,
+    db_pool: DbPool,
+    booking_id: web::Path<i32>,
+) -> impl Responder {
+    match booking_db::get_booking_detail(db_pool, booking_id.into_inner(), user.uid).await {
+        Ok(booking) => HttpResponse::Ok().json(booking),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn accept_booking(
+    user: AuthenticatedUser,
+    db_pool: DbPool,
+    booking_id: web::Path<i32>,
+    body: TransitionBody,
+) -> impl Responder {
+    transition(
+        user,
+        db_pool,
+        booking_id.into_inner(),
+        BookingAction::Accept,
+        body,
+    )
+    .await
+}
+
+pub async fn reject_booking(
+    user: AuthenticatedUser,
+    db_pool: DbPool,
+    booking_id: web::Path<i32>,
+    body: TransitionBody,
+) -> impl Responder {
+    transition(
+        user,
+        db_pool,
+        booking_id.into_inner(),
+        BookingAction::Reject,
+        body,
+    )
+    .await
+}
+
+pub async fn counter_offer_booking(
+    user: AuthenticatedUser,
+    db_pool: DbPool,
+    booking_id: web::Path<i32>,
+    offer_dto: web::Json<CounterOfferDTO>,
+) -> impl Responder {
+    match booking_db::submit_counter_offer(
+        db_pool,
+        booking_id.into_inner(),
+        user.uid,
+        user.roles,
+        offer_dto.into_inner(),
+    )
+    .await
+    {
+        Ok(offer) => HttpResponse::Ok().json(offer),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn get_booking_offers(
+    user: AuthenticatedUser,
+    db_pool: DbPool,
+    booking_id: web::Path<i32>,
+) -> impl Responder {
+    match booking_db::get_booking_offers(db_pool, booking_id.into_inner(), user.uid).await {
+        Ok(offers) => HttpResponse::Ok().json(offers),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn start_booking(
+    user: AuthenticatedUser,
+    db_pool: DbPool,
+    booking_id: web::Path<i32>,
+    body: TransitionBody,
+) -> impl Responder {
+    transition(
+        user,
+        db_pool,
+        booking_id.into_inner(),
+        BookingAction::Start,
+        body,
+    )
+    .await
+}
+
+pub async fn complete_booking(
+    user: AuthenticatedUser,
+    db_pool: DbPool,
+    booking_id: web::Path<i32>,
+    body: TransitionBody,
+) -> impl Responder {
+    transition(
+        user,
+        db_pool,
+        booking_id.into_inner(),
+        BookingAction::Complete,
+        body,
+    )
+    .await
+}
+
+pub async fn cancel_booking(
+    user: AuthenticatedUser,
+    db_pool: DbPool,
+    booking_id: web::Path<i32>,
+    body: TransitionBody,
+) -> impl Responder {
+    transition(
+        user,
+        db_pool,
+        booking_id.into_inner(),
+        BookingAction::Cancel,
+        body,
+    )
+    .await
+}
+
+pub async fn flag_booking(
+    user: AuthenticatedUser,
+    db_pool: DbPool,
+    booking_id: web::Path<i32>,
+    body: TransitionBody,
+) -> impl Responder {
+    transition(
+        user,
+        db_pool,
+        booking_id.into_inner(),
+        BookingAction::Flag,
+        body,
+    )
+    .await
+}
+
+pub async fn get_booking_history(
+    user: AuthenticatedUser,
+    db_pool: DbPool,
+    booking_id: web::Path<i32>,
+) -> impl Responder {
+    match booking_db::get_booking_history(db_pool, booking_id.into_inner(), user.uid).await {
+        Ok(history) => HttpResponse::Ok().json(history),
+        Err(e) => e.error_response(),
+    }
+}
+
+async fn transition(
+    user: AuthenticatedUser,
+    db_pool: DbPool,
+    booking_id: i32,
+    action: BookingAction,
+    body: TransitionBody,
+) -> HttpResponse {
+    let note = body.and_then(|body| body.into_inner().note);
+
+    match booking_db::transition_booking(db_pool, booking_id, user.uid, user.roles, action, note)
+        .await
+    {
+        Ok(booking) => HttpResponse::Ok().json(booking),
+        Err(e) => e.error_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{
+        insert_fixture, request_as, status_of, test_app, test_pool, CUSTOMER, PROFESSIONAL,
+        STRANGER,
+    };
+    use actix_web::http::{Method, StatusCode};
+
+    fn query(status: Option<&str>, limit: Option<i64>, offset: Option<i64>) -> BookingListQuery {
+        BookingListQuery {
+            status: status.map(String::from),
+            from: None,
+            to: None,
+            category_id: None,
+            limit,
+            offset,
+        }
+    }
+
+    #[test]
+    fn list_queries_parse_statuses_and_clamp_paging() {
+        let filter = query(Some("1, 4"), Some(500), Some(-3))
+            .into_filter()
+            .unwrap();
+        assert_eq!(filter.statuses, Some(vec![1, 4]));
+        assert_eq!(filter.limit, MAX_PAGE_SIZE);
+        assert_eq!(filter.offset, 0);
+
+        let filter = query(None, None, None).into_filter().unwrap();
+        assert_eq!(filter.statuses, None);
+        assert_eq!(filter.limit, DEFAULT_PAGE_SIZE);
+
+        assert!(matches!(
+            query(Some("accepted"), None, None).into_filter(),
+            Err(BookingError::InvalidQuery(_))
+        ));
+        assert!(matches!(
+            query(Some("42"), None, None).into_filter(),
+            Err(BookingError::InvalidStatus(42))
+        ));
+    }
+
+    #[actix_web::test]
+    async fn booking_endpoints_require_a_party() {
+        let Some(pool) = test_pool() else {
+            return;
+        };
+        let fixture = insert_fixture(&pool);
+        let app = test_app!(pool);
+
+        let booking = format!("/bookings/{}", fixture.booking_id);
+        let offers = format!("/bookings/{}/offers", fixture.booking_id);
+        let history = format!("/bookings/{}/history", fixture.booking_id);
+        let profile_bookings = format!("/bookings/profile/{}", fixture.profile_id);
+
+        for uri in [&booking, &offers, &history] {
+            for (user_uid, expected) in [
+                (CUSTOMER, StatusCode::OK),
+                (PROFESSIONAL, StatusCode::OK),
+                (STRANGER, StatusCode::FORBIDDEN),
+            ] {
+                assert_eq!(
+                    status_of!(app, request_as(Method::GET, uri, user_uid)),
+                    expected,
+                    "GET {} as {}",
+                    uri,
+                    user_uid
+                );
+            }
+        }
+        for (user_uid, expected) in [
+            (PROFESSIONAL, StatusCode::OK),
+            (CUSTOMER, StatusCode::FORBIDDEN),
+            (STRANGER, StatusCode::FORBIDDEN),
+        ] {
+            assert_eq!(
+                status_of!(app, request_as(Method::GET, &profile_bookings, user_uid)),
+                expected,
+                "GET {} as {}",
+                profile_bookings,
+                user_uid
+            );
+        }
+
+        for action in ["accept", "reject", "start", "complete", "cancel", "flag"] {
+            let uri = format!("/bookings/{}/{}", fixture.booking_id, action);
+            assert_eq!(
+                status_of!(app, request_as(Method::POST, &uri, STRANGER)),
+                StatusCode::FORBIDDEN,
+                "POST {}",
+                uri
+            );
+        }
+        let counter_offer = format!("/bookings/{}/counter-offer", fixture.booking_id);
+        let offer = serde_json::json!({ "offering_price": 60.0 });
+        assert_eq!(
+            status_of!(
+                app,
+                request_as(Method::POST, &counter_offer, STRANGER).set_json(offer)
+            ),
+            StatusCode::FORBIDDEN
+        );
     }
 }
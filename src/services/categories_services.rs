@@ -1,2 +1,3 @@
+pub mod category_cache;
 pub mod category_service;
 pub mod category_endpoints;
\ No newline at end of file
@@ -0,0 +1,65 @@
+use crate::dal::category_db;
+use crate::db::Pool;
+use crate::models::dtos::category_dto::CategoryDTO;
+use actix_web::web;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// How long the category catalog is served from memory before it is read
+/// from the database again.
+const CATEGORY_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// In-memory copy of the category catalog, shared by all workers. Categories
+/// change rarely, so a short TTL keeps it fresh enough; the admin endpoints
+/// invalidate it on every change.
+pub struct CategoryCache {
+    ttl: Duration,
+    entry: RwLock<Option<(Instant, Arc<Vec<CategoryDTO>>)>>,
+}
+
+impl Default for CategoryCache {
+    fn default() -> Self {
+        CategoryCache::new(CATEGORY_CACHE_TTL)
+    }
+}
+
+impl CategoryCache {
+    pub fn new(ttl: Duration) -> Self {
+        CategoryCache {
+            ttl,
+            entry: RwLock::new(None),
+        }
+    }
+
+    /// The cached catalog, loading it first when missing or expired.
+    pub async fn get(&self, db_pool: web::Data<Pool>) -> Result<Arc<Vec<CategoryDTO>>, String> {
+        if let Some(catalog) = self.fresh() {
+            return Ok(catalog);
+        }
+
+        let catalog = web::block(move || {
+            let mut conn = db_pool.get().map_err(|e| e.to_string())?;
+            category_db::get_category_catalog(&mut conn).map_err(|e| e.to_string())
+        })
+        .await
+        .map_err(|e| format!("Blocking error: {}", e))??;
+
+        let catalog = Arc::new(catalog);
+        *self.entry.write().unwrap_or_else(|e| e.into_inner()) =
+            Some((Instant::now(), catalog.clone()));
+        Ok(catalog)
+    }
+
+    /// Drops the cached catalog so the next read sees recent changes.
+    pub fn invalidate(&self) {
+        *self.entry.write().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    fn fresh(&self) -> Option<Arc<Vec<CategoryDTO>>> {
+        let entry = self.entry.read().unwrap_or_else(|e| e.into_inner());
+        entry
+            .as_ref()
+            .filter(|(loaded_at, _)| loaded_at.elapsed() < self.ttl)
+            .map(|(_, catalog)| catalog.clone())
+    }
+}
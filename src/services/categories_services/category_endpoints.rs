@@ -2,8 +2,43 @@ use super::category_service;
 use actix_web::web;
 
 pub fn category_routes(cfg: &mut web::ServiceConfig) {
-    cfg.service(web::scope("/category").route(
-        "/subcategories/{category_id}",
-        web::get().to(category_service::get_subcategories),
-    ));
+    cfg.service(
+        web::scope("/category")
+            .route("", web::get().to(category_service::get_categories))
+            .route(
+                "/suggest",
+                web::get().to(category_service::suggest_categories),
+            )
+            .route(
+                "/subcategories/{category_id}",
+                web::get().to(category_service::get_subcategories),
+            ),
+    )
+    .service(
+        web::scope("/admin")
+            .route(
+                "/categories",
+                web::post().to(category_service::create_category),
+            )
+            .route(
+                "/categories/{category_id}",
+                web::put().to(category_service::update_category),
+            )
+            .route(
+                "/categories/{category_id}",
+                web::delete().to(category_service::delete_category),
+            )
+            .route(
+                "/subcategories",
+                web::post().to(category_service::create_subcategory),
+            )
+            .route(
+                "/subcategories/{subcategory_id}",
+                web::put().to(category_service::update_subcategory),
+            )
+            .route(
+                "/subcategories/{subcategory_id}",
+                web::delete().to(category_service::delete_subcategory),
+            ),
+    );
 }
@@ -1,30 +1,62 @@
+use super::category_cache::CategoryCache;
 use crate::dal::category_db;
 use crate::db::Pool;
-use crate::services::firebase_service::verify_token;
-use actix_web::{web, HttpResponse, HttpRequest, Responder, http::header::HeaderValue};
+use crate::middleware::authenticated_user::{AdminUser, AuthenticatedUser};
+use crate::models::category_aggregate::category_suggestions;
+use crate::models::dtos::category_dto::{CategoryInputDTO, SubcategoryInputDTO};
+use actix_web::{web, HttpResponse, Responder, ResponseError};
+use log::error;
+use serde::Deserialize;
 
+const DEFAULT_SUGGESTION_LIMIT: usize = 10;
+const MAX_SUGGESTION_LIMIT: usize = 25;
 
-pub async fn get_subcategories(req: HttpRequest, category_id: web::Path<i32>, db_pool: web::Data<Pool>) -> impl Responder {
+#[derive(Deserialize)]
+pub struct SuggestQuery {
+    q: String,
+    limit: Option<usize>,
+}
 
-    // Verify the token
-    let token = _extract_token_from_auth_header(req.headers().get("Authorization"));
-    match token {
-        Some(t) => {
-            if let Ok(is_valid) = verify_token(&t).await {
-                if !is_valid {
-                    // If the token is not valid, return an unauthorized response
-                    return HttpResponse::Unauthorized().body("Invalid token");
-                }
-            } else {
-                // If token verification failed due to some error
-                return HttpResponse::InternalServerError().finish();
-            }
-        },
-        None => {
-            // If no token is present in the request
-            return HttpResponse::Unauthorized().body("No token");
+/// All categories with their subcategories and offering counts.
+pub async fn get_categories(
+    _user: AuthenticatedUser,
+    db_pool: web::Data<Pool>,
+    cache: web::Data<CategoryCache>,
+) -> impl Responder {
+    match cache.get(db_pool).await {
+        Ok(catalog) => HttpResponse::Ok().json(catalog.as_slice()),
+        Err(e) => {
+            error!("Error occurred: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Type-ahead over category and subcategory names, e.g. `?q=gard`.
+pub async fn suggest_categories(
+    _user: AuthenticatedUser,
+    query: web::Query<SuggestQuery>,
+    db_pool: web::Data<Pool>,
+    cache: web::Data<CategoryCache>,
+) -> impl Responder {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_SUGGESTION_LIMIT)
+        .clamp(1, MAX_SUGGESTION_LIMIT);
+
+    match cache.get(db_pool).await {
+        Ok(catalog) => {
+            HttpResponse::Ok().json(category_suggestions::suggest(&catalog, &query.q, limit))
+        }
+        Err(e) => {
+            error!("Error occurred: {:?}", e);
+            HttpResponse::InternalServerError().finish()
         }
     }
+}
+
+
+pub async fn get_subcategories(_user: AuthenticatedUser, category_id: web::Path<i32>, db_pool: web::Data<Pool>) -> impl Responder {
     let mut conn = db_pool.get().expect("Failed to get DB connection from pool");
     
 
@@ -48,10 +80,106 @@ pub async fn get_subcategories(req: HttpRequest, category_id: web::Path<i32>, db
     }
 }
 
-fn _extract_token_from_auth_header(auth_header: Option<&HeaderValue>) -> Option<String> {
-    // Extract the Bearer token from the Authorization header
-    auth_header?
-        .to_str().ok()?
-        .split_whitespace().nth(1)
-        .map(String::from)
-}
\ No newline at end of file
+pub async fn create_category(
+    _admin: AdminUser,
+    db_pool: web::Data<Pool>,
+    cache: web::Data<CategoryCache>,
+    category_dto: web::Json<CategoryInputDTO>,
+) -> impl Responder {
+    match category_db::create_category(db_pool, category_dto.into_inner()).await {
+        Ok(category) => {
+            cache.invalidate();
+            HttpResponse::Created().json(category)
+        }
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn update_category(
+    _admin: AdminUser,
+    category_id: web::Path<i32>,
+    db_pool: web::Data<Pool>,
+    cache: web::Data<CategoryCache>,
+    category_dto: web::Json<CategoryInputDTO>,
+) -> impl Responder {
+    match category_db::update_category(
+        db_pool,
+        category_id.into_inner(),
+        category_dto.into_inner(),
+    )
+    .await
+    {
+        Ok(category) => {
+            cache.invalidate();
+            HttpResponse::Ok().json(category)
+        }
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn delete_category(
+    _admin: AdminUser,
+    category_id: web::Path<i32>,
+    db_pool: web::Data<Pool>,
+    cache: web::Data<CategoryCache>,
+) -> impl Responder {
+    match category_db::delete_category(db_pool, category_id.into_inner()).await {
+        Ok(()) => {
+            cache.invalidate();
+            HttpResponse::NoContent().finish()
+        }
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn create_subcategory(
+    _admin: AdminUser,
+    db_pool: web::Data<Pool>,
+    cache: web::Data<CategoryCache>,
+    subcategory_dto: web::Json<SubcategoryInputDTO>,
+) -> impl Responder {
+    match category_db::create_subcategory(db_pool, subcategory_dto.into_inner()).await {
+        Ok(subcategory) => {
+            cache.invalidate();
+            HttpResponse::Created().json(subcategory)
+        }
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn update_subcategory(
+    _admin: AdminUser,
+    subcategory_id: web::Path<i32>,
+    db_pool: web::Data<Pool>,
+    cache: web::Data<CategoryCache>,
+    subcategory_dto: web::Json<SubcategoryInputDTO>,
+) -> impl Responder {
+    match category_db::update_subcategory(
+        db_pool,
+        subcategory_id.into_inner(),
+        subcategory_dto.into_inner(),
+    )
+    .await
+    {
+        Ok(subcategory) => {
+            cache.invalidate();
+            HttpResponse::Ok().json(subcategory)
+        }
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn delete_subcategory(
+    _admin: AdminUser,
+    subcategory_id: web::Path<i32>,
+    db_pool: web::Data<Pool>,
+    cache: web::Data<CategoryCache>,
+) -> impl Responder {
+    match category_db::delete_subcategory(db_pool, subcategory_id.into_inner()).await {
+        Ok(()) => {
+            cache.invalidate();
+            HttpResponse::NoContent().finish()
+        }
+        Err(e) => e.error_response(),
+    }
+}
@@ -1,19 +1,18 @@
 use crate::dal::chat_db;
+use crate::handlers::websocket_handler::validate_websocket_auth;
+use crate::middleware::authenticated_user::AuthenticatedUser;
+use crate::middleware::token_verifier::TokenVerifier;
+use crate::policies::ownership;
 use actix::{fut::ActorFutureExt, Actor, ActorContext, AsyncContext, StreamHandler};
-use actix_web::http::header::HeaderValue;
-use actix_web::{web, Error, HttpRequest, HttpResponse, Responder};
+use actix_web::{web, Error, HttpRequest, HttpResponse, Responder, ResponseError};
 use actix_web_actors::ws;
 use chrono::Utc;
 use diesel::r2d2::{ConnectionManager, Pool};
 use diesel::PgConnection;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::env;
 use tracing::{error, info};
 
-const FIREBASE_VALIDATE_TOKEN_URL: &str =
-    "https://identitytoolkit.googleapis.com/v1/accounts:lookup";
-
 #[derive(Serialize, Deserialize, Debug)]
 struct IncomingMessage {
     message: Option<String>,
@@ -196,8 +195,13 @@ pub async fn chat_route(
     req: HttpRequest,
     stream: web::Payload,
     db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    verifier: web::Data<dyn TokenVerifier>,
 ) -> Result<HttpResponse, Error> {
     let user_uid = extract_user_uuid(&req)?;
+    // Messages are sent as the path uid, so only that party may connect.
+    if let Err(e) = validate_websocket_auth(&req, &user_uid, verifier.get_ref(), &db_pool).await {
+        return Ok(e.error_response());
+    }
 
     ws::start(
         ChatWebSocket {
@@ -210,15 +214,14 @@ pub async fn chat_route(
 }
 
 pub async fn get_user_chats(
-    req: HttpRequest,
+    user: AuthenticatedUser,
     user_uid: web::Path<String>,
     db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
 ) -> impl Responder {
-    if !verify_token_from_request(&req).await {
-        return HttpResponse::Unauthorized().body("Invalid or missing token");
-    }
-
     let mut conn = db_pool.get().expect("Failed to get DB connection");
+    if let Err(e) = ownership::authorize_party(&mut conn, &user.uid, &user_uid) {
+        return e.error_response();
+    }
     match chat_db::get_chats_for_user(&mut conn, &user_uid) {
         Ok(chats) => HttpResponse::Ok().json(chats),
         Err(_) => HttpResponse::InternalServerError().json(ApiResponse {
@@ -243,14 +246,14 @@ pub struct PaginationParams {
 }
 
 pub async fn read_message(
-    req: HttpRequest,
+    user: AuthenticatedUser,
     message_id: web::Path<i32>,
     db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
 ) -> impl Responder {
-    if !verify_token_from_request(&req).await {
-        return HttpResponse::Unauthorized().body("Invalid or missing token");
-    }
     let mut conn = db_pool.get().expect("Failed to get DB connection");
+    if let Err(e) = ownership::authorize_message_receiver(&mut conn, &user.uid, *message_id) {
+        return e.error_response();
+    }
     match chat_db::read_message(&mut conn, &message_id) {
         Ok(message) => HttpResponse::Ok().json(message),
         Err(_) => HttpResponse::InternalServerError().json(ApiResponse {
@@ -264,15 +267,11 @@ pub async fn read_message(
 }
 
 pub async fn get_chat_messages(
-    req: HttpRequest,
+    user: AuthenticatedUser,
     query_info: web::Query<ChatQuery>,
     pagination: web::Query<PaginationParams>,
     db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
 ) -> impl Responder {
-    if !verify_token_from_request(&req).await {
-        return HttpResponse::Unauthorized().body("Invalid or missing token");
-    }
-
     let chat_id = match query_info.chat_id {
         Some(id) => id,
         None => return HttpResponse::BadRequest().body("Chat ID is required"),
@@ -281,6 +280,9 @@ pub async fn get_chat_messages(
     let limit = pagination.limit.unwrap_or(50);
     let offset = pagination.offset.unwrap_or(0);
     let mut conn = db_pool.get().expect("Failed to get DB connection");
+    if let Err(e) = ownership::authorize_chat(&mut conn, &user.uid, chat_id) {
+        return e.error_response();
+    }
 
     match chat_db::get_messages_for_chat(&mut conn, chat_id, limit, offset) {
         Ok(chat_items) => HttpResponse::Ok().json(chat_items),
@@ -288,27 +290,21 @@ pub async fn get_chat_messages(
     }
 }
 
-async fn verify_token_from_request(req: &HttpRequest) -> bool {
-    if let Some(token) = extract_token_from_auth_header(req.headers().get("Authorization")) {
-        match verify_token(&token).await {
-            Ok(is_valid) => is_valid,
-            Err(_) => false,
-        }
-    } else {
-        false
-    }
-}
 pub async fn retrieve_chat(
-    req: HttpRequest,
+    user: AuthenticatedUser,
     path: web::Path<(String, String)>,
     db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
 ) -> impl Responder {
-    if !verify_token_from_request(&req).await {
-        return HttpResponse::Unauthorized().body("Invalid or missing token");
-    }
-
     let (user_uid, professional_profile_uid) = path.into_inner();
     let mut conn = db_pool.get().expect("Failed to get DB connection");
+    if let Err(e) = ownership::authorize_either_party(
+        &mut conn,
+        &user.uid,
+        &user_uid,
+        &professional_profile_uid,
+    ) {
+        return e.error_response();
+    }
 
     match chat_db::retrieve_chat(&mut conn, &user_uid, &professional_profile_uid) {
         Ok(chat) => HttpResponse::Ok().json(chat),
@@ -322,33 +318,129 @@ pub async fn retrieve_chat(
     }
 }
 
-async fn verify_token(token: &str) -> Result<bool, reqwest::Error> {
-    let api_key = env::var("FIREBASE_API_KEY").expect("FIREBASE_API_KEY must be set");
-    let url = format!("{}?key={}", FIREBASE_VALIDATE_TOKEN_URL, api_key);
-    let client = reqwest::Client::new();
-    let res = client
-        .post(&url)
-        .json(&serde_json::json!({
-            "idToken": token
-        }))
-        .send()
-        .await?;
-
-    Ok(res.status().is_success())
-}
-
-fn extract_token_from_auth_header(auth_header: Option<&HeaderValue>) -> Option<String> {
-    auth_header?
-        .to_str()
-        .ok()?
-        .split_whitespace()
-        .nth(1)
-        .map(String::from)
-}
-
 fn extract_user_uuid(req: &HttpRequest) -> Result<String, Error> {
     req.match_info()
         .get("user_id")
         .ok_or_else(|| actix_web::error::ErrorBadRequest("Missing user_id parameter"))
         .map(|id| id.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{
+        insert_fixture, request_as, status_of, test_app, test_pool, CUSTOMER, PROFESSIONAL,
+        PROFILE_UID, STRANGER,
+    };
+    use actix_web::http::{Method, StatusCode};
+    use actix_web::test;
+
+    #[actix_web::test]
+    async fn chat_socket_only_accepts_a_party_of_the_path_uid() {
+        let Some(pool) = test_pool() else {
+            return;
+        };
+        insert_fixture(&pool);
+        let app = test_app!(pool);
+
+        for (path_uid, user_uid) in [
+            (CUSTOMER, STRANGER),
+            (PROFILE_UID, STRANGER),
+            (PROFILE_UID, CUSTOMER),
+        ] {
+            let uri = format!("/chat/ws/{}?token={}", path_uid, user_uid);
+            let request = test::TestRequest::get().uri(&uri).to_request();
+            assert_eq!(
+                test::call_service(&app, request).await.status(),
+                StatusCode::FORBIDDEN,
+                "{} as {}",
+                path_uid,
+                user_uid
+            );
+        }
+
+        let request = test::TestRequest::get()
+            .uri(&format!("/chat/ws/{}", CUSTOMER))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, request).await.status(),
+            StatusCode::UNAUTHORIZED
+        );
+
+        // Authorized, but not a WebSocket handshake. The professional may
+        // connect by the uid of their profile.
+        for (path_uid, user_uid) in [(CUSTOMER, CUSTOMER), (PROFILE_UID, PROFESSIONAL)] {
+            let uri = format!("/chat/ws/{}?token={}", path_uid, user_uid);
+            let request = test::TestRequest::get().uri(&uri).to_request();
+            assert_eq!(
+                test::call_service(&app, request).await.status(),
+                StatusCode::BAD_REQUEST,
+                "{} as {}",
+                path_uid,
+                user_uid
+            );
+        }
+    }
+
+    #[actix_web::test]
+    async fn chat_endpoints_require_a_participant() {
+        let Some(pool) = test_pool() else {
+            return;
+        };
+        let fixture = insert_fixture(&pool);
+        let app = test_app!(pool);
+
+        let own_chats = format!("/chat/retrieve/{}", CUSTOMER);
+        let messages = format!("/chat/messages?chat_id={}", fixture.chat_id);
+        let conversation = format!("/chat/retrieve_chat/{}/{}", CUSTOMER, PROFILE_UID);
+
+        for (uri, user_uid, expected) in [
+            (&own_chats, CUSTOMER, StatusCode::OK),
+            (&own_chats, PROFESSIONAL, StatusCode::FORBIDDEN),
+            (&own_chats, STRANGER, StatusCode::FORBIDDEN),
+            (&messages, CUSTOMER, StatusCode::OK),
+            (&messages, PROFESSIONAL, StatusCode::OK),
+            (&messages, STRANGER, StatusCode::FORBIDDEN),
+            (&conversation, CUSTOMER, StatusCode::OK),
+            (&conversation, PROFESSIONAL, StatusCode::OK),
+            (&conversation, STRANGER, StatusCode::FORBIDDEN),
+        ] {
+            assert_eq!(
+                status_of!(app, request_as(Method::GET, uri, user_uid)),
+                expected,
+                "GET {} as {}",
+                uri,
+                user_uid
+            );
+        }
+
+        let missing_chat = format!("/chat/messages?chat_id={}", fixture.chat_id + 1_000_000);
+        assert_eq!(
+            status_of!(app, request_as(Method::GET, &missing_chat, CUSTOMER)),
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[actix_web::test]
+    async fn only_the_receiver_marks_a_message_read() {
+        let Some(pool) = test_pool() else {
+            return;
+        };
+        let fixture = insert_fixture(&pool);
+        let app = test_app!(pool);
+
+        let uri = format!("/chat/messages/{}", fixture.message_id);
+        for (user_uid, expected) in [
+            (STRANGER, StatusCode::FORBIDDEN),
+            (PROFESSIONAL, StatusCode::FORBIDDEN),
+            (CUSTOMER, StatusCode::OK),
+        ] {
+            assert_eq!(
+                status_of!(app, request_as(Method::POST, &uri, user_uid)),
+                expected,
+                "POST {} as {}",
+                uri,
+                user_uid
+            );
+        }
+    }
+}
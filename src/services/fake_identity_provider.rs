@@ -0,0 +1,202 @@
+use crate::errors::firebase_errors::FirebaseServiceError;
+use crate::services::identity_provider::{
+    IdentityClaims, IdentityFuture, IdentityProvider, NewIdentity,
+};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+const TOKEN_PREFIX: &str = "fake-token:";
+
+/// Stands in for Firebase in tests. Accounts live in memory and the ID
+/// token of a user is `fake-token:<uid>`, see [`FakeIdentityProvider::token_for`].
+#[derive(Default)]
+pub struct FakeIdentityProvider {
+    /// Accounts by uid.
+    accounts: Mutex<HashMap<String, NewIdentity>>,
+    /// Request type and recipient of every email sent, oldest first.
+    sent_emails: Mutex<Vec<(&'static str, String)>>,
+}
+
+impl FakeIdentityProvider {
+    /// The ID token the fake accepts for `uid`.
+    pub fn token_for(uid: &str) -> String {
+        format!("{}{}", TOKEN_PREFIX, uid)
+    }
+
+    /// The account created for `email`, if any.
+    pub fn account(&self, email: &str) -> Option<NewIdentity> {
+        self.accounts
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .values()
+            .find(|account| account.email == email)
+            .cloned()
+    }
+
+    /// The emails sent so far as `(request type, recipient)`, e.g.
+    /// `("PASSWORD_RESET", "user@example.com")`.
+    pub fn sent_emails(&self) -> Vec<(&'static str, String)> {
+        self.sent_emails
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn account_of_token(&self, token: &str) -> Result<NewIdentity, FirebaseServiceError> {
+        let uid = token.strip_prefix(TOKEN_PREFIX).ok_or_else(|| {
+            FirebaseServiceError::FirebaseApiError("INVALID_ID_TOKEN".to_string())
+        })?;
+        self.accounts
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(uid)
+            .cloned()
+            .ok_or_else(|| FirebaseServiceError::FirebaseApiError("USER_NOT_FOUND".to_string()))
+    }
+
+    fn send_email(&self, request_type: &'static str, email: String) {
+        self.sent_emails
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((request_type, email));
+    }
+}
+
+impl IdentityProvider for FakeIdentityProvider {
+    fn create_user<'a>(
+        &'a self,
+        email: &'a str,
+        password: &'a str,
+    ) -> IdentityFuture<'a, NewIdentity> {
+        Box::pin(async move {
+            // The same checks and error codes as Firebase.
+            if !email.contains('@') {
+                return Err(FirebaseServiceError::FirebaseApiError(
+                    "INVALID_EMAIL".to_string(),
+                ));
+            }
+            if password.len() < 6 {
+                return Err(FirebaseServiceError::FirebaseApiError(
+                    "WEAK_PASSWORD".to_string(),
+                ));
+            }
+            if self.account(email).is_some() {
+                return Err(FirebaseServiceError::FirebaseApiError(
+                    "EMAIL_EXISTS".to_string(),
+                ));
+            }
+
+            let account = NewIdentity {
+                uid: Uuid::new_v4().simple().to_string(),
+                email: email.to_string(),
+            };
+            self.accounts
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(account.uid.clone(), account.clone());
+            Ok(account)
+        })
+    }
+
+    fn send_password_reset<'a>(&'a self, email: &'a str) -> IdentityFuture<'a, ()> {
+        Box::pin(async move {
+            let account = self.account(email).ok_or_else(|| {
+                FirebaseServiceError::FirebaseApiError("EMAIL_NOT_FOUND".to_string())
+            })?;
+            self.send_email("PASSWORD_RESET", account.email);
+            Ok(())
+        })
+    }
+
+    fn send_email_verification<'a>(&'a self, id_token: &'a str) -> IdentityFuture<'a, ()> {
+        Box::pin(async move {
+            let account = self.account_of_token(id_token)?;
+            self.send_email("VERIFY_EMAIL", account.email);
+            Ok(())
+        })
+    }
+
+    fn delete_user<'a>(&'a self, id_token: &'a str) -> IdentityFuture<'a, ()> {
+        Box::pin(async move {
+            let account = self.account_of_token(id_token)?;
+            self.accounts
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(&account.uid);
+            Ok(())
+        })
+    }
+
+    fn verify_token<'a>(&'a self, token: &'a str) -> IdentityFuture<'a, IdentityClaims> {
+        Box::pin(async move {
+            let uid = token
+                .strip_prefix(TOKEN_PREFIX)
+                .filter(|uid| !uid.is_empty())
+                .ok_or(FirebaseServiceError::JwtDecodeError)?;
+
+            Ok(IdentityClaims {
+                uid: uid.to_string(),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[actix_web::test]
+    async fn created_users_sign_in_with_their_token() {
+        let identity = FakeIdentityProvider::default();
+        let account = identity
+            .create_user("user@example.com", "secret123")
+            .await
+            .unwrap();
+
+        let token = FakeIdentityProvider::token_for(&account.uid);
+        let claims = identity.verify_token(&token).await.unwrap();
+        assert_eq!(claims.uid, account.uid);
+        assert_eq!(identity.uid_from_token(&token).await.unwrap(), account.uid);
+
+        assert!(matches!(
+            identity.create_user("user@example.com", "secret123").await,
+            Err(FirebaseServiceError::FirebaseApiError(message)) if message == "EMAIL_EXISTS"
+        ));
+        assert!(identity.verify_token("someone-else").await.is_err());
+    }
+
+    #[actix_web::test]
+    async fn sends_emails_to_and_deletes_existing_accounts() {
+        let identity = FakeIdentityProvider::default();
+        let account = identity
+            .create_user("user@example.com", "secret123")
+            .await
+            .unwrap();
+        let token = FakeIdentityProvider::token_for(&account.uid);
+
+        identity
+            .send_password_reset("user@example.com")
+            .await
+            .unwrap();
+        identity.send_email_verification(&token).await.unwrap();
+        assert!(matches!(
+            identity.send_password_reset("nobody@example.com").await,
+            Err(FirebaseServiceError::FirebaseApiError(message)) if message == "EMAIL_NOT_FOUND"
+        ));
+        assert_eq!(
+            identity.sent_emails(),
+            vec![
+                ("PASSWORD_RESET", "user@example.com".to_string()),
+                ("VERIFY_EMAIL", "user@example.com".to_string()),
+            ]
+        );
+
+        identity.delete_user(&token).await.unwrap();
+        assert!(identity.account("user@example.com").is_none());
+        assert!(matches!(
+            identity.delete_user(&token).await,
+            Err(FirebaseServiceError::FirebaseApiError(message)) if message == "USER_NOT_FOUND"
+        ));
+    }
+}
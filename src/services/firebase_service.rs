@@ -1,120 +1,347 @@
 use crate::errors::firebase_errors::FirebaseServiceError;
-use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use crate::middleware::firebase_keys::{FirebaseKeyCache, FIREBASE_PUBLIC_KEYS_URL};
+use crate::services::identity_provider::{
+    IdentityClaims, IdentityFuture, IdentityProvider, NewIdentity,
+};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::Utc;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, env};
+use std::env;
 
-const FIREBASE_SIGN_UP_URL: &str = "https://identitytoolkit.googleapis.com/v1/accounts:signUp";
-const FIREBASE_VALIDATE_TOKEN_URL: &str =
-    "https://identitytoolkit.googleapis.com/v1/accounts:lookup";
+const IDENTITY_TOOLKIT_URL: &str = "https://identitytoolkit.googleapis.com";
 
-#[derive(Debug, Serialize, Deserialize)]
-struct Claims {
-    sub: String,
+/// The emulator accepts any API key, so none has to be configured for it.
+const EMULATOR_API_KEY: &str = "fake-api-key";
+
+/// Where Firebase Auth is reached. Everything defaults to Google's
+/// production endpoints; pointing it at the Firebase Auth emulator lets the
+/// auth paths run offline.
+#[derive(Debug, Clone)]
+pub struct FirebaseConfig {
+    pub api_key: Option<String>,
+    pub project_id: Option<String>,
+    /// Base of the Identity Toolkit REST API, without the version.
+    pub identity_toolkit_url: String,
+    /// The x509 certificates ID tokens are signed with.
+    pub public_keys_url: String,
+    /// The emulator issues unsigned ID tokens, which are only accepted in
+    /// this mode.
+    pub emulator: bool,
+}
+
+impl FirebaseConfig {
+    /// Reads `FIREBASE_API_KEY` and `FIREBASE_PROJECT_ID`. Setting
+    /// `FIREBASE_AUTH_EMULATOR_HOST`, as for the Firebase SDKs, switches to
+    /// the emulator; `FIREBASE_IDENTITY_TOOLKIT_URL` and
+    /// `FIREBASE_PUBLIC_KEYS_URL` override single endpoints.
+    pub fn from_env() -> Self {
+        let emulator_host = non_empty_var("FIREBASE_AUTH_EMULATOR_HOST");
+        let identity_toolkit_url = non_empty_var("FIREBASE_IDENTITY_TOOLKIT_URL")
+            .or_else(|| {
+                emulator_host
+                    .as_ref()
+                    .map(|host| format!("http://{}/identitytoolkit.googleapis.com", host))
+            })
+            .unwrap_or_else(|| IDENTITY_TOOLKIT_URL.to_string());
+        let public_keys_url = non_empty_var("FIREBASE_PUBLIC_KEYS_URL")
+            .unwrap_or_else(|| FIREBASE_PUBLIC_KEYS_URL.to_string());
+        let emulator = emulator_host.is_some();
+
+        FirebaseConfig {
+            api_key: non_empty_var("FIREBASE_API_KEY")
+                .or_else(|| emulator.then(|| EMULATOR_API_KEY.to_string())),
+            project_id: non_empty_var("FIREBASE_PROJECT_ID"),
+            identity_toolkit_url: identity_toolkit_url.trim_end_matches('/').to_string(),
+            public_keys_url,
+            emulator,
+        }
+    }
+}
+
+fn non_empty_var(name: &str) -> Option<String> {
+    env::var(name)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
 }
 
 #[derive(Serialize)]
-struct FirebaseRegisterRequest {
-    email: String,
-    password: String,
+#[serde(rename_all = "camelCase")]
+struct SignUpRequest<'a> {
+    email: &'a str,
+    password: &'a str,
     return_secure_token: bool,
 }
 
-#[allow(non_snake_case)]
 #[derive(Deserialize)]
-pub struct FirebaseRegisterResponse {
-    pub idToken: String,
-    pub email: String,
-    pub refreshToken: String,
-    pub expiresIn: String,
-    pub localId: String, // The UID
-}
-
-pub async fn create_firebase_user(
-    email: &str,
-    password: &str,
-) -> Result<FirebaseRegisterResponse, FirebaseServiceError> {
-    let api_key = env::var("FIREBASE_API_KEY")?;
-    let client = Client::new();
-    let request_body = FirebaseRegisterRequest {
-        email: email.to_string(),
-        password: password.to_string(),
-        return_secure_token: true,
-    };
+#[serde(rename_all = "camelCase")]
+struct SignUpResponse {
+    local_id: String,
+    email: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SendOobCodeRequest<'a> {
+    request_type: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    email: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id_token: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DeleteAccountRequest<'a> {
+    id_token: &'a str,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: ErrorBody,
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    message: String,
+}
+
+/// Firebase Auth, or the Firebase Auth emulator, as the identity provider.
+pub struct FirebaseIdentityProvider {
+    config: FirebaseConfig,
+    client: Client,
+    keys: FirebaseKeyCache,
+}
+
+impl FirebaseIdentityProvider {
+    pub fn new(config: FirebaseConfig) -> Self {
+        FirebaseIdentityProvider {
+            keys: FirebaseKeyCache::new(&config.public_keys_url),
+            client: Client::new(),
+            config,
+        }
+    }
+
+    fn api_key(&self) -> Result<&str, FirebaseServiceError> {
+        self.config
+            .api_key
+            .as_deref()
+            .ok_or(FirebaseServiceError::MissingConfig("FIREBASE_API_KEY"))
+    }
+
+    fn project_id(&self) -> Result<&str, FirebaseServiceError> {
+        self.config
+            .project_id
+            .as_deref()
+            .ok_or(FirebaseServiceError::MissingConfig("FIREBASE_PROJECT_ID"))
+    }
+
+    /// URL of the Identity Toolkit method `method`, e.g. `accounts:signUp`.
+    fn endpoint(&self, method: &str) -> Result<String, FirebaseServiceError> {
+        Ok(format!(
+            "{}/v1/{}?key={}",
+            self.config.identity_toolkit_url,
+            method,
+            self.api_key()?
+        ))
+    }
+
+    /// Posts `body` to the Identity Toolkit method `method`. Failures carry
+    /// Firebase's own message, e.g. EMAIL_EXISTS or WEAK_PASSWORD.
+    async fn request<B: Serialize>(
+        &self,
+        method: &str,
+        body: &B,
+    ) -> Result<reqwest::Response, FirebaseServiceError> {
+        let response = self
+            .client
+            .post(self.endpoint(method)?)
+            .json(body)
+            .send()
+            .await?;
 
-    let response = client
-        .post(format!("{}?key={}", FIREBASE_SIGN_UP_URL, api_key))
-        .json(&request_body)
-        .send()
-        .await?;
-
-    if response.status().is_success() {
-        response
-            .json::<FirebaseRegisterResponse>()
-            .await
-            .map_err(|_| {
+        if !response.status().is_success() {
+            let message = response
+                .json::<ErrorResponse>()
+                .await
+                .map(|body| body.error.message)
+                .unwrap_or_else(|_| format!("Firebase call {} failed", method));
+            return Err(FirebaseServiceError::FirebaseApiError(message));
+        }
+        Ok(response)
+    }
+}
+
+impl IdentityProvider for FirebaseIdentityProvider {
+    fn create_user<'a>(
+        &'a self,
+        email: &'a str,
+        password: &'a str,
+    ) -> IdentityFuture<'a, NewIdentity> {
+        Box::pin(async move {
+            let request_body = SignUpRequest {
+                email,
+                password,
+                return_secure_token: true,
+            };
+            let response = self.request("accounts:signUp", &request_body).await?;
+            let account = response.json::<SignUpResponse>().await.map_err(|_| {
                 FirebaseServiceError::FirebaseApiError(
                     "Failed to parse Firebase response".to_string(),
                 )
+            })?;
+
+            Ok(NewIdentity {
+                uid: account.local_id,
+                email: account.email,
             })
-    } else {
-        Err(FirebaseServiceError::FirebaseApiError(
-            "Failed to create user in Firebase".to_string(),
-        ))
+        })
+    }
+
+    fn send_password_reset<'a>(&'a self, email: &'a str) -> IdentityFuture<'a, ()> {
+        Box::pin(async move {
+            let request_body = SendOobCodeRequest {
+                request_type: "PASSWORD_RESET",
+                email: Some(email),
+                id_token: None,
+            };
+            self.request("accounts:sendOobCode", &request_body).await?;
+            Ok(())
+        })
+    }
+
+    fn send_email_verification<'a>(&'a self, id_token: &'a str) -> IdentityFuture<'a, ()> {
+        Box::pin(async move {
+            let request_body = SendOobCodeRequest {
+                request_type: "VERIFY_EMAIL",
+                email: None,
+                id_token: Some(id_token),
+            };
+            self.request("accounts:sendOobCode", &request_body).await?;
+            Ok(())
+        })
+    }
+
+    fn delete_user<'a>(&'a self, id_token: &'a str) -> IdentityFuture<'a, ()> {
+        Box::pin(async move {
+            self.request("accounts:delete", &DeleteAccountRequest { id_token })
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn verify_token<'a>(&'a self, token: &'a str) -> IdentityFuture<'a, IdentityClaims> {
+        Box::pin(async move {
+            let project_id = self.project_id()?;
+            if self.config.emulator {
+                emulator_claims(token, project_id, Utc::now().timestamp())
+            } else {
+                self.keys.verify(token, project_id).await
+            }
+        })
     }
 }
 
-pub async fn verify_token(token: &str) -> Result<bool, FirebaseServiceError> {
-    let api_key = env::var("FIREBASE_API_KEY")?;
-    let url = format!("{}?key={}", FIREBASE_VALIDATE_TOKEN_URL, api_key);
-    let client = reqwest::Client::new();
-    let res = client
-        .post(&url)
-        .json(&serde_json::json!({
-            "idToken": token
-        }))
-        .send()
-        .await?;
-    Ok(res.status().is_success())
-}
-
-async fn get_firebase_public_keys() -> Result<HashMap<String, DecodingKey>, FirebaseServiceError> {
-    let jwks_url =
-        "https://www.googleapis.com/robot/v1/metadata/x509/securetoken@system.gserviceaccount.com";
-    let client = Client::new();
-    let response = client
-        .get(jwks_url)
-        .send()
-        .await?
-        .json::<HashMap<String, String>>()
-        .await?;
-    let mut keys = HashMap::new();
-    for (kid, key) in response {
-        keys.insert(kid, DecodingKey::from_rsa_pem(key.as_bytes())?);
-    }
-
-    Ok(keys)
-}
-
-pub async fn extract_uid_from_firebase_token(token: &str) -> Result<String, FirebaseServiceError> {
-    let header = decode_header(token).map_err(|_| FirebaseServiceError::JwtDecodeError)?;
-    let kid = header.kid.ok_or(FirebaseServiceError::MissingKidError)?;
-
-    let keys = get_firebase_public_keys().await?;
-    let key = keys
-        .get(&kid)
-        .ok_or(FirebaseServiceError::InvalidKidError)?;
-
-    let mut validation = Validation::new(Algorithm::RS256);
-    let firebase_project_id = env::var("FIREBASE_PROJECT_ID")?;
-    validation.set_audience(&[firebase_project_id.clone()]);
-    validation.set_issuer(&[format!(
-        "https://securetoken.google.com/{}",
-        firebase_project_id
-    )]);
-
-    let token_data = decode::<Claims>(token, key, &validation)
-        .map_err(|_| FirebaseServiceError::JwtDecodeError)?;
-
-    Ok(token_data.claims.sub)
+#[derive(Deserialize)]
+struct EmulatorHeader {
+    alg: String,
+}
+
+#[derive(Deserialize)]
+struct EmulatorClaims {
+    sub: String,
+    aud: String,
+    iss: String,
+    exp: i64,
+}
+
+/// Claims of an ID token issued by the Auth emulator. These tokens carry
+/// `alg: none` and no signature, so only their audience, issuer and expiry
+/// can be checked.
+fn emulator_claims(
+    token: &str,
+    project_id: &str,
+    now: i64,
+) -> Result<IdentityClaims, FirebaseServiceError> {
+    let mut parts = token.split('.');
+    let (header, payload) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(header), Some(payload), Some(_signature), None) => (header, payload),
+        _ => return Err(FirebaseServiceError::JwtDecodeError),
+    };
+    let decode = |part: &str| URL_SAFE_NO_PAD.decode(part.trim_end_matches('='));
+
+    let header: EmulatorHeader = decode(header)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .ok_or(FirebaseServiceError::JwtDecodeError)?;
+    let claims: EmulatorClaims = decode(payload)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .ok_or(FirebaseServiceError::JwtDecodeError)?;
+
+    let issuer = format!("https://securetoken.google.com/{}", project_id);
+    if header.alg != "none"
+        || claims.aud != project_id
+        || claims.iss != issuer
+        || claims.exp <= now
+        || claims.sub.is_empty()
+    {
+        return Err(FirebaseServiceError::JwtDecodeError);
+    }
+    Ok(IdentityClaims { uid: claims.sub })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn unsigned_token(header: serde_json::Value, claims: serde_json::Value) -> String {
+        format!(
+            "{}.{}.",
+            URL_SAFE_NO_PAD.encode(header.to_string()),
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        )
+    }
+
+    fn claims(aud: &str, exp: i64) -> serde_json::Value {
+        serde_json::json!({
+            "sub": "user-1",
+            "aud": aud,
+            "iss": format!("https://securetoken.google.com/{}", aud),
+            "exp": exp,
+        })
+    }
+
+    #[test]
+    fn accepts_unsigned_emulator_tokens_of_the_project() {
+        let token = unsigned_token(
+            serde_json::json!({ "alg": "none" }),
+            claims("demo", NOW + 60),
+        );
+
+        let identity = emulator_claims(&token, "demo", NOW).unwrap();
+        assert_eq!(identity.uid, "user-1");
+    }
+
+    #[test]
+    fn rejects_expired_foreign_and_signed_emulator_tokens() {
+        let none = serde_json::json!({ "alg": "none" });
+        let expired = unsigned_token(none.clone(), claims("demo", NOW));
+        let foreign = unsigned_token(none, claims("other", NOW + 60));
+        let signed = unsigned_token(
+            serde_json::json!({ "alg": "RS256" }),
+            claims("demo", NOW + 60),
+        );
+
+        for token in [expired, foreign, signed, "not-a-jwt".to_string()] {
+            assert!(matches!(
+                emulator_claims(&token, "demo", NOW),
+                Err(FirebaseServiceError::JwtDecodeError)
+            ));
+        }
+    }
 }
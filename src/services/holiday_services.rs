@@ -0,0 +1,2 @@
+pub mod holiday_endpoints;
+pub mod holiday_service;
@@ -0,0 +1,14 @@
+use super::holiday_service;
+use actix_web::web;
+
+pub fn holiday_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/holidays")
+            .route("", web::get().to(holiday_service::get_holidays))
+            .route("", web::post().to(holiday_service::create_holiday))
+            .route(
+                "/{holiday_id}",
+                web::delete().to(holiday_service::delete_holiday),
+            ),
+    );
+}
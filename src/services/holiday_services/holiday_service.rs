@@ -0,0 +1,49 @@
+use crate::dal::holiday_db;
+use crate::db::Pool;
+use crate::middleware::authenticated_user::{AdminUser, AuthenticatedUser};
+use crate::models::public_holiday::NewPublicHoliday;
+use actix_web::{web, HttpResponse, Responder, ResponseError};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct HolidayQuery {
+    state: Option<String>,
+    year: Option<i32>,
+}
+
+/// Holiday calendar, optionally narrowed to the holidays of one state and
+/// one year.
+pub async fn get_holidays(
+    _user: AuthenticatedUser,
+    db_pool: web::Data<Pool>,
+    query: web::Query<HolidayQuery>,
+) -> impl Responder {
+    let query = query.into_inner();
+
+    match holiday_db::get_holidays(db_pool, query.state, query.year).await {
+        Ok(holidays) => HttpResponse::Ok().json(holidays),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn create_holiday(
+    _admin: AdminUser,
+    db_pool: web::Data<Pool>,
+    holiday: web::Json<NewPublicHoliday>,
+) -> impl Responder {
+    match holiday_db::create_holiday(db_pool, holiday.into_inner()).await {
+        Ok(holiday) => HttpResponse::Created().json(holiday),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn delete_holiday(
+    _admin: AdminUser,
+    holiday_id: web::Path<i32>,
+    db_pool: web::Data<Pool>,
+) -> impl Responder {
+    match holiday_db::delete_holiday(db_pool, holiday_id.into_inner()).await {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => e.error_response(),
+    }
+}
@@ -0,0 +1,48 @@
+use crate::errors::firebase_errors::FirebaseServiceError;
+use std::future::Future;
+use std::pin::Pin;
+
+pub type IdentityFuture<'a, T> =
+    Pin<Box<dyn Future<Output = Result<T, FirebaseServiceError>> + 'a>>;
+
+/// An account the identity provider created.
+#[derive(Debug, Clone)]
+pub struct NewIdentity {
+    pub uid: String,
+    pub email: String,
+}
+
+/// What a verified ID token says about its user.
+#[derive(Debug, Clone)]
+pub struct IdentityClaims {
+    pub uid: String,
+}
+
+/// The service that owns user accounts and signs their ID tokens. `main`
+/// registers Firebase as a `web::Data<dyn IdentityProvider>`; tests use an
+/// in-process fake instead.
+pub trait IdentityProvider: Send + Sync {
+    /// Creates an email and password account.
+    fn create_user<'a>(
+        &'a self,
+        email: &'a str,
+        password: &'a str,
+    ) -> IdentityFuture<'a, NewIdentity>;
+
+    /// Checks an ID token and returns its claims.
+    fn verify_token<'a>(&'a self, token: &'a str) -> IdentityFuture<'a, IdentityClaims>;
+
+    /// Emails a password reset link to the account with `email`.
+    fn send_password_reset<'a>(&'a self, email: &'a str) -> IdentityFuture<'a, ()>;
+
+    /// Emails a verification link to the account the ID token belongs to.
+    fn send_email_verification<'a>(&'a self, id_token: &'a str) -> IdentityFuture<'a, ()>;
+
+    /// Deletes the account the ID token belongs to.
+    fn delete_user<'a>(&'a self, id_token: &'a str) -> IdentityFuture<'a, ()>;
+
+    /// The uid an ID token was issued to.
+    fn uid_from_token<'a>(&'a self, token: &'a str) -> IdentityFuture<'a, String> {
+        Box::pin(async move { Ok(self.verify_token(token).await?.uid) })
+    }
+}
@@ -4,15 +4,75 @@ pub fn professional_profile_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/profiles")
             // Define user-related endpoints
+            .route(
+                "",
+                web::post().to(super::professional_profile_service::create_profile),
+            )
+            .route(
+                "/mine",
+                web::get().to(super::professional_profile_service::get_own_profiles),
+            )
             .route(
                 "/search",
                 web::get()
                     .to(super::professional_profile_service::get_professional_profile_handler),
             )
+            .route(
+                "/search/text",
+                web::get().to(super::professional_profile_service::text_search_handler),
+            )
             .route(
                 "/{profile_id}",
                 web::get().to(super::professional_profile_service::get_profile_by_id),
             )
+            .route(
+                "/{profile_id}",
+                web::put().to(super::professional_profile_service::update_profile),
+            )
+            .route(
+                "/{profile_id}/address",
+                web::put().to(super::professional_profile_service::set_profile_address),
+            )
+            .route(
+                "/{profile_id}/hours",
+                web::get().to(super::professional_profile_service::get_business_hours),
+            )
+            .route(
+                "/{profile_id}/hours",
+                web::put().to(super::professional_profile_service::set_weekly_hours),
+            )
+            .route(
+                "/{profile_id}/hours/exceptions",
+                web::post().to(super::professional_profile_service::create_hours_exception),
+            )
+            .route(
+                "/{profile_id}/hours/exceptions/{exception_id}",
+                web::delete().to(super::professional_profile_service::delete_hours_exception),
+            )
+            .route(
+                "/{profile_id}/offerings",
+                web::get().to(super::professional_profile_service::get_profile_offerings),
+            )
+            .route(
+                "/{profile_id}/offerings",
+                web::post().to(super::professional_profile_service::create_offering),
+            )
+            .route(
+                "/{profile_id}/offerings/{offering_id}",
+                web::put().to(super::professional_profile_service::update_offering),
+            )
+            .route(
+                "/{profile_id}/offerings/{offering_id}",
+                web::delete().to(super::professional_profile_service::delete_offering),
+            )
+            .route(
+                "/{profile_id}/reviews",
+                web::get().to(super::professional_profile_service::get_profile_reviews),
+            )
+            .route(
+                "/{profile_id}/slots",
+                web::get().to(super::professional_profile_service::get_profile_slots),
+            )
             .route(
                 "/{professional_id}/book-service",
                 web::post().to(super::professional_profile_service::book_service_handler),
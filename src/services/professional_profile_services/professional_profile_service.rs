@@ -1,69 +1,224 @@
 use crate::dal::booking_db;
-use crate::dal::professional_profile_db;
+use crate::dal::business_hours_db;
+use crate::dal::professional_profile_db::{self, ProfileSearchFilter};
+use crate::dal::review_db::{self, ReviewPageFilter};
+use crate::errors::booking_errors::BookingError;
+use crate::errors::review_errors::ReviewError;
+use crate::middleware::authenticated_user::{AuthenticatedUser, CustomerUser, ProfessionalUser};
+use crate::models::dtos::address_dto::AddressDTO;
 use crate::models::dtos::booking_dto::BookingDTO;
-use crate::services::firebase_service::{extract_uid_from_firebase_token, verify_token};
-use actix_web::{http::header::HeaderValue, web, HttpRequest, HttpResponse, Responder};
+use crate::models::dtos::business_hours_dto::{BusinessHourExceptionInputDTO, WeeklyHoursDTO};
+use crate::models::dtos::professional_profile_edit_dto::{
+    CreateProfessionalProfileDTO, ServiceOfferingInputDTO, UpdateProfessionalProfileDTO,
+};
+use crate::models::professional_aggregate::profile_search::{SearchCursor, SearchSort};
+use crate::models::review_aggregate::review_query::{ReviewCursor, ReviewSort};
+use actix_web::{web, HttpResponse, Responder, ResponseError};
+use chrono::Duration;
 use diesel::prelude::*;
 use diesel::r2d2::{ConnectionManager, Pool};
 use serde::Deserialize;
 
+/// Search radius used when the client does not send one, and the largest
+/// one accepted.
+const DEFAULT_SEARCH_RADIUS_KM: f64 = 5.0;
+const MAX_SEARCH_RADIUS_KM: f64 = 100.0;
+
+const DEFAULT_PAGE_SIZE: i64 = 20;
+const MAX_PAGE_SIZE: i64 = 100;
+
+const MAX_SEARCH_TEXT_LENGTH: usize = 200;
+
+/// Query of both the subcategory search, which requires `subcategory_ids`,
+/// and the text search, which requires `q` and may narrow by subcategory.
 #[derive(Deserialize)]
 pub struct ProfessionalProfileQuery {
-    subcategory_ids: String,
+    subcategory_ids: Option<String>,
+    q: Option<String>,
     lat: f64,
     lng: f64,
+    radius: Option<f64>,
+    open_now: Option<bool>,
+    min_rating: Option<f64>,
+    remote_available: Option<bool>,
+    delivery_enabled: Option<bool>,
+    min_price: Option<f64>,
+    max_price: Option<f64>,
+    sort: Option<SearchSort>,
+    cursor: Option<String>,
+    limit: Option<i64>,
+}
+
+impl ProfessionalProfileQuery {
+    fn into_filter(self) -> Result<ProfileSearchFilter, String> {
+        let subcategory_ids = self
+            .subcategory_ids
+            .map(|ids| {
+                ids.split(',')
+                    .map(|s| s.trim().parse::<i32>())
+                    .collect::<Result<Vec<i32>, _>>()
+                    .map_err(|_| "Invalid subcategory IDs".to_string())
+            })
+            .transpose()?;
+
+        let text = match self.q.as_deref().map(str::trim) {
+            Some("") | None => None,
+            Some(text) if text.chars().count() > MAX_SEARCH_TEXT_LENGTH => {
+                return Err(format!(
+                    "q must be at most {} characters",
+                    MAX_SEARCH_TEXT_LENGTH
+                ))
+            }
+            Some(text) => Some(text.to_string()),
+        };
+        let sort = match (self.sort, &text) {
+            (Some(SearchSort::Relevance), None) => {
+                return Err("sort=relevance requires q".to_string())
+            }
+            (Some(sort), _) => sort,
+            (None, Some(_)) => SearchSort::Relevance,
+            (None, None) => SearchSort::default(),
+        };
+
+        if !(-90.0..=90.0).contains(&self.lat) || !(-180.0..=180.0).contains(&self.lng) {
+            return Err("Invalid coordinates".to_string());
+        }
+        let radius_km = self.radius.unwrap_or(DEFAULT_SEARCH_RADIUS_KM);
+        if !(radius_km > 0.0 && radius_km <= MAX_SEARCH_RADIUS_KM) {
+            return Err(format!(
+                "radius must be greater than 0 and at most {} km",
+                MAX_SEARCH_RADIUS_KM
+            ));
+        }
+        if self
+            .min_rating
+            .is_some_and(|rating| !(0.0..=5.0).contains(&rating))
+        {
+            return Err("min_rating must be between 0 and 5".to_string());
+        }
+        if let (Some(min_price), Some(max_price)) = (self.min_price, self.max_price) {
+            if min_price > max_price {
+                return Err("min_price must not exceed max_price".to_string());
+            }
+        }
+
+        let cursor = self
+            .cursor
+            .as_deref()
+            .map(SearchCursor::decode)
+            .transpose()?;
+
+        Ok(ProfileSearchFilter {
+            subcategory_ids,
+            text,
+            lat: self.lat,
+            lng: self.lng,
+            radius_km,
+            open_now: self.open_now.unwrap_or(false),
+            min_rating: self.min_rating,
+            remote_available: self.remote_available,
+            delivery_enabled: self.delivery_enabled,
+            min_price: self.min_price,
+            max_price: self.max_price,
+            sort,
+            cursor,
+            limit: self
+                .limit
+                .unwrap_or(DEFAULT_PAGE_SIZE)
+                .clamp(1, MAX_PAGE_SIZE),
+        })
+    }
+}
+
+/// Default and bounds for the `duration` of requested slots, in minutes.
+const DEFAULT_SLOT_MINUTES: i64 = 60;
+const MIN_SLOT_MINUTES: i64 = 15;
+const MAX_SLOT_MINUTES: i64 = 8 * 60;
+/// Longest window a single slots request may cover.
+const MAX_SLOT_RANGE_DAYS: i64 = 31;
+
+const DEFAULT_REVIEW_PAGE_SIZE: i64 = 10;
+const MAX_REVIEW_PAGE_SIZE: i64 = 50;
+
+#[derive(Deserialize)]
+pub struct ReviewsQuery {
+    #[serde(default)]
+    sort: ReviewSort,
+    rating: Option<i32>,
+    cursor: Option<String>,
+    limit: Option<i64>,
+}
+
+impl ReviewsQuery {
+    fn into_filter(self) -> Result<ReviewPageFilter, ReviewError> {
+        if self.rating.is_some_and(|stars| !(1..=5).contains(&stars)) {
+            return Err(ReviewError::InvalidQuery(
+                "rating must be between 1 and 5".to_string(),
+            ));
+        }
+        let cursor = self
+            .cursor
+            .as_deref()
+            .map(|cursor| ReviewCursor::decode(self.sort, cursor))
+            .transpose()?;
+
+        Ok(ReviewPageFilter {
+            sort: self.sort,
+            rating: self.rating,
+            cursor,
+            limit: self
+                .limit
+                .unwrap_or(DEFAULT_REVIEW_PAGE_SIZE)
+                .clamp(1, MAX_REVIEW_PAGE_SIZE),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SlotsQuery {
+    from: String,
+    to: String,
+    duration: Option<i64>,
 }
 
 pub async fn get_professional_profile_handler(
-    req: HttpRequest,
+    _user: AuthenticatedUser,
     query_info: web::Query<ProfessionalProfileQuery>,
     db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
 ) -> impl Responder {
-    // Verify the token
-    let token = _extract_token_from_auth_header(req.headers().get("Authorization"));
-    match token {
-        Some(t) => {
-            if let Ok(is_valid) = verify_token(&t).await {
-                if !is_valid {
-                    // If the token is not valid, return an unauthorized response
-                    return HttpResponse::Unauthorized().body("Invalid token");
-                }
-            } else {
-                // If token verification failed due to some error
-                return HttpResponse::InternalServerError().finish();
-            }
-        }
-        None => {
-            // If no token is present in the request
-            return HttpResponse::Unauthorized().body("No token");
-        }
+    if query_info.subcategory_ids.is_none() {
+        return HttpResponse::BadRequest().body("subcategory_ids is required");
     }
+    search_profiles(query_info.into_inner(), db_pool).await
+}
+
+/// Full-text search over professionals, categories and subcategories near a
+/// location, e.g. `?q=plumber leak&lat=..&lng=..`.
+pub async fn text_search_handler(
+    _user: AuthenticatedUser,
+    query_info: web::Query<ProfessionalProfileQuery>,
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+) -> impl Responder {
+    if query_info.q.as_deref().is_none_or(|q| q.trim().is_empty()) {
+        return HttpResponse::BadRequest().body("q is required");
+    }
+    search_profiles(query_info.into_inner(), db_pool).await
+}
+
+async fn search_profiles(
+    query: ProfessionalProfileQuery,
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+) -> HttpResponse {
     let mut conn = db_pool
         .get()
         .expect("Failed to get DB connection from pool");
-    let lat = query_info.lat;
-    let lng = query_info.lng;
-    let subcategory_ids: Result<Vec<i32>, _> = query_info
-        .subcategory_ids
-        .split(',')
-        .map(|s| s.parse::<i32>())
-        .collect();
-
-    let subcategory_ids = match subcategory_ids {
-        Ok(ids) => ids,
-        Err(_) => return HttpResponse::BadRequest().body("Invalid subcategory IDs"),
+    let filter = match query.into_filter() {
+        Ok(filter) => filter,
+        Err(message) => return HttpResponse::BadRequest().body(message),
     };
 
-    match professional_profile_db::search_services(subcategory_ids, lat, lng, &mut conn).await {
-        Ok(user_profiles) => {
-            if user_profiles.is_empty() {
-                // If the list is empty, return 200 OK with an empty list
-                HttpResponse::Ok().json(user_profiles)
-            } else {
-                // If there are user profiles, return them with a 200 OK
-                HttpResponse::Ok().json(user_profiles)
-            }
-        }
+    match professional_profile_db::search_services(&filter, &mut conn).await {
+        Ok(page) => HttpResponse::Ok().json(page),
         Err(e) => {
             // Log or handle the error more appropriately here
             println!("Error occurred: {:?}", e);
@@ -73,31 +228,199 @@ pub async fn get_professional_profile_handler(
     }
 }
 
-pub async fn get_profile_by_id(
-    req: HttpRequest,
+pub async fn create_profile(
+    user: ProfessionalUser,
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    profile_dto: web::Json<CreateProfessionalProfileDTO>,
+) -> impl Responder {
+    match professional_profile_db::create_profile(db_pool, user.uid, profile_dto.into_inner()).await
+    {
+        Ok(profile) => HttpResponse::Created().json(profile),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn get_own_profiles(
+    user: ProfessionalUser,
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+) -> impl Responder {
+    match professional_profile_db::get_own_profiles(db_pool, user.uid).await {
+        Ok(profiles) => HttpResponse::Ok().json(profiles),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn update_profile(
+    user: ProfessionalUser,
     profile_id: web::Path<i32>,
     db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    profile_dto: web::Json<UpdateProfessionalProfileDTO>,
 ) -> impl Responder {
-    // Verify the token
-    let token = _extract_token_from_auth_header(req.headers().get("Authorization"));
+    match professional_profile_db::update_profile(
+        db_pool,
+        profile_id.into_inner(),
+        user.uid,
+        profile_dto.into_inner(),
+    )
+    .await
+    {
+        Ok(profile) => HttpResponse::Ok().json(profile),
+        Err(e) => e.error_response(),
+    }
+}
 
-    match token {
-        Some(t) => {
-            if let Ok(is_valid) = verify_token(&t).await {
-                if !is_valid {
-                    // If the token is not valid, return an unauthorized response
-                    return HttpResponse::Unauthorized().body("Invalid token");
-                }
-            } else {
-                // If token verification failed due to some error
-                return HttpResponse::InternalServerError().finish();
-            }
-        }
-        None => {
-            // If no token is present in the request
-            return HttpResponse::Unauthorized().body("No token");
-        }
+pub async fn set_profile_address(
+    user: ProfessionalUser,
+    profile_id: web::Path<i32>,
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    address_dto: web::Json<AddressDTO>,
+) -> impl Responder {
+    match professional_profile_db::set_profile_address(
+        db_pool,
+        profile_id.into_inner(),
+        user.uid,
+        address_dto.into_inner(),
+    )
+    .await
+    {
+        Ok(address) => HttpResponse::Ok().json(address),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn get_profile_offerings(
+    _user: AuthenticatedUser,
+    profile_id: web::Path<i32>,
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+) -> impl Responder {
+    match professional_profile_db::get_offerings(db_pool, profile_id.into_inner()).await {
+        Ok(offerings) => HttpResponse::Ok().json(offerings),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn create_offering(
+    user: ProfessionalUser,
+    profile_id: web::Path<i32>,
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    offering_dto: web::Json<ServiceOfferingInputDTO>,
+) -> impl Responder {
+    match professional_profile_db::create_offering(
+        db_pool,
+        profile_id.into_inner(),
+        user.uid,
+        offering_dto.into_inner(),
+    )
+    .await
+    {
+        Ok(offering) => HttpResponse::Created().json(offering),
+        Err(e) => e.error_response(),
     }
+}
+
+pub async fn update_offering(
+    user: ProfessionalUser,
+    path: web::Path<(i32, i32)>,
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    offering_dto: web::Json<ServiceOfferingInputDTO>,
+) -> impl Responder {
+    let (profile_id, offering_id) = path.into_inner();
+
+    match professional_profile_db::update_offering(
+        db_pool,
+        profile_id,
+        offering_id,
+        user.uid,
+        offering_dto.into_inner(),
+    )
+    .await
+    {
+        Ok(offering) => HttpResponse::Ok().json(offering),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn delete_offering(
+    user: ProfessionalUser,
+    path: web::Path<(i32, i32)>,
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+) -> impl Responder {
+    let (profile_id, offering_id) = path.into_inner();
+
+    match professional_profile_db::delete_offering(db_pool, profile_id, offering_id, user.uid).await
+    {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn get_business_hours(
+    _user: AuthenticatedUser,
+    profile_id: web::Path<i32>,
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+) -> impl Responder {
+    match business_hours_db::get_business_hours(db_pool, profile_id.into_inner()).await {
+        Ok(hours) => HttpResponse::Ok().json(hours),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn set_weekly_hours(
+    user: ProfessionalUser,
+    profile_id: web::Path<i32>,
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    weekly_hours: web::Json<Vec<WeeklyHoursDTO>>,
+) -> impl Responder {
+    match business_hours_db::set_weekly_hours(
+        db_pool,
+        profile_id.into_inner(),
+        user.uid,
+        weekly_hours.into_inner(),
+    )
+    .await
+    {
+        Ok(hours) => HttpResponse::Ok().json(hours),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn create_hours_exception(
+    user: ProfessionalUser,
+    profile_id: web::Path<i32>,
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+    exception_dto: web::Json<BusinessHourExceptionInputDTO>,
+) -> impl Responder {
+    match business_hours_db::create_exception(
+        db_pool,
+        profile_id.into_inner(),
+        user.uid,
+        exception_dto.into_inner(),
+    )
+    .await
+    {
+        Ok(exception) => HttpResponse::Created().json(exception),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn delete_hours_exception(
+    user: ProfessionalUser,
+    path: web::Path<(i32, i32)>,
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+) -> impl Responder {
+    let (profile_id, exception_id) = path.into_inner();
+
+    match business_hours_db::delete_exception(db_pool, profile_id, exception_id, user.uid).await {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn get_profile_by_id(
+    _user: AuthenticatedUser,
+    profile_id: web::Path<i32>,
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+) -> impl Responder {
     let mut conn = db_pool
         .get()
         .expect("Failed to get DB connection from pool");
@@ -107,48 +430,141 @@ pub async fn get_profile_by_id(
         Err(_) => HttpResponse::NotFound().finish(),
     }
 }
-fn _extract_token_from_auth_header(auth_header: Option<&HeaderValue>) -> Option<String> {
-    // Extract the Bearer token from the Authorization header
-    auth_header?
-        .to_str()
-        .ok()?
-        .split_whitespace()
-        .nth(1)
-        .map(String::from)
-}
 pub async fn book_service_handler(
-    req: HttpRequest,
+    user: CustomerUser,
     db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
     booking_dto: web::Json<BookingDTO>,
     profile_id: web::Path<i32>,
 ) -> impl Responder {
-    let token = req
-        .headers()
-        .get("Authorization")
-        .and_then(|h| h.to_str().ok())
-        .and_then(|auth_header| auth_header.split_whitespace().nth(1)); // Assuming "Bearer <token>"
-
-    match token {
-        Some(t) => match verify_token(&t).await {
-            Ok(is_valid) if is_valid => match extract_uid_from_firebase_token(&t).await {
-                Ok(user_uid) => {
-                    match booking_db::place_booking(
-                        db_pool.clone(),
-                        user_uid,
-                        booking_dto.into_inner(),
-                        profile_id.into_inner(),
-                    )
-                    .await
-                    {
-                        Ok(booking) => HttpResponse::Ok().json(booking),
-                        Err(_) => HttpResponse::InternalServerError().finish(),
-                    }
-                }
-                Err(_) => HttpResponse::Unauthorized().body("Invalid user"),
-            },
-            Ok(_) => HttpResponse::Unauthorized().body("Invalid token"),
-            Err(_) => HttpResponse::InternalServerError().finish(),
-        },
-        None => HttpResponse::Unauthorized().body("No token"),
+    match booking_db::place_booking(
+        db_pool,
+        user.uid,
+        booking_dto.into_inner(),
+        profile_id.into_inner(),
+    )
+    .await
+    {
+        Ok(booking) => HttpResponse::Ok().json(booking),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn get_profile_slots(
+    _user: AuthenticatedUser,
+    profile_id: web::Path<i32>,
+    query: web::Query<SlotsQuery>,
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+) -> impl Responder {
+    let query = query.into_inner();
+    let (from, to) = match (
+        booking_db::parse_timestamp(&Some(query.from)),
+        booking_db::parse_timestamp(&Some(query.to)),
+    ) {
+        (Ok(Some(from)), Ok(Some(to))) => (from, to),
+        (Err(e), _) | (_, Err(e)) => return e.error_response(),
+        _ => return BookingError::InvalidTimeRange.error_response(),
+    };
+    if to <= from {
+        return BookingError::InvalidTimeRange.error_response();
+    }
+    if to - from > Duration::days(MAX_SLOT_RANGE_DAYS) {
+        return BookingError::InvalidQuery(format!(
+            "range must not exceed {} days",
+            MAX_SLOT_RANGE_DAYS
+        ))
+        .error_response();
+    }
+
+    let minutes = query.duration.unwrap_or(DEFAULT_SLOT_MINUTES);
+    if !(MIN_SLOT_MINUTES..=MAX_SLOT_MINUTES).contains(&minutes) {
+        return BookingError::InvalidQuery(format!(
+            "duration must be between {} and {} minutes",
+            MIN_SLOT_MINUTES, MAX_SLOT_MINUTES
+        ))
+        .error_response();
+    }
+
+    // Slots that already started cannot be booked any more.
+    let from = from.max(chrono::Utc::now());
+
+    match booking_db::get_open_slots(
+        db_pool.clone(),
+        profile_id.into_inner(),
+        from,
+        to,
+        Duration::minutes(minutes),
+    )
+    .await
+    {
+        Ok(slots) => HttpResponse::Ok().json(slots),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn get_profile_reviews(
+    _user: AuthenticatedUser,
+    profile_id: web::Path<i32>,
+    query: web::Query<ReviewsQuery>,
+    db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
+) -> impl Responder {
+    let filter = match query.into_inner().into_filter() {
+        Ok(filter) => filter,
+        Err(e) => return e.error_response(),
+    };
+
+    match review_db::get_profile_reviews(db_pool, profile_id.into_inner(), filter).await {
+        Ok(page) => HttpResponse::Ok().json(page),
+        Err(e) => e.error_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search(query: &str) -> Result<ProfileSearchFilter, String> {
+        web::Query::<ProfessionalProfileQuery>::from_query(query)
+            .unwrap()
+            .into_inner()
+            .into_filter()
+    }
+
+    #[test]
+    fn search_queries_apply_defaults() {
+        let filter = search("subcategory_ids=3,%204&lat=52.5&lng=13.4").unwrap();
+        assert_eq!(filter.subcategory_ids, Some(vec![3, 4]));
+        assert_eq!(filter.radius_km, DEFAULT_SEARCH_RADIUS_KM);
+        assert!(!filter.open_now);
+        assert_eq!(filter.limit, DEFAULT_PAGE_SIZE);
+
+        let filter = search(
+            "subcategory_ids=3&lat=52.5&lng=13.4&radius=20&open_now=true&min_rating=4\
+             &remote_available=true&min_price=10&max_price=50&limit=1000",
+        )
+        .unwrap();
+        assert_eq!(filter.radius_km, 20.0);
+        assert!(filter.open_now);
+        assert_eq!(filter.min_rating, Some(4.0));
+        assert_eq!(filter.remote_available, Some(true));
+        assert_eq!(filter.delivery_enabled, None);
+        assert_eq!(
+            (filter.min_price, filter.max_price),
+            (Some(10.0), Some(50.0))
+        );
+        assert_eq!(filter.limit, MAX_PAGE_SIZE);
+    }
+
+    #[test]
+    fn search_queries_reject_out_of_range_filters() {
+        for query in [
+            "subcategory_ids=3,x&lat=52.5&lng=13.4",
+            "subcategory_ids=3&lat=91&lng=13.4",
+            "subcategory_ids=3&lat=52.5&lng=13.4&radius=0",
+            "subcategory_ids=3&lat=52.5&lng=13.4&radius=101",
+            "subcategory_ids=3&lat=52.5&lng=13.4&min_rating=6",
+            "subcategory_ids=3&lat=52.5&lng=13.4&min_price=50&max_price=10",
+        ] {
+            assert!(search(query).is_err(), "{}", query);
+        }
     }
 }
@@ -13,6 +13,10 @@ pub fn professional_routes(cfg: &mut web::ServiceConfig) {
             .route(
                 "/register",
                 web::post().to(professional_service::register_professional),
+            )
+            .route(
+                "/me",
+                web::put().to(professional_service::rename_professional),
             ),
     );
 }
@@ -1,9 +1,13 @@
 use crate::dal::professional_db;
 use crate::db::Pool;
+use crate::errors::professional_profile_errors::ProfessionalProfileError;
+use crate::middleware::authenticated_user::ProfessionalUser;
+use crate::models::dtos::professional_profile_edit_dto::RenameProfessionalDTO;
 use crate::models::professional_aggregate::new_professional::RegistrationData;
-use crate::services::firebase_service::create_firebase_user;
-use actix_web::{web, HttpResponse, Responder};
+use crate::services::identity_provider::IdentityProvider;
+use actix_web::{web, HttpResponse, Responder, ResponseError};
 use actix_web::web::block;
+use diesel::result::Error;
 
 pub async fn get_professional_handler(professional_email: web::Path<String>, db_pool: web::Data<Pool>) -> impl Responder {
     let mut conn = db_pool.get().expect("Failed to get DB connection from pool");
@@ -14,7 +18,7 @@ pub async fn get_professional_handler(professional_email: web::Path<String>, db_
     }
 }
 
-pub async fn register_professional(data: web::Json<RegistrationData>, db_pool: web::Data<Pool>) -> impl Responder {
+pub async fn register_professional(data: web::Json<RegistrationData>, db_pool: web::Data<Pool>, identity: web::Data<dyn IdentityProvider>) -> impl Responder {
     let mut conn = db_pool.get().expect("Failed to get DB connection from pool");
 
     // Extract data
@@ -23,10 +27,10 @@ pub async fn register_professional(data: web::Json<RegistrationData>, db_pool: w
 
 
     // Interact with Firebase to create a new user and get UID
-    match create_firebase_user(email, password).await {
-        Ok(firebase_response) => {
+    match identity.create_user(email, password).await {
+        Ok(new_identity) => {
             match block(move || {
-                professional_db::save_professional_to_database(&mut conn, &data.name, &firebase_response.email, &firebase_response.localId)
+                professional_db::save_professional_to_database(&mut conn, &data.name, &new_identity.email, &new_identity.uid)
             }).await {
                 Ok(_) => HttpResponse::Ok().body("Professional registered successfully"),
                 Err(err) => HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
@@ -35,3 +39,32 @@ pub async fn register_professional(data: web::Json<RegistrationData>, db_pool: w
         Err(err) => return HttpResponse::BadRequest().body(format!("Firebase error: {}", err)),
     }
 }
+
+/// Renames the calling professional; the name shown on their profiles follows.
+pub async fn rename_professional(
+    user: ProfessionalUser,
+    data: web::Json<RenameProfessionalDTO>,
+    db_pool: web::Data<Pool>,
+) -> impl Responder {
+    let new_name = data.into_inner().name.trim().to_string();
+    if new_name.is_empty() {
+        return HttpResponse::BadRequest().body("Name must not be empty");
+    }
+
+    let result = block(move || -> Result<(), ProfessionalProfileError> {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| ProfessionalProfileError::DatabasePoolError(e.to_string()))?;
+        professional_db::rename_professional(&mut conn, &user.uid, &new_name).map_err(|e| match e {
+            Error::NotFound => ProfessionalProfileError::NotProfessional,
+            e => e.into(),
+        })
+    })
+    .await
+    .map_err(|e| ProfessionalProfileError::BlockingError(format!("Blocking error: {}", e)));
+
+    match result {
+        Ok(Ok(())) => HttpResponse::NoContent().finish(),
+        Ok(Err(e)) | Err(e) => e.error_response(),
+    }
+}
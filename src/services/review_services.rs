@@ -0,0 +1,2 @@
+pub mod review_endpoints;
+pub mod review_service;
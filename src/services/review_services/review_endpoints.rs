@@ -0,0 +1,38 @@
+use super::review_service;
+use actix_web::web;
+
+pub fn review_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/reviews")
+            .route("", web::post().to(review_service::create_review))
+            .route("/{review_id}", web::put().to(review_service::update_review))
+            .route(
+                "/{review_id}",
+                web::delete().to(review_service::delete_review),
+            )
+            .route(
+                "/{review_id}/reply",
+                web::put().to(review_service::reply_to_review),
+            )
+            .route(
+                "/{review_id}/reply",
+                web::delete().to(review_service::delete_reply),
+            )
+            .route(
+                "/{review_id}/report",
+                web::post().to(review_service::report_review),
+            ),
+    )
+    .service(
+        web::scope("/moderation/reports")
+            .route("", web::get().to(review_service::get_moderation_queue))
+            .route(
+                "/{report_id}/hide",
+                web::post().to(review_service::hide_reported_review),
+            )
+            .route(
+                "/{report_id}/resolve",
+                web::post().to(review_service::resolve_report),
+            ),
+    );
+}
@@ -0,0 +1,157 @@
+use crate::dal::review_db;
+use crate::middleware::authenticated_user::{AdminUser, AuthenticatedUser};
+use crate::models::dtos::review_dto::{
+    CreateReviewDTO, ResolveReportDTO, ReviewReplyDTO, ReviewReportDTO, UpdateReviewDTO,
+};
+use actix_web::{web, HttpResponse, Responder, ResponseError};
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool};
+use serde::Deserialize;
+
+type DbPool = web::Data<Pool<ConnectionManager<PgConnection>>>;
+
+const DEFAULT_PAGE_SIZE: i64 = 20;
+const MAX_PAGE_SIZE: i64 = 100;
+
+#[derive(Deserialize)]
+pub struct PageQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+pub async fn create_review(
+    user: AuthenticatedUser,
+    db_pool: DbPool,
+    review_dto: web::Json<CreateReviewDTO>,
+) -> impl Responder {
+    match review_db::create_review(db_pool, user.uid, review_dto.into_inner()).await {
+        Ok(review) => HttpResponse::Created().json(review),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn update_review(
+    user: AuthenticatedUser,
+    db_pool: DbPool,
+    review_id: web::Path<i32>,
+    review_dto: web::Json<UpdateReviewDTO>,
+) -> impl Responder {
+    match review_db::update_review(
+        db_pool,
+        review_id.into_inner(),
+        user.uid,
+        review_dto.into_inner(),
+    )
+    .await
+    {
+        Ok(review) => HttpResponse::Ok().json(review),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn delete_review(
+    user: AuthenticatedUser,
+    db_pool: DbPool,
+    review_id: web::Path<i32>,
+) -> impl Responder {
+    match review_db::delete_review(db_pool, review_id.into_inner(), user.uid).await {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn reply_to_review(
+    user: AuthenticatedUser,
+    db_pool: DbPool,
+    review_id: web::Path<i32>,
+    reply_dto: web::Json<ReviewReplyDTO>,
+) -> impl Responder {
+    match review_db::reply_to_review(
+        db_pool,
+        review_id.into_inner(),
+        user.uid,
+        reply_dto.into_inner(),
+    )
+    .await
+    {
+        Ok(reply) => HttpResponse::Ok().json(reply),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn delete_reply(
+    user: AuthenticatedUser,
+    db_pool: DbPool,
+    review_id: web::Path<i32>,
+) -> impl Responder {
+    match review_db::delete_reply(db_pool, review_id.into_inner(), user.uid).await {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn report_review(
+    user: AuthenticatedUser,
+    db_pool: DbPool,
+    review_id: web::Path<i32>,
+    report_dto: web::Json<ReviewReportDTO>,
+) -> impl Responder {
+    match review_db::report_review(
+        db_pool,
+        review_id.into_inner(),
+        user.uid,
+        report_dto.into_inner(),
+    )
+    .await
+    {
+        Ok(report) => HttpResponse::Created().json(report),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn get_moderation_queue(
+    _admin: AdminUser,
+    db_pool: DbPool,
+    query: web::Query<PageQuery>,
+) -> impl Responder {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    let offset = query.offset.unwrap_or(0).max(0);
+
+    match review_db::get_moderation_queue(db_pool, limit, offset).await {
+        Ok(reports) => HttpResponse::Ok().json(reports),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn hide_reported_review(
+    _admin: AdminUser,
+    db_pool: DbPool,
+    report_id: web::Path<i32>,
+) -> impl Responder {
+    match review_db::hide_reported_review(db_pool, report_id.into_inner()).await {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn resolve_report(
+    admin: AdminUser,
+    db_pool: DbPool,
+    report_id: web::Path<i32>,
+    resolve_dto: web::Json<ResolveReportDTO>,
+) -> impl Responder {
+    match review_db::resolve_report(
+        db_pool,
+        report_id.into_inner(),
+        admin.uid,
+        resolve_dto.into_inner().action,
+    )
+    .await
+    {
+        Ok(report) => HttpResponse::Ok().json(report),
+        Err(e) => e.error_response(),
+    }
+}
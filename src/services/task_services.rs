@@ -1,2 +1,3 @@
 pub mod task_endpoints;
+pub mod task_expiry;
 pub mod task_service;
@@ -4,6 +4,28 @@ use super::task_service;
 
 pub fn task_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
-        web::scope("/task").route("/place", web::post().to(task_service::place_task_handler)),
+        web::scope("/task")
+            .route("/place", web::post().to(task_service::place_task_handler))
+            .route("/mine", web::get().to(task_service::get_my_tasks))
+            .route("/browse", web::get().to(task_service::browse_tasks))
+            .route("/{task_id}", web::put().to(task_service::update_task))
+            .route("/{task_id}", web::delete().to(task_service::delete_task))
+            .route("/{task_id}/close", web::post().to(task_service::close_task))
+            .route(
+                "/{task_id}/proposals",
+                web::post().to(task_service::submit_proposal),
+            )
+            .route(
+                "/{task_id}/proposals",
+                web::get().to(task_service::get_task_proposals),
+            )
+            .route(
+                "/{task_id}/proposals/{proposal_id}/accept",
+                web::post().to(task_service::accept_proposal),
+            )
+            .route(
+                "/{task_id}/proposals/{proposal_id}/reject",
+                web::post().to(task_service::reject_proposal),
+            ),
     );
 }
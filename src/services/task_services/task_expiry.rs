@@ -0,0 +1,24 @@
+use crate::dal::task_db;
+use crate::db::Pool;
+use actix_web::web;
+use log::{error, info};
+use std::time::Duration;
+
+/// How often open tasks whose date has passed are marked as expired.
+const TASK_EXPIRY_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Marks past-due tasks as expired right away and then every
+/// `TASK_EXPIRY_INTERVAL`, so read endpoints never have to.
+pub fn spawn(db_pool: web::Data<Pool>) {
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(TASK_EXPIRY_INTERVAL);
+        loop {
+            interval.tick().await;
+            match task_db::expire_stale_tasks(db_pool.clone()).await {
+                Ok(0) => {}
+                Ok(expired) => info!("Expired {} past-due tasks", expired),
+                Err(e) => error!("Task expiry failed: {:?}", e),
+            }
+        }
+    });
+}
@@ -1,37 +1,350 @@
-use crate::dal::task_db;
-use crate::models::dtos::task_dto::TaskDto;
-use crate::services::firebase_service::{extract_uid_from_firebase_token, verify_token};
-use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use crate::dal::task_db::{self, Location, TaskFilter};
+use crate::errors::task_errors::TaskError;
+use crate::middleware::authenticated_user::{AuthenticatedUser, CustomerUser, ProfessionalUser};
+use crate::models::dtos::task_dto::{TaskDto, TaskProposalDTO};
+use actix_web::{web, HttpResponse, Responder, ResponseError};
 use diesel::r2d2::{ConnectionManager, Pool};
 use diesel::PgConnection;
+use serde::Deserialize;
+
+type DbPool = web::Data<Pool<ConnectionManager<PgConnection>>>;
+
+const DEFAULT_PAGE_SIZE: i64 = 20;
+const MAX_PAGE_SIZE: i64 = 100;
+
+#[derive(Deserialize)]
+pub struct PageQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct BrowseTaskQuery {
+    pub category_id: Option<i32>,
+    pub min_price: Option<f64>,
+    pub max_price: Option<f64>,
+    /// Scheduled date as `YYYY-MM-DD`.
+    pub date: Option<String>,
+    pub lat: Option<f64>,
+    pub lng: Option<f64>,
+    pub radius_km: Option<f64>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl BrowseTaskQuery {
+    fn into_filter(self) -> Result<TaskFilter, TaskError> {
+        let location = match (self.lat, self.lng) {
+            (Some(lat), Some(lng)) => Some(Location {
+                lat,
+                lng,
+                radius_km: self.radius_km,
+            }),
+            (None, None) if self.radius_km.is_none() => None,
+            _ => {
+                return Err(TaskError::InvalidQuery(
+                    "lat and lng must be given together, and are required for radius_km"
+                        .to_string(),
+                ))
+            }
+        };
+        if self.radius_km.is_some_and(|radius| radius <= 0.0) {
+            return Err(TaskError::InvalidQuery(
+                "radius_km must be positive".to_string(),
+            ));
+        }
+
+        Ok(TaskFilter {
+            category_id: self.category_id,
+            min_price: self.min_price,
+            max_price: self.max_price,
+            scheduled_date: task_db::parse_date(&self.date)?,
+            location,
+            limit: page_limit(self.limit),
+            offset: self.offset.unwrap_or(0).max(0),
+        })
+    }
+}
+
+fn page_limit(limit: Option<i64>) -> i64 {
+    limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+}
 
 pub async fn place_task_handler(
-    req: HttpRequest,
+    user: CustomerUser,
     db_pool: web::Data<Pool<ConnectionManager<PgConnection>>>,
     task_dto: web::Json<TaskDto>,
 ) -> impl Responder {
-    let token = req
-        .headers()
-        .get("Authorization")
-        .and_then(|h| h.to_str().ok())
-        .and_then(|auth_header| auth_header.split_whitespace().nth(1)); // Assuming "Bearer <token>"
-
-    match token {
-        Some(t) => match verify_token(&t).await {
-            Ok(is_valid) if is_valid => match extract_uid_from_firebase_token(&t).await {
-                Ok(user_uid) => {
-                    match task_db::place_task(db_pool.clone(), user_uid, task_dto.into_inner())
-                        .await
-                    {
-                        Ok(task) => HttpResponse::Ok().json(task),
-                        Err(_) => HttpResponse::InternalServerError().finish(),
-                    }
-                }
-                Err(_) => HttpResponse::Unauthorized().body("Invalid user"),
-            },
-            Ok(_) => HttpResponse::Unauthorized().body("Invalid token"),
-            Err(_) => HttpResponse::InternalServerError().finish(),
-        },
-        None => HttpResponse::Unauthorized().body("No token"),
+    match task_db::place_task(db_pool, user.uid, task_dto.into_inner()).await {
+        Ok(task) => HttpResponse::Ok().json(task),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn get_my_tasks(
+    user: AuthenticatedUser,
+    db_pool: DbPool,
+    query: web::Query<PageQuery>,
+) -> impl Responder {
+    let query = query.into_inner();
+
+    match task_db::get_tasks_for_user(
+        db_pool,
+        user.uid,
+        page_limit(query.limit),
+        query.offset.unwrap_or(0).max(0),
+    )
+    .await
+    {
+        Ok(tasks) => HttpResponse::Ok().json(tasks),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn browse_tasks(
+    user: ProfessionalUser,
+    db_pool: DbPool,
+    query: web::Query<BrowseTaskQuery>,
+) -> impl Responder {
+    let filter = match query.into_inner().into_filter() {
+        Ok(filter) => filter,
+        Err(e) => return e.error_response(),
+    };
+
+    match task_db::browse_tasks(db_pool, user.uid, filter).await {
+        Ok(tasks) => HttpResponse::Ok().json(tasks),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn update_task(
+    user: CustomerUser,
+    db_pool: DbPool,
+    task_id: web::Path<i32>,
+    task_dto: web::Json<TaskDto>,
+) -> impl Responder {
+    match task_db::update_task(
+        db_pool,
+        task_id.into_inner(),
+        user.uid,
+        task_dto.into_inner(),
+    )
+    .await
+    {
+        Ok(task) => HttpResponse::Ok().json(task),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn delete_task(
+    user: CustomerUser,
+    db_pool: DbPool,
+    task_id: web::Path<i32>,
+) -> impl Responder {
+    match task_db::delete_task(db_pool, task_id.into_inner(), user.uid).await {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn close_task(
+    user: CustomerUser,
+    db_pool: DbPool,
+    task_id: web::Path<i32>,
+) -> impl Responder {
+    match task_db::close_task(db_pool, task_id.into_inner(), user.uid).await {
+        Ok(task) => HttpResponse::Ok().json(task),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn submit_proposal(
+    user: ProfessionalUser,
+    db_pool: DbPool,
+    task_id: web::Path<i32>,
+    proposal_dto: web::Json<TaskProposalDTO>,
+) -> impl Responder {
+    match task_db::submit_proposal(
+        db_pool,
+        task_id.into_inner(),
+        user.uid,
+        proposal_dto.into_inner(),
+    )
+    .await
+    {
+        Ok(proposal) => HttpResponse::Created().json(proposal),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn get_task_proposals(
+    user: AuthenticatedUser,
+    db_pool: DbPool,
+    task_id: web::Path<i32>,
+) -> impl Responder {
+    match task_db::get_task_proposals(db_pool, task_id.into_inner(), user.uid).await {
+        Ok(proposals) => HttpResponse::Ok().json(proposals),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn accept_proposal(
+    user: CustomerUser,
+    db_pool: DbPool,
+    path: web::Path<(i32, i32)>,
+) -> impl Responder {
+    let (task_id, proposal_id) = path.into_inner();
+
+    match task_db::accept_proposal(db_pool, task_id, proposal_id, user.uid).await {
+        Ok(booking) => HttpResponse::Ok().json(booking),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn reject_proposal(
+    user: CustomerUser,
+    db_pool: DbPool,
+    path: web::Path<(i32, i32)>,
+) -> impl Responder {
+    let (task_id, proposal_id) = path.into_inner();
+
+    match task_db::reject_proposal(db_pool, task_id, proposal_id, user.uid).await {
+        Ok(proposal) => HttpResponse::Ok().json(proposal),
+        Err(e) => e.error_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{
+        insert_fixture, request_as, status_of, test_app, test_pool, CUSTOMER, STRANGER,
+    };
+    use actix_web::http::{Method, StatusCode};
+
+    fn browse_query(lat: Option<f64>, lng: Option<f64>, radius_km: Option<f64>) -> BrowseTaskQuery {
+        BrowseTaskQuery {
+            category_id: None,
+            min_price: None,
+            max_price: None,
+            date: None,
+            lat,
+            lng,
+            radius_km,
+            limit: Some(0),
+            offset: Some(-1),
+        }
+    }
+
+    #[test]
+    fn browse_queries_need_a_complete_location() {
+        let filter = browse_query(Some(52.5), Some(13.4), Some(10.0))
+            .into_filter()
+            .unwrap();
+        let location = filter.location.unwrap();
+        assert_eq!((location.lat, location.lng), (52.5, 13.4));
+        assert_eq!(location.radius_km, Some(10.0));
+        assert_eq!((filter.limit, filter.offset), (1, 0));
+
+        assert!(browse_query(None, None, None)
+            .into_filter()
+            .unwrap()
+            .location
+            .is_none());
+        for invalid in [
+            browse_query(Some(52.5), None, None),
+            browse_query(None, None, Some(10.0)),
+            browse_query(Some(52.5), Some(13.4), Some(0.0)),
+        ] {
+            assert!(matches!(
+                invalid.into_filter(),
+                Err(TaskError::InvalidQuery(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn page_limits_are_clamped() {
+        assert_eq!(page_limit(None), DEFAULT_PAGE_SIZE);
+        assert_eq!(page_limit(Some(1_000)), MAX_PAGE_SIZE);
+        assert_eq!(page_limit(Some(-5)), 1);
+    }
+
+    #[actix_web::test]
+    async fn task_endpoints_require_the_owner() {
+        let Some(pool) = test_pool() else {
+            return;
+        };
+        let fixture = insert_fixture(&pool);
+        let app = test_app!(pool);
+
+        let task = format!("/task/{}", fixture.task_id);
+        let proposals = format!("/task/{}/proposals", fixture.task_id);
+        let update = serde_json::json!({
+            "title": "Fix the kitchen sink",
+            "category_id": 1,
+            "is_flexible_timing": true,
+        });
+
+        assert_eq!(
+            status_of!(
+                app,
+                request_as(Method::PUT, &task, STRANGER).set_json(update)
+            ),
+            StatusCode::FORBIDDEN
+        );
+        for (method, uri) in [
+            (Method::GET, proposals.clone()),
+            (Method::POST, format!("{}/close", task)),
+            (Method::POST, format!("{}/1/accept", proposals)),
+            (Method::POST, format!("{}/1/reject", proposals)),
+            (Method::DELETE, task.clone()),
+        ] {
+            assert_eq!(
+                status_of!(app, request_as(method.clone(), &uri, STRANGER)),
+                StatusCode::FORBIDDEN,
+                "{} {}",
+                method,
+                uri
+            );
+        }
+
+        assert_eq!(
+            status_of!(app, request_as(Method::GET, &proposals, CUSTOMER)),
+            StatusCode::OK
+        );
+    }
+
+    #[actix_web::test]
+    async fn placed_tasks_need_valid_prices() {
+        let Some(pool) = test_pool() else {
+            return;
+        };
+        let fixture = insert_fixture(&pool);
+        let app = test_app!(pool);
+
+        for (min_price, max_price, expected) in [
+            (50.0, 10.0, StatusCode::BAD_REQUEST),
+            (-5.0, 10.0, StatusCode::BAD_REQUEST),
+            (10.0, 50.0, StatusCode::OK),
+        ] {
+            let task = serde_json::json!({
+                "title": "Paint the fence",
+                "category_id": fixture.category_id,
+                "is_flexible_timing": true,
+                "min_price": min_price,
+                "max_price": max_price,
+            });
+            assert_eq!(
+                status_of!(
+                    app,
+                    request_as(Method::POST, "/task/place", CUSTOMER).set_json(&task)
+                ),
+                expected,
+                "{} to {}",
+                min_price,
+                max_price
+            );
+        }
     }
 }
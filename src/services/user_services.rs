@@ -1,2 +1,3 @@
+pub mod account_service;
 pub mod user_service;
 pub mod user_endpoints;
\ No newline at end of file
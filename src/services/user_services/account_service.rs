@@ -0,0 +1,402 @@
+use crate::dal::account_db;
+use crate::db::Pool;
+use crate::errors::account_errors::AccountError;
+use crate::errors::firebase_errors::FirebaseServiceError;
+use crate::middleware::authenticated_user::{bearer_token, AuthenticatedUser};
+use crate::models::dtos::account_dto::{PasswordResetDTO, ProfileUpdateDTO};
+use crate::services::identity_provider::IdentityProvider;
+use actix_web::{
+    http::header::AUTHORIZATION, web, HttpRequest, HttpResponse, Responder, ResponseError,
+};
+
+pub async fn update_own_profile(
+    user: AuthenticatedUser,
+    update: web::Json<ProfileUpdateDTO>,
+    db_pool: web::Data<Pool>,
+) -> impl Responder {
+    match account_db::update_profile(db_pool, user.uid, update.into_inner()).await {
+        Ok(profile) => HttpResponse::Ok().json(profile),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// Emails a password reset link. Answers 204 whether or not an account uses
+/// the address, so the endpoint cannot be used to probe for accounts.
+pub async fn request_password_reset(
+    data: web::Json<PasswordResetDTO>,
+    identity: web::Data<dyn IdentityProvider>,
+) -> impl Responder {
+    let email = data.email.trim();
+    if !email.contains('@') {
+        return AccountError::InvalidEmail.error_response();
+    }
+
+    match identity.send_password_reset(email).await {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(FirebaseServiceError::FirebaseApiError(message)) if message == "EMAIL_NOT_FOUND" => {
+            HttpResponse::NoContent().finish()
+        }
+        Err(e) => AccountError::from(e).error_response(),
+    }
+}
+
+/// Emails the caller a link to verify their address.
+pub async fn send_email_verification(
+    user: AuthenticatedUser,
+    req: HttpRequest,
+    identity: web::Data<dyn IdentityProvider>,
+) -> impl Responder {
+    let result = async {
+        let (uid, token) = identity_token(&req, identity.get_ref()).await?;
+        if uid != user.uid {
+            return Err(AccountError::IdentityTokenRequired);
+        }
+        identity.send_email_verification(&token).await?;
+        Ok::<_, AccountError>(())
+    }
+    .await;
+
+    match result {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// Deletes the caller's account. The backend data is cleaned up first and
+/// the identity removed last: if the identity provider fails, the user can
+/// still sign in and retry, which would be impossible the other way round.
+/// Accounts marked deleted no longer pass [`AuthenticatedUser`], so retries
+/// are authenticated by the ID token alone.
+pub async fn delete_own_account(
+    req: HttpRequest,
+    db_pool: web::Data<Pool>,
+    identity: web::Data<dyn IdentityProvider>,
+) -> impl Responder {
+    let result = async {
+        let (uid, token) = identity_token(&req, identity.get_ref()).await?;
+        let deletion = account_db::delete_account_data(db_pool, uid).await?;
+
+        match identity.delete_user(&token).await {
+            // Already gone, e.g. when a previous attempt timed out.
+            Err(FirebaseServiceError::FirebaseApiError(message)) if message == "USER_NOT_FOUND" => {
+            }
+            other => other?,
+        }
+        Ok::<_, AccountError>(deletion)
+    }
+    .await;
+
+    match result {
+        Ok(deletion) => HttpResponse::Ok().json(deletion),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// The caller's uid and bearer token, provided it is an ID token of the
+/// identity provider. The provider only acts on its own tokens, so session
+/// tokens are refused here.
+async fn identity_token(
+    req: &HttpRequest,
+    identity: &dyn IdentityProvider,
+) -> Result<(String, String), AccountError> {
+    let token = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(bearer_token)
+        .ok_or(AccountError::IdentityTokenRequired)?;
+
+    match identity.uid_from_token(token).await {
+        Ok(uid) => Ok((uid, token.to_string())),
+        Err(e @ FirebaseServiceError::MissingConfig(_)) => Err(e.into()),
+        _ => Err(AccountError::IdentityTokenRequired),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dal::account_db::DELETED_USER_NAME;
+    use crate::middleware::session_tokens::SessionTokens;
+    use crate::models::booking_aggregate::booking_status::BookingStatus;
+    use crate::models::task_aggregate::task_status::TaskStatus;
+    use crate::models::user_aggregate::role::Role;
+    use crate::schema::schema::{
+        bookings, professional_profiles, professionals, review, task, user_roles, users,
+    };
+    use crate::services::fake_identity_provider::FakeIdentityProvider;
+    use crate::test_support::{
+        booking_request, identity_app, insert_fixture, status_of, test_pool, CUSTOMER,
+        PROFESSIONAL, STRANGER,
+    };
+    use actix_web::http::StatusCode;
+    use actix_web::test;
+    use chrono::Utc;
+    use diesel::prelude::*;
+    use std::sync::Arc;
+
+    #[actix_web::test]
+    async fn users_manage_their_profile_and_email() {
+        let Some(pool) = test_pool() else {
+            return;
+        };
+        let fixture = insert_fixture(&pool);
+        let identity = Arc::new(FakeIdentityProvider::default());
+        let app = identity_app!(pool, identity, None);
+        let bearer = |uid: &str| {
+            (
+                "Authorization",
+                format!("Bearer {}", FakeIdentityProvider::token_for(uid)),
+            )
+        };
+
+        let update = test::TestRequest::put()
+            .uri("/users/me")
+            .insert_header(bearer(CUSTOMER))
+            .set_json(serde_json::json!({ "name": " Renamed ", "image_url": "https://img/me.png" }))
+            .to_request();
+        let profile: serde_json::Value = test::call_and_read_body_json(&app, update).await;
+        assert_eq!(profile["name"], "Renamed");
+        assert_eq!(profile["image_url"], "https://img/me.png");
+
+        let empty = test::TestRequest::put()
+            .uri("/users/me")
+            .insert_header(bearer(CUSTOMER))
+            .set_json(serde_json::json!({}));
+        assert_eq!(status_of!(app, empty), StatusCode::BAD_REQUEST);
+
+        // A professional's new name also shows on their profiles.
+        let rename = test::TestRequest::put()
+            .uri("/users/me")
+            .insert_header(bearer(PROFESSIONAL))
+            .set_json(serde_json::json!({ "name": "Renamed Professional" }));
+        assert_eq!(status_of!(app, rename), StatusCode::OK);
+        let profile_name: String = professional_profiles::table
+            .find(fixture.profile_id)
+            .select(professional_profiles::professional_name)
+            .first(&mut pool.get().unwrap())
+            .unwrap();
+        assert_eq!(profile_name, "Renamed Professional");
+
+        let account = identity
+            .create_user("policy-reset@example.com", "secret123")
+            .await
+            .unwrap();
+        for (email, expected) in [
+            ("policy-reset@example.com", StatusCode::NO_CONTENT),
+            ("policy-unknown@example.com", StatusCode::NO_CONTENT),
+            ("not-an-email", StatusCode::BAD_REQUEST),
+        ] {
+            let reset = test::TestRequest::post()
+                .uri("/users/password-reset")
+                .set_json(serde_json::json!({ "email": email }));
+            assert_eq!(status_of!(app, reset), expected, "{}", email);
+        }
+        let verify = test::TestRequest::post()
+            .uri("/users/me/verify-email")
+            .insert_header(bearer(&account.uid));
+        assert_eq!(status_of!(app, verify), StatusCode::NO_CONTENT);
+
+        assert_eq!(
+            identity.sent_emails(),
+            vec![
+                ("PASSWORD_RESET", "policy-reset@example.com".to_string()),
+                ("VERIFY_EMAIL", "policy-reset@example.com".to_string()),
+            ]
+        );
+    }
+
+    #[actix_web::test]
+    async fn account_deletion_cleans_up_before_removing_the_identity() {
+        let Some(pool) = test_pool() else {
+            return;
+        };
+        let fixture = insert_fixture(&pool);
+        let identity = Arc::new(FakeIdentityProvider::default());
+        let sessions = web::Data::new(SessionTokens::new(&"s".repeat(32)).unwrap());
+        let app = identity_app!(pool, identity, Some(sessions.clone()));
+
+        let registration =
+            test::TestRequest::post()
+                .uri("/users/register")
+                .set_json(serde_json::json!({
+                    "email": "policy-leaving@example.com",
+                    "password": "secret123",
+                    "name": "Leaving User",
+                }));
+        assert_eq!(status_of!(app, registration), StatusCode::OK);
+        let account = identity.account("policy-leaving@example.com").unwrap();
+
+        // Hand the fixture's task, booking and profile to the new user and let
+        // them review the profile.
+        {
+            let conn = &mut pool.get().unwrap();
+            let user_id: i32 = users::table
+                .filter(users::user_uid.eq(&account.uid))
+                .select(users::id)
+                .first(conn)
+                .unwrap();
+            diesel::update(task::table.find(fixture.task_id))
+                .set(task::user_uid.eq(&account.uid))
+                .execute(conn)
+                .unwrap();
+            diesel::update(bookings::table.find(fixture.booking_id))
+                .set(bookings::customer_uid.eq(&account.uid))
+                .execute(conn)
+                .unwrap();
+            diesel::update(professionals::table.filter(professionals::user_uid.eq(PROFESSIONAL)))
+                .set(professionals::user_uid.eq(&account.uid))
+                .execute(conn)
+                .unwrap();
+            diesel::insert_into(user_roles::table)
+                .values((
+                    user_roles::user_uid.eq(&account.uid),
+                    user_roles::role.eq(i32::from(Role::Professional)),
+                ))
+                .execute(conn)
+                .unwrap();
+            diesel::insert_into(review::table)
+                .values((
+                    review::user_id.eq(user_id),
+                    review::professional_profile_id.eq(fixture.profile_id),
+                    review::message.eq("Great"),
+                    review::rate.eq(5.0),
+                    review::user_name.eq("Leaving User"),
+                    review::published_at.eq(Utc::now()),
+                    review::is_hidden.eq(false),
+                ))
+                .execute(conn)
+                .unwrap();
+        }
+
+        // Firebase only deletes accounts for their own ID tokens.
+        let session_token = sessions.issue(&account.uid).unwrap();
+        let with_session = test::TestRequest::delete()
+            .uri("/users/me")
+            .insert_header(("Authorization", format!("Bearer {}", session_token)));
+        assert_eq!(status_of!(app, with_session), StatusCode::UNAUTHORIZED);
+        assert!(identity.account("policy-leaving@example.com").is_some());
+
+        let id_token = FakeIdentityProvider::token_for(&account.uid);
+        let delete = || {
+            test::TestRequest::delete()
+                .uri("/users/me")
+                .insert_header(("Authorization", format!("Bearer {}", id_token)))
+                .to_request()
+        };
+        let deletion: serde_json::Value = test::call_and_read_body_json(&app, delete()).await;
+        assert_eq!(
+            deletion,
+            serde_json::json!({
+                "user_uid": account.uid,
+                "anonymized_reviews": 1,
+                "closed_tasks": 1,
+                "withdrawn_proposals": 0,
+                "cancelled_bookings": 1,
+            })
+        );
+        assert!(identity.account("policy-leaving@example.com").is_none());
+
+        // Retrying after a partial failure finds nothing left to clean up.
+        let retry: serde_json::Value = test::call_and_read_body_json(&app, delete()).await;
+        assert_eq!(retry["cancelled_bookings"], 0);
+
+        // Tokens issued before the deletion no longer get in.
+        for token in [&id_token, &session_token] {
+            let update = test::TestRequest::put()
+                .uri("/users/me")
+                .insert_header(("Authorization", format!("Bearer {}", token)))
+                .set_json(serde_json::json!({ "name": "Back again" }));
+            assert_eq!(status_of!(app, update), StatusCode::UNAUTHORIZED);
+        }
+
+        // The deleted professional's profile cannot be booked anymore.
+        let book = test::TestRequest::post()
+            .uri(&format!("/profiles/{}/book-service", fixture.profile_id))
+            .insert_header((
+                "Authorization",
+                format!("Bearer {}", FakeIdentityProvider::token_for(STRANGER)),
+            ))
+            .set_json(booking_request(&fixture, "2030-01-07T10:00:00Z", None));
+        assert_eq!(status_of!(app, book), StatusCode::NOT_FOUND);
+
+        let conn = &mut pool.get().unwrap();
+        let reviewer: String = review::table
+            .filter(review::professional_profile_id.eq(fixture.profile_id))
+            .select(review::user_name)
+            .first(conn)
+            .unwrap();
+        assert_eq!(reviewer, DELETED_USER_NAME);
+        let task_status: i32 = task::table
+            .find(fixture.task_id)
+            .select(task::status)
+            .first(conn)
+            .unwrap();
+        assert_eq!(task_status, i32::from(TaskStatus::Closed));
+        let booking_status: i32 = bookings::table
+            .find(fixture.booking_id)
+            .select(bookings::status)
+            .first(conn)
+            .unwrap();
+        assert_eq!(booking_status, i32::from(BookingStatus::Cancelled));
+        let (name, email): (String, String) = users::table
+            .filter(users::user_uid.eq(&account.uid))
+            .select((users::name, users::email))
+            .first(conn)
+            .unwrap();
+        assert_eq!(name, DELETED_USER_NAME);
+        assert_ne!(email, "policy-leaving@example.com");
+        let (profile_name, is_active): (String, bool) = professional_profiles::table
+            .find(fixture.profile_id)
+            .select((
+                professional_profiles::professional_name,
+                professional_profiles::is_active,
+            ))
+            .first(conn)
+            .unwrap();
+        assert_eq!(profile_name, DELETED_USER_NAME);
+        assert!(!is_active);
+        let roles: i64 = user_roles::table
+            .filter(user_roles::user_uid.eq(&account.uid))
+            .count()
+            .get_result(conn)
+            .unwrap();
+        assert_eq!(roles, 0);
+    }
+
+    #[actix_web::test]
+    async fn profiles_of_deleted_accounts_are_not_found() {
+        let Some(pool) = test_pool() else {
+            return;
+        };
+        let fixture = insert_fixture(&pool);
+        let identity = Arc::new(FakeIdentityProvider::default());
+        let app = identity_app!(pool, identity, None);
+        let profile = format!("/profiles/{}", fixture.profile_id);
+        let get_as_stranger = |uri: String| {
+            test::TestRequest::get().uri(&uri).insert_header((
+                "Authorization",
+                format!("Bearer {}", FakeIdentityProvider::token_for(STRANGER)),
+            ))
+        };
+
+        for (is_active, expected) in [(true, StatusCode::OK), (false, StatusCode::NOT_FOUND)] {
+            diesel::update(professional_profiles::table.find(fixture.profile_id))
+                .set(professional_profiles::is_active.eq(is_active))
+                .execute(&mut pool.get().unwrap())
+                .unwrap();
+            for uri in [
+                profile.clone(),
+                format!("{}/offerings", profile),
+                format!("{}/reviews", profile),
+            ] {
+                assert_eq!(
+                    status_of!(app, get_as_stranger(uri.clone())),
+                    expected,
+                    "{}",
+                    uri
+                );
+            }
+        }
+    }
+}
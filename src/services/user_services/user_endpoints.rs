@@ -1,15 +1,43 @@
 use actix_web::web;
 
-use super::user_service;
+use super::{account_service, user_service};
 
 pub fn user_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/users")
             // Define user-related endpoints
+            .route(
+                "/me",
+                web::put().to(account_service::update_own_profile),
+            )
+            .route(
+                "/me",
+                web::delete().to(account_service::delete_own_account),
+            )
+            .route(
+                "/me/verify-email",
+                web::post().to(account_service::send_email_verification),
+            )
+            .route(
+                "/password-reset",
+                web::post().to(account_service::request_password_reset),
+            )
             .route(
                 "/{user_email}",
                 web::get().to(user_service::get_user_handler),
             )
-            .route("/register", web::post().to(user_service::register_user)),
+            .route("/register", web::post().to(user_service::register_user))
+            .route(
+                "/{user_uid}/roles",
+                web::get().to(user_service::get_user_roles),
+            )
+            .route(
+                "/{user_uid}/roles",
+                web::post().to(user_service::grant_user_role),
+            )
+            .route(
+                "/{user_uid}/roles/{role}",
+                web::delete().to(user_service::revoke_user_role),
+            ),
     );
 }
@@ -1,9 +1,14 @@
-use crate::dal::user_db;
+use crate::dal::{role_db, user_db};
 use crate::db::Pool;
+use crate::errors::auth_errors::AuthError;
+use crate::errors::role_errors::RoleError;
+use crate::middleware::authenticated_user::{AdminUser, AuthenticatedUser};
+use crate::models::dtos::role_dto::{RoleGrantDTO, UserRolesDTO};
 use crate::models::user_aggregate::new_user::RegistrationData;
-use crate::services::firebase_service::create_firebase_user;
+use crate::models::user_aggregate::role::Role;
+use crate::services::identity_provider::IdentityProvider;
 use actix_web::web::block;
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{web, HttpResponse, Responder, ResponseError};
 
 pub async fn get_user_handler(
     user_email: web::Path<String>,
@@ -26,6 +31,7 @@ pub async fn get_user_handler(
 pub async fn register_user(
     data: web::Json<RegistrationData>,
     db_pool: web::Data<Pool>,
+    identity: web::Data<dyn IdentityProvider>,
 ) -> impl Responder {
     let mut conn = match db_pool.get() {
         Ok(conn) => conn,
@@ -40,14 +46,14 @@ pub async fn register_user(
     let password = &data.password;
 
     // Interact with Firebase to create a new user and get UID
-    match create_firebase_user(email, password).await {
-        Ok(firebase_response) => {
+    match identity.create_user(email, password).await {
+        Ok(new_identity) => {
             match block(move || {
                 user_db::save_user_to_database(
                     &mut conn,
                     &data.name,
-                    &firebase_response.email,
-                    &firebase_response.localId,
+                    &new_identity.email,
+                    &new_identity.uid,
                 )
             })
             .await
@@ -63,3 +69,207 @@ pub async fn register_user(
         Err(err) => return HttpResponse::BadRequest().body(format!("Firebase error: {}", err)),
     }
 }
+
+/// Roles of a user. Users can look up their own roles, admins anyone's.
+pub async fn get_user_roles(
+    user: AuthenticatedUser,
+    user_uid: web::Path<String>,
+    db_pool: web::Data<Pool>,
+) -> impl Responder {
+    let user_uid = user_uid.into_inner();
+    if user.uid == user_uid {
+        return HttpResponse::Ok().json(UserRolesDTO {
+            user_uid,
+            roles: user.roles,
+        });
+    }
+    if !user.has_role(Role::Admin) {
+        return AuthError::AdminRequired.error_response();
+    }
+
+    match role_db::get_roles(db_pool, user_uid.clone()).await {
+        Ok(roles) => HttpResponse::Ok().json(UserRolesDTO { user_uid, roles }),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn grant_user_role(
+    _admin: AdminUser,
+    user_uid: web::Path<String>,
+    grant: web::Json<RoleGrantDTO>,
+    db_pool: web::Data<Pool>,
+) -> impl Responder {
+    let user_uid = user_uid.into_inner();
+
+    match role_db::grant_role(db_pool, user_uid.clone(), grant.into_inner().role).await {
+        Ok(roles) => HttpResponse::Ok().json(UserRolesDTO { user_uid, roles }),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub async fn revoke_user_role(
+    admin: AdminUser,
+    path: web::Path<(String, Role)>,
+    db_pool: web::Data<Pool>,
+) -> impl Responder {
+    let (user_uid, role) = path.into_inner();
+    // Keeps admins from locking themselves out by accident.
+    if user_uid == admin.uid && role == Role::Admin {
+        return RoleError::OwnAdminRole(role).error_response();
+    }
+
+    match role_db::revoke_role(db_pool, user_uid.clone(), role).await {
+        Ok(roles) => HttpResponse::Ok().json(UserRolesDTO { user_uid, roles }),
+        Err(e) => e.error_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::token_verifier::TokenVerifier;
+    use crate::services::fake_identity_provider::FakeIdentityProvider;
+    use crate::services::professional_services::professional_endpoints;
+    use crate::services::user_services::user_endpoints;
+    use crate::test_support::{
+        insert_fixture, request_as, status_of, test_app, test_pool, UidIsToken, ADMIN, CUSTOMER,
+        STRANGER,
+    };
+    use actix_web::http::{Method, StatusCode};
+    use actix_web::{test, App};
+    use std::sync::Arc;
+
+    #[actix_web::test]
+    async fn only_admins_manage_roles() {
+        let Some(pool) = test_pool() else {
+            return;
+        };
+        insert_fixture(&pool);
+        let app = test_app!(pool);
+
+        let roles = format!("/users/{}/roles", CUSTOMER);
+        for (user_uid, expected) in [
+            (CUSTOMER, StatusCode::OK),
+            (ADMIN, StatusCode::OK),
+            (STRANGER, StatusCode::FORBIDDEN),
+        ] {
+            assert_eq!(
+                status_of!(app, request_as(Method::GET, &roles, user_uid)),
+                expected,
+                "GET {} as {}",
+                roles,
+                user_uid
+            );
+        }
+
+        let grant = serde_json::json!({ "role": "professional" });
+        assert_eq!(
+            status_of!(
+                app,
+                request_as(Method::POST, &roles, CUSTOMER).set_json(&grant)
+            ),
+            StatusCode::FORBIDDEN
+        );
+        let granted: serde_json::Value = test::call_and_read_body_json(
+            &app,
+            request_as(Method::POST, &roles, ADMIN)
+                .set_json(&grant)
+                .to_request(),
+        )
+        .await;
+        assert_eq!(
+            granted["roles"],
+            serde_json::json!(["customer", "professional"])
+        );
+
+        let unknown = format!("/users/{}/roles", "policy-nobody");
+        assert_eq!(
+            status_of!(
+                app,
+                request_as(Method::POST, &unknown, ADMIN).set_json(&grant)
+            ),
+            StatusCode::NOT_FOUND
+        );
+
+        let revoke = format!("/users/{}/roles/professional", CUSTOMER);
+        assert_eq!(
+            status_of!(app, request_as(Method::DELETE, &revoke, STRANGER)),
+            StatusCode::FORBIDDEN
+        );
+        let revoked: serde_json::Value = test::call_and_read_body_json(
+            &app,
+            request_as(Method::DELETE, &revoke, ADMIN).to_request(),
+        )
+        .await;
+        assert_eq!(revoked["roles"], serde_json::json!(["customer"]));
+
+        let own_admin = format!("/users/{}/roles/admin", ADMIN);
+        assert_eq!(
+            status_of!(app, request_as(Method::DELETE, &own_admin, ADMIN)),
+            StatusCode::CONFLICT
+        );
+    }
+
+    #[actix_web::test]
+    async fn registration_grants_the_matching_role() {
+        let Some(pool) = test_pool() else {
+            return;
+        };
+        let identity = Arc::new(FakeIdentityProvider::default());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(pool.clone()))
+                .app_data(web::Data::from(
+                    Arc::new(UidIsToken) as Arc<dyn TokenVerifier>
+                ))
+                .app_data(web::Data::from(
+                    identity.clone() as Arc<dyn IdentityProvider>
+                ))
+                .configure(user_endpoints::user_routes)
+                .configure(professional_endpoints::professional_routes),
+        )
+        .await;
+
+        for (uri, email, role) in [
+            (
+                "/users/register",
+                "policy-new-customer@example.com",
+                "customer",
+            ),
+            (
+                "/professionals/register",
+                "policy-new-professional@example.com",
+                "professional",
+            ),
+        ] {
+            let registration = serde_json::json!({
+                "email": email,
+                "password": "secret123",
+                "name": "New User",
+            });
+            let request = test::TestRequest::post()
+                .uri(uri)
+                .set_json(registration)
+                .to_request();
+            assert_eq!(
+                test::call_service(&app, request).await.status(),
+                StatusCode::OK,
+                "POST {}",
+                uri
+            );
+
+            let account = identity.account(email).expect("account was not created");
+            let roles: serde_json::Value = test::call_and_read_body_json(
+                &app,
+                request_as(
+                    Method::GET,
+                    &format!("/users/{}/roles", account.uid),
+                    &account.uid,
+                )
+                .to_request(),
+            )
+            .await;
+            assert_eq!(roles["roles"], serde_json::json!([role]));
+        }
+    }
+}
@@ -0,0 +1,297 @@
+//! Shared setup for tests that talk to a real database.
+//!
+//! Such tests run only when `TEST_DATABASE_URL` names a database with the
+//! migrations applied; everything they write is rolled back afterwards.
+
+use crate::db::Pool;
+use crate::errors::auth_errors::AuthError;
+use crate::middleware::token_verifier::{TokenVerifier, VerifyFuture};
+use crate::models::booking_aggregate::booking_status::BookingStatus;
+use crate::models::task_aggregate::task_status::TaskStatus;
+use crate::models::user_aggregate::role::Role;
+use crate::schema::schema::{
+    bookings, categories, chat, message, professional_profiles, professionals, service_offerings,
+    subcategories, task, user_roles, users,
+};
+use actix_web::http::Method;
+use actix_web::test;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, CustomizeConnection};
+use std::env;
+
+pub const CUSTOMER: &str = "policy-customer";
+pub const PROFESSIONAL: &str = "policy-professional";
+pub const STRANGER: &str = "policy-stranger";
+pub const ADMIN: &str = "policy-admin";
+pub const PROFILE_UID: &str = "policy-profile";
+
+/// Treats every token as the uid it names, so tests can act as anyone.
+pub struct UidIsToken;
+
+impl TokenVerifier for UidIsToken {
+    fn verify<'a>(&'a self, token: &'a str) -> VerifyFuture<'a> {
+        Box::pin(async move {
+            if token.is_empty() {
+                Err(AuthError::InvalidToken)
+            } else {
+                Ok(token.to_string())
+            }
+        })
+    }
+}
+
+/// Keeps everything a test writes inside one transaction that is rolled
+/// back when the pool's only connection is dropped.
+#[derive(Debug)]
+struct TestTransaction;
+
+impl CustomizeConnection<PgConnection, diesel::r2d2::Error> for TestTransaction {
+    fn on_acquire(&self, conn: &mut PgConnection) -> Result<(), diesel::r2d2::Error> {
+        conn.begin_test_transaction()
+            .map_err(diesel::r2d2::Error::QueryError)
+    }
+}
+
+/// A pool on the test database, or `None` if `TEST_DATABASE_URL` is unset
+/// and the calling test should be skipped.
+pub fn test_pool() -> Option<Pool> {
+    let Ok(database_url) = env::var("TEST_DATABASE_URL") else {
+        eprintln!("TEST_DATABASE_URL is not set, skipping database test");
+        return None;
+    };
+    let pool = Pool::builder()
+        .max_size(1)
+        .connection_customizer(Box::new(TestTransaction))
+        .build(ConnectionManager::new(database_url))
+        .expect("Failed to create pool.");
+    Some(pool)
+}
+
+pub struct Fixture {
+    pub category_id: i32,
+    pub profile_id: i32,
+    pub offering_id: i32,
+    pub chat_id: i32,
+    pub message_id: i32,
+    pub booking_id: i32,
+    pub task_id: i32,
+}
+
+/// A chat between `CUSTOMER` and the profile of `PROFESSIONAL`, with one
+/// message to the customer, one booking and one task of the customer. The
+/// profile has one offering and no business hours.
+/// `STRANGER` holds both roles but takes part in none of it.
+pub fn insert_fixture(pool: &Pool) -> Fixture {
+    let conn = &mut pool.get().expect("Failed to get DB connection");
+    let now = Utc::now();
+
+    diesel::insert_into(users::table)
+        .values((
+            users::name.eq("Policy Customer"),
+            users::email.eq("policy-customer@example.com"),
+            users::user_uid.eq(CUSTOMER),
+        ))
+        .execute(conn)
+        .unwrap();
+    for (user_uid, role) in [
+        (CUSTOMER, Role::Customer),
+        (PROFESSIONAL, Role::Professional),
+        (STRANGER, Role::Customer),
+        (STRANGER, Role::Professional),
+        (ADMIN, Role::Admin),
+    ] {
+        diesel::insert_into(user_roles::table)
+            .values((
+                user_roles::user_uid.eq(user_uid),
+                user_roles::role.eq(i32::from(role)),
+            ))
+            .execute(conn)
+            .unwrap();
+    }
+
+    let category_id: i32 = diesel::insert_into(categories::table)
+        .values(categories::name.eq("Policy tests"))
+        .returning(categories::id)
+        .get_result(conn)
+        .unwrap();
+    let professional_id: i32 = diesel::insert_into(professionals::table)
+        .values((
+            professionals::name.eq("Policy Professional"),
+            professionals::email.eq("policy-professional@example.com"),
+            professionals::user_uid.eq(PROFESSIONAL),
+        ))
+        .returning(professionals::id)
+        .get_result(conn)
+        .unwrap();
+    let profile_id: i32 = diesel::insert_into(professional_profiles::table)
+        .values((
+            professional_profiles::professional_id.eq(professional_id),
+            professional_profiles::category_id.eq(category_id),
+            professional_profiles::delivery_enabled.eq(false),
+            professional_profiles::remote_available.eq(false),
+            professional_profiles::professional_name.eq("Policy Professional"),
+            professional_profiles::professional_profile_uid.eq(PROFILE_UID),
+            professional_profiles::review_count.eq(0),
+            professional_profiles::timezone.eq("Europe/Berlin"),
+        ))
+        .returning(professional_profiles::id)
+        .get_result(conn)
+        .unwrap();
+    let subcategory_id: i32 = diesel::insert_into(subcategories::table)
+        .values((
+            subcategories::name.eq("Policy consultations"),
+            subcategories::category_id.eq(category_id),
+        ))
+        .returning(subcategories::id)
+        .get_result(conn)
+        .unwrap();
+    let offering_id: i32 = diesel::insert_into(service_offerings::table)
+        .values((
+            service_offerings::professional_profile_id.eq(profile_id),
+            service_offerings::subcategory_id.eq(subcategory_id),
+            service_offerings::price.eq(50.0),
+            service_offerings::subcategory_name.eq("Policy consultations"),
+            service_offerings::pricing_unit.eq(0),
+            service_offerings::estimated_duration_minutes.eq(60),
+        ))
+        .returning(service_offerings::id)
+        .get_result(conn)
+        .unwrap();
+    let chat_id: i32 = diesel::insert_into(chat::table)
+        .values((
+            chat::last_message_time.eq(now.naive_utc()),
+            chat::user_uid.eq(CUSTOMER),
+            chat::professional_profile_uid.eq(PROFILE_UID),
+        ))
+        .returning(chat::id)
+        .get_result(conn)
+        .unwrap();
+    let message_id: i32 = diesel::insert_into(message::table)
+        .values((
+            message::chat_id.eq(chat_id),
+            message::text.eq("Hello"),
+            message::timestamp.eq(now.naive_utc()),
+            message::is_read.eq(false),
+            message::receiver_uid.eq(CUSTOMER),
+            message::sender_uid.eq(PROFESSIONAL),
+        ))
+        .returning(message::id)
+        .get_result(conn)
+        .unwrap();
+    let booking_id: i32 = diesel::insert_into(bookings::table)
+        .values((
+            bookings::customer_uid.eq(CUSTOMER),
+            bookings::professional_profile_uid.eq(PROFILE_UID),
+            bookings::status.eq(i32::from(BookingStatus::Proposed)),
+            bookings::category_id.eq(category_id),
+            bookings::offering_price.eq(50.0),
+            bookings::chat_id.eq(chat_id),
+            bookings::creation_time.eq(now),
+        ))
+        .returning(bookings::id)
+        .get_result(conn)
+        .unwrap();
+    let task_id: i32 = diesel::insert_into(task::table)
+        .values((
+            task::user_uid.eq(CUSTOMER),
+            task::creation_time.eq(now),
+            task::title.eq("Fix the sink"),
+            task::is_flexible_timing.eq(true),
+            task::category_id.eq(category_id),
+            task::status.eq(i32::from(TaskStatus::Open)),
+        ))
+        .returning(task::id)
+        .get_result(conn)
+        .unwrap();
+
+    Fixture {
+        category_id,
+        profile_id,
+        offering_id,
+        chat_id,
+        message_id,
+        booking_id,
+        task_id,
+    }
+}
+
+/// An app serving the routes under test, with tokens naming their uid.
+macro_rules! test_app {
+    ($pool:expr) => {
+        actix_web::test::init_service(
+            actix_web::App::new()
+                .app_data(actix_web::web::Data::new($pool.clone()))
+                .app_data(actix_web::web::Data::from(std::sync::Arc::new(
+                    $crate::test_support::UidIsToken,
+                )
+                    as std::sync::Arc<dyn $crate::middleware::token_verifier::TokenVerifier>))
+                .configure($crate::services::user_services::user_endpoints::user_routes)
+                .configure(
+                    $crate::services::professional_profile_services::professional_profile_endpoints::professional_profile_routes,
+                )
+                .configure($crate::services::chat_services::chat_endpoints::chat_routes)
+                .configure($crate::services::booking_services::booking_endpoints::booking_routes)
+                .configure($crate::services::task_services::task_endpoints::task_routes),
+        )
+        .await
+    };
+}
+pub(crate) use test_app;
+
+/// An app that authenticates like production: ID tokens of the fake
+/// identity provider and, if given, session tokens.
+macro_rules! identity_app {
+    ($pool:expr, $identity:expr, $sessions:expr) => {{
+        let identity: actix_web::web::Data<
+            dyn $crate::services::identity_provider::IdentityProvider,
+        > = actix_web::web::Data::from(
+            $identity.clone()
+                as std::sync::Arc<dyn $crate::services::identity_provider::IdentityProvider>,
+        );
+        let verifier =
+            $crate::middleware::token_verifier::BearerTokenVerifier::new(identity.clone(), $sessions);
+        actix_web::test::init_service(
+            actix_web::App::new()
+                .app_data(actix_web::web::Data::new($pool.clone()))
+                .app_data(identity)
+                .app_data(actix_web::web::Data::from(std::sync::Arc::new(verifier)
+                    as std::sync::Arc<dyn $crate::middleware::token_verifier::TokenVerifier>))
+                .configure($crate::services::user_services::user_endpoints::user_routes)
+                .configure(
+                    $crate::services::professional_profile_services::professional_profile_endpoints::professional_profile_routes,
+                ),
+        )
+        .await
+    }};
+}
+pub(crate) use identity_app;
+
+/// The status `$app` answers with to `$request`.
+macro_rules! status_of {
+    ($app:expr, $request:expr) => {
+        actix_web::test::call_service(&$app, $request.to_request())
+            .await
+            .status()
+    };
+}
+pub(crate) use status_of;
+
+pub fn request_as(method: Method, uri: &str, user_uid: &str) -> test::TestRequest {
+    test::TestRequest::default()
+        .method(method)
+        .uri(uri)
+        .insert_header(("Authorization", format!("Bearer {}", user_uid)))
+}
+
+/// A booking request for the fixture's profile between `start` and `end`.
+pub fn booking_request(fixture: &Fixture, start: &str, end: Option<&str>) -> serde_json::Value {
+    serde_json::json!({
+        "customer_uid": STRANGER,
+        "professional_profile_uid": PROFILE_UID,
+        "date_time": start,
+        "end_time": end,
+        "status": 0,
+        "offering_id": fixture.offering_id,
+    })
+}